@@ -0,0 +1,52 @@
+use std::env;
+use std::io;
+use std::io::{BufRead, Write};
+use std::process;
+use aritexpr::cli::{parse_args, calc, CalcArgs, USAGE};
+
+fn main() {
+    let args = parse_args(env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    });
+
+    let mut success = true;
+    if args.repl {
+        let stdin = io::stdin();
+        loop {
+            print!("> ");
+            io::stdout().flush().expect("flush stdout");
+            let mut line = String::new();
+            if stdin.lock().read_line(&mut line).expect("read stdin") == 0 {
+                break;
+            }
+            print_calc(&args, line.trim());
+        }
+    } else if args.inputs.is_empty() {
+        for line in io::stdin().lock().lines() {
+            success &= print_calc(&args, line.expect("read stdin").trim());
+        }
+    } else {
+        for input in &args.inputs {
+            success &= print_calc(&args, input);
+        }
+    }
+
+    if !success {
+        process::exit(1);
+    }
+}
+
+fn print_calc(args: &CalcArgs, input: &str) -> bool {
+    match calc(args, input) {
+        Ok(output) => {
+            println!("{}", output);
+            true
+        },
+        Err(err) => {
+            eprintln!("{}", err);
+            false
+        },
+    }
+}
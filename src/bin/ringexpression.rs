@@ -1,26 +1,44 @@
 use std::env;
-use aritexpr::expression::parser::parse_int_ring_expression;
+use std::process;
+use aritexpr::error::AritError;
+use aritexpr::evaluate::{evaluate_in_ring, ring_display_name, RINGS};
+use aritexpr::expression::ring::RingErrorKind;
 
 fn main() {
     let mut args= env::args();
     args.next().expect("What");
-    let str = args.next().expect("No argument");
-    match parse_int_ring_expression(&str) {
-        Ok(expr) => {
-            match expr.evaluate() {
-                Ok(element) => {
-                    println!("Result: {}" , element);
-                },
-                Err(err) => {
-                    eprintln!("{}: {}", err.message, str);
-                    // eprintln!("{:>1$}", "^", err.message.len() + err.p.);
-                },
-            };
+    let mut ring = "int".to_string();
+    let mut str = args.next().unwrap_or_else(|| usage());
+    if str == "--ring" {
+        ring = args.next().unwrap_or_else(|| usage());
+        str = args.next().unwrap_or_else(|| usage());
+    }
+    match evaluate_in_ring(&ring, &str) {
+        Ok(element) => {
+            println!("Result: {}" , element);
         },
-        Err(err) => {
+        Err(AritError::Evaluate(err)) => {
+            eprintln!("{}: {}", err.message, str);
+            if let Some(position) = err.position {
+                eprintln!("{:>1$}", "^", err.message.len() + position + 3);
+            }
+            if let RingErrorKind::NotInRing { suggested_ring: Some(suggested_ring), .. } = err.kind {
+                eprintln!("hint: this expression isn't exact in {}; try --ring {}", ring_display_name(&ring).unwrap_or(&ring), suggested_ring);
+            }
+        },
+        Err(AritError::Parse(err)) => {
             eprintln!("{}: {}", err.message, str);
             eprintln!("{:>1$}", "^", err.message.len() + err.position + 3);
         },
+        Err(err) => {
+            eprintln!("{}", err);
+            usage();
+        },
     };
 
 }
+
+fn usage() -> ! {
+    eprintln!("Usage: ringexpression [--ring {}] <expression>", RINGS.join("|"));
+    process::exit(2);
+}
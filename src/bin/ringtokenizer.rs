@@ -1,15 +1,26 @@
 use std::env;
-use aritexpr::token::intring::IntRingTokenParser;
+use aritexpr::token::intring::{IntRingTokenParser, token_stats};
 use aritexpr::token::TokenIterator;
 use itertools::Itertools;
 
 fn main() {
     let mut args= env::args();
     args.next().expect("What");
-    let str = args.next().expect("No argument");
+    let mut str = args.next().expect("No argument");
+    let stats = str == "--stats";
+    if stats {
+        str = args.next().expect("No argument");
+    }
     let iter = TokenIterator::new(&str, IntRingTokenParser::new());
     let tokens_result: Result<Vec<_>, _> = iter.collect();
     match tokens_result {
+        Ok(tokens) if stats => {
+            let stats = token_stats(&tokens);
+            println!("Tokens: {}", stats.tokens);
+            println!("Values: {}", stats.values);
+            println!("Operators: {}", stats.operators);
+            println!("Parentheses: {}", stats.parentheses);
+        },
         Ok(tokens) => println!("Tokens: {}", tokens.iter().map(|wp| &wp.token).format(" ")),
         Err(err) => {
             eprintln!("{}: {}", err.message, str);
@@ -0,0 +1,183 @@
+use crate::evaluate::{evaluate_in_ring, RINGS};
+use crate::token::TokenIterator;
+use crate::token::intring::IntRingTokenParser;
+use itertools::Itertools;
+use std::fmt::{Display, Formatter, Write};
+use std::error;
+
+/// Arguments for the `ringcalc` binary.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct CalcArgs {
+    /// Name of the ring to evaluate in, see [RINGS].
+    pub ring: String,
+    /// Print results as JSON objects.
+    pub json: bool,
+    /// Print tokens instead of evaluating.
+    pub tokens: bool,
+    /// Read expressions interactively.
+    pub repl: bool,
+    /// Expressions given as arguments. If empty and not [CalcArgs::repl], expressions are read
+    /// from stdin, one per line.
+    pub inputs: Vec<String>,
+}
+
+impl Default for CalcArgs {
+    fn default() -> Self {
+        CalcArgs {
+            ring: "int".to_string(),
+            json: false,
+            tokens: false,
+            repl: false,
+            inputs: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ArgsError {
+    pub message: String,
+}
+
+impl Display for ArgsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl error::Error for ArgsError {
+}
+
+pub const USAGE: &str = "Usage: ringcalc [--ring <name>] [--json] [--tokens] [--repl] [<expression>...]";
+
+/// Parse `args`, not including the program name.
+pub fn parse_args(args: impl IntoIterator<Item=String>) -> Result<CalcArgs, ArgsError> {
+    let mut calc_args = CalcArgs::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ring" => {
+                calc_args.ring = args.next().ok_or_else(|| ArgsError { message: "Missing ring name for --ring".to_string() })?;
+                if !RINGS.contains(&calc_args.ring.as_str()) {
+                    return Err(ArgsError { message: format!("Unknown ring {}, expected one of {}", calc_args.ring, RINGS.iter().format(", ")) });
+                }
+            }
+            "--json" => calc_args.json = true,
+            "--tokens" => calc_args.tokens = true,
+            "--repl" => calc_args.repl = true,
+            "--" => calc_args.inputs.extend(args.by_ref()),
+            option if option.starts_with("--") => {
+                return Err(ArgsError { message: format!("Unknown option {}", option) });
+            }
+            _ => calc_args.inputs.push(arg),
+        }
+    }
+    Ok(calc_args)
+}
+
+/// Evaluate or tokenize `input` as specified by `args`. Input is tokenized as it would be when
+/// parsed in the selected ring. Returns the text to print on success, or the error text.
+pub fn calc(args: &CalcArgs, input: &str) -> Result<String, String> {
+    let result = if args.tokens {
+        let big_integers = args.ring == "bigint";
+        TokenIterator::new(&input, IntRingTokenParser::new().with_big_integers(big_integers))
+            .map_ok(|token| token.token)
+            .collect::<Result<Vec<_>, _>>()
+            .map(|tokens| tokens.iter().format(" ").to_string())
+            .map_err(|err| err.to_string())
+    } else {
+        evaluate_in_ring(&args.ring, input).map_err(|err| err.to_string())
+    };
+
+    if args.json {
+        match result {
+            Ok(value) => Ok(format!("{{\"input\": {}, \"result\": {}}}", json_string(input), json_string(&value))),
+            Err(err) => Err(format!("{{\"input\": {}, \"error\": {}}}", json_string(input), json_string(&err))),
+        }
+    } else {
+        result
+    }
+}
+
+fn json_string(str: &str) -> String {
+    let mut json = String::with_capacity(str.len() + 2);
+    json.push('"');
+    for c in str.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).expect("writing to string"),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::{parse_args, CalcArgs, calc, ArgsError};
+
+    fn args(args: &[&str]) -> Result<CalcArgs, ArgsError> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parse_ring_json() {
+        assert_eq!(Ok(CalcArgs {
+            ring: "rational".to_string(),
+            json: true,
+            inputs: vec!["5/2".to_string()],
+            ..CalcArgs::default()
+        }), args(&["--ring", "rational", "--json", "5/2"]));
+    }
+
+    #[test]
+    fn parse_multiple_inputs() {
+        assert_eq!(Ok(CalcArgs {
+            tokens: true,
+            inputs: vec!["1 + 2".to_string(), "--json".to_string()],
+            ..CalcArgs::default()
+        }), args(&["1 + 2", "--tokens", "--", "--json"]));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Err(ArgsError { message: "Missing ring name for --ring".to_string() }), args(&["--ring"]));
+        assert_eq!(Err(ArgsError { message: "Unknown option --hest".to_string() }), args(&["--hest"]));
+        assert_eq!(Err(ArgsError { message: "Unknown ring hest, expected one of int, rational, bigint".to_string() }),
+                   args(&["--ring", "hest", "1"]));
+    }
+
+    #[test]
+    fn calc_evaluate() {
+        let calc_args = CalcArgs::default();
+
+        assert_eq!(Ok("7".to_string()), calc(&calc_args, "2 + 5"));
+    }
+
+    #[test]
+    fn calc_tokens() {
+        let calc_args = CalcArgs { tokens: true, ..CalcArgs::default() };
+
+        assert_eq!(Ok("( 2 + 5 )".to_string()), calc(&calc_args, "(2+5)"));
+    }
+
+    #[test]
+    fn calc_tokens_in_ring() {
+        let int_args = CalcArgs { tokens: true, ..CalcArgs::default() };
+        let big_int_args = CalcArgs { tokens: true, ring: "bigint".to_string(), ..CalcArgs::default() };
+
+        assert!(calc(&int_args, "100000000000000000000 + 1").is_err());
+        assert_eq!(Ok("100000000000000000000 + 1".to_string()), calc(&big_int_args, "100000000000000000000 + 1"));
+    }
+
+    #[test]
+    fn calc_json() {
+        let calc_args = CalcArgs { json: true, ..CalcArgs::default() };
+
+        assert_eq!(Ok(r#"{"input": "2 + 5", "result": "7"}"#.to_string()), calc(&calc_args, "2 + 5"));
+        assert_eq!(Err(r#"{"input": "5 / \"", "error": "Error parsing expression at position 4: Unexpected '\"'; expected a digit, a letter, an operator or a parenthesis"}"#.to_string()),
+                   calc(&calc_args, "5 / \""));
+    }
+}
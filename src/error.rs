@@ -0,0 +1,62 @@
+use crate::expression::parser::ParseExpressionError;
+use crate::expression::EvaluateExpressionError;
+use std::fmt::{Display, Formatter};
+use std::error;
+
+/// Error parsing or evaluating an expression.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum AritError {
+    Parse(ParseExpressionError),
+    Evaluate(EvaluateExpressionError),
+    /// The named ring is not supported, see [RINGS](crate::evaluate::RINGS).
+    UnknownRing(String),
+    /// The expression has more nodes than allowed by [SafetyLimits](crate::evaluate::SafetyLimits).
+    TooManyNodes { limit: usize },
+    /// The expression is nested deeper than allowed by [SafetyLimits](crate::evaluate::SafetyLimits).
+    TooDeep { limit: usize },
+    /// The expression has more operations than allowed by [SafetyLimits](crate::evaluate::SafetyLimits).
+    TooManyOperations { limit: usize },
+    /// A literal or an intermediate value has more bits than allowed by
+    /// [SafetyLimits](crate::evaluate::SafetyLimits).
+    IntegerTooWide { limit: u32 },
+}
+
+impl Display for AritError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AritError::Parse(err) => err.fmt(f),
+            AritError::Evaluate(err) => err.fmt(f),
+            AritError::UnknownRing(ring) => write!(f, "Unknown ring: {}", ring),
+            AritError::TooManyNodes { limit } => write!(f, "Expression has more than {} nodes", limit),
+            AritError::TooDeep { limit } => write!(f, "Expression is nested deeper than {}", limit),
+            AritError::TooManyOperations { limit } => write!(f, "Expression has more than {} operations", limit),
+            AritError::IntegerTooWide { limit } => write!(f, "Integer wider than {} bits", limit),
+        }
+    }
+}
+
+impl error::Error for AritError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            AritError::Parse(err) => Some(err),
+            AritError::Evaluate(err) => Some(err),
+            AritError::UnknownRing(_)
+            | AritError::TooManyNodes { .. }
+            | AritError::TooDeep { .. }
+            | AritError::TooManyOperations { .. }
+            | AritError::IntegerTooWide { .. } => None,
+        }
+    }
+}
+
+impl From<ParseExpressionError> for AritError {
+    fn from(err: ParseExpressionError) -> Self {
+        AritError::Parse(err)
+    }
+}
+
+impl From<EvaluateExpressionError> for AritError {
+    fn from(err: EvaluateExpressionError) -> Self {
+        AritError::Evaluate(err)
+    }
+}
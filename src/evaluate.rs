@@ -0,0 +1,273 @@
+use crate::error::AritError;
+use crate::expression::parser::{parse_int_ring_expression, parse_rational_ring_expression, parse_big_int_ring_expression};
+use crate::expression::ring::intring::{IntRing, IntRingElement};
+use crate::expression::ring::rational::RationalRingElement;
+use crate::expression::ring::{RingError, RingErrorKind};
+use crate::expression::visit::ExpressionVisitor;
+use crate::expression::{ExpressionComponent, Operator};
+
+/// Parse and evaluate `str` as an [IntRing] expression.
+pub fn evaluate_int_ring_expression(str: impl AsRef<str>) -> Result<IntRingElement, AritError> {
+    Ok(parse_int_ring_expression(str)?.evaluate()?)
+}
+
+/// Parse and evaluate `str` as a [RationalRing](crate::expression::ring::rational::RationalRing) expression.
+pub fn evaluate_rational_ring_expression(str: impl AsRef<str>) -> Result<RationalRingElement, AritError> {
+    Ok(parse_rational_ring_expression(str)?.evaluate()?)
+}
+
+/// Limits for evaluating expressions from untrusted input with [safe_evaluate_int].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct SafetyLimits {
+    /// Maximum number of nodes, see [node_count](crate::expression::ExpressionComponent::node_count).
+    pub max_nodes: usize,
+    /// Maximum depth, see [depth](crate::expression::ExpressionComponent::depth).
+    pub max_depth: usize,
+    /// Maximum number of operations, counting binary operations, negations and function calls.
+    pub max_operations: usize,
+    /// Maximum number of bits, including the sign bit, of literals and of the values computed
+    /// during evaluation. Limits of 64 or more allow all `i64` values.
+    pub max_integer_bits: u32,
+}
+
+/// Parse and evaluate `str` as an [IntRing] expression from untrusted input. The limits on
+/// nodes, depth and operations are checked in a single pass after parsing, before anything is
+/// evaluated. The limit on integer width is checked for literals, and for the operands and
+/// results of the operations while evaluating, such that evaluation stops at the first value too
+/// wide. Each breached limit is reported by its own [AritError] variant.
+pub fn safe_evaluate_int(str: impl AsRef<str>, limits: &SafetyLimits) -> Result<i64, AritError> {
+    let expression = parse_int_ring_expression(str)?;
+    let statistics = OperationStatistics::of(&expression, limits.max_integer_bits);
+    if statistics.nodes > limits.max_nodes {
+        return Err(AritError::TooManyNodes { limit: limits.max_nodes });
+    }
+    if statistics.depth > limits.max_depth {
+        return Err(AritError::TooDeep { limit: limits.max_depth });
+    }
+    if statistics.operations > limits.max_operations {
+        return Err(AritError::TooManyOperations { limit: limits.max_operations });
+    }
+    let too_wide = Err(AritError::IntegerTooWide { limit: limits.max_integer_bits });
+    if !statistics.literals_fit {
+        return too_wide;
+    }
+    // operands are checked too, since the results of negations and function calls are not observed
+    let mut values_fit = true;
+    let value = expression.evaluate_with_checked_observer(&mut |event| {
+        let result_fits = event.result.as_ref().map_or(true, |result| fits_bits(result.value(), limits.max_integer_bits));
+        values_fit = fits_bits(event.left.value(), limits.max_integer_bits)
+            && fits_bits(event.right.value(), limits.max_integer_bits)
+            && result_fits;
+        if values_fit {
+            Ok(())
+        } else {
+            Err(RingError { message: format!("Integer wider than {} bits", limits.max_integer_bits), kind: RingErrorKind::Overflow })
+        }
+    });
+    if !values_fit {
+        return too_wide;
+    }
+    let value = value?.value();
+    if !fits_bits(value, limits.max_integer_bits) {
+        return too_wide;
+    }
+    Ok(value)
+}
+
+/// Whether `value` is in the range of a signed integer of `bits` bits.
+fn fits_bits(value: i64, bits: u32) -> bool {
+    if bits >= 64 {
+        return true;
+    }
+    let bound = 1i64 << bits.saturating_sub(1);
+    bits > 0 && -bound <= value && value < bound
+}
+
+/// Statistics checked against [SafetyLimits] before evaluating.
+struct OperationStatistics {
+    nodes: usize,
+    depth: usize,
+    operations: usize,
+    literals_fit: bool,
+    max_integer_bits: u32,
+}
+
+impl OperationStatistics {
+    fn of(expression: &ExpressionComponent<IntRing>, max_integer_bits: u32) -> OperationStatistics {
+        let mut statistics = OperationStatistics { nodes: 0, depth: 0, operations: 0, literals_fit: true, max_integer_bits };
+        expression.visit(&mut statistics);
+        statistics
+    }
+}
+
+impl ExpressionVisitor<IntRing> for OperationStatistics {
+    fn visit_node(&mut self, _expression: &ExpressionComponent<IntRing>, depth: usize) {
+        self.nodes += 1;
+        self.depth = self.depth.max(depth);
+    }
+
+    fn visit_ring_element(&mut self, element: &IntRingElement, _depth: usize) {
+        self.literals_fit &= fits_bits(element.value(), self.max_integer_bits);
+    }
+
+    fn visit_function_call(&mut self, _name: &str, _arguments: usize, _depth: usize) {
+        self.operations += 1;
+    }
+
+    fn visit_unary_minus(&mut self, _depth: usize) {
+        self.operations += 1;
+    }
+
+    fn visit_binary_operation(&mut self, _operator: Operator, _depth: usize) {
+        self.operations += 1;
+    }
+}
+
+/// Names of the rings supported by [evaluate_in_ring].
+pub const RINGS: &[&str] = &["int", "rational", "bigint"];
+
+/// Readable name of the ring named `ring` in [RINGS], e.g. `integers` for `int`, for messages to
+/// the user.
+pub fn ring_display_name(ring: &str) -> Option<&'static str> {
+    match ring {
+        "int" | "bigint" => Some("integers"),
+        "rational" => Some("rationals"),
+        _ => None,
+    }
+}
+
+/// Parse and evaluate `str` as an expression in the ring named `ring`, see [RINGS]. Returns the
+/// resulting ring element formatted as a string.
+pub fn evaluate_in_ring(ring: &str, str: impl AsRef<str>) -> Result<String, AritError> {
+    match ring {
+        "int" => Ok(evaluate_int_ring_expression(str)?.to_string()),
+        "rational" => Ok(evaluate_rational_ring_expression(str)?.to_string()),
+        "bigint" => Ok(parse_big_int_ring_expression(str)?.evaluate()?.to_string()),
+        _ => Err(AritError::UnknownRing(ring.to_string())),
+    }
+}
+
+/// Parse and evaluate each of the independent expressions in `inputs`. The results are returned
+/// in the order of the inputs.
+pub fn evaluate_many(inputs: &[&str]) -> Vec<Result<IntRingElement, AritError>> {
+    inputs.iter().map(evaluate_int_ring_expression).collect()
+}
+
+/// Parse and evaluate each of the independent expressions in `inputs`, and call `sink` with the
+/// index of the input and its result as soon as it is evaluated, such that e.g. a user interface
+/// can show results incrementally. Inputs are evaluated in order on the calling thread.
+pub fn evaluate_many_streaming(inputs: Vec<String>, mut sink: impl FnMut(usize, Result<IntRingElement, AritError>)) {
+    for (index, input) in inputs.iter().enumerate() {
+        sink(index, evaluate_int_ring_expression(input));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::AritError;
+    use crate::evaluate::{evaluate_int_ring_expression, evaluate_many, evaluate_many_streaming, evaluate_in_ring, safe_evaluate_int, SafetyLimits};
+    use crate::evaluate::{ring_display_name, RINGS};
+    use crate::expression::ring::intring::IntRingElement;
+    use crate::expression::EvaluateExpressionError;
+    use crate::expression::parser::ParseExpressionError;
+    use crate::expression::parser::ParseExpressionErrorKind::{NoExpression, UnexpectedEndOfInput};
+    use crate::expression::ring::DivisionRemainder;
+    use crate::expression::ring::RingErrorKind::NotInRing;
+
+    #[test]
+    fn evaluate() {
+        assert_eq!(Ok(IntRingElement::new(7)), evaluate_int_ring_expression("2 + 5"));
+    }
+
+    #[test]
+    fn evaluate_many_in_order() {
+        let results = evaluate_many(&["2 + 5", "", "5 / 2", "3 * 4"]);
+
+        assert_eq!(vec![
+            Ok(IntRingElement::new(7)),
+            Err(AritError::Parse(ParseExpressionError{message: "No expression".to_string(), position: 0, kind: NoExpression})),
+            Err(AritError::Evaluate(EvaluateExpressionError{message: "Result not in ring during division".to_string(), kind: NotInRing { suggested_ring: Some("rational"), remainder: Some(Box::new(DivisionRemainder { quotient: "2".to_string(), remainder: "1".to_string() })) }, position: Some(2), span: Some(0..5)})),
+            Ok(IntRingElement::new(12)),
+        ], results);
+    }
+
+    #[test]
+    fn evaluate_many_streaming_in_order() {
+        let inputs = vec!["2 + 5".to_string(), "2 +".to_string(), "3 * 4".to_string()];
+        let mut invocations = Vec::new();
+
+        evaluate_many_streaming(inputs, |index, result| invocations.push((index, result)));
+
+        assert_eq!(vec![
+            (0, Ok(IntRingElement::new(7))),
+            (1, Err(AritError::Parse(ParseExpressionError{message: "Unexpected end of input".to_string(), position: 3, kind: UnexpectedEndOfInput}))),
+            (2, Ok(IntRingElement::new(12))),
+        ], invocations);
+    }
+
+    #[test]
+    fn evaluate_in_int_ring() {
+        assert_eq!(Ok("7".to_string()), evaluate_in_ring("int", "2 + 5"));
+    }
+
+    #[test]
+    fn evaluate_in_rational_ring() {
+        assert_eq!(Ok("5/2".to_string()), evaluate_in_ring("rational", "5 / 2"));
+    }
+
+    #[test]
+    fn evaluate_in_big_int_ring() {
+        assert_eq!(Ok("1267650600228229401496703205376".to_string()), evaluate_in_ring("bigint", "2 ^ 100"));
+    }
+
+    #[test]
+    fn evaluate_in_unknown_ring() {
+        assert_eq!(Err(AritError::UnknownRing("complex".to_string())), evaluate_in_ring("complex", "5 / 2"));
+    }
+
+    #[test]
+    fn ring_display_names() {
+        assert!(RINGS.iter().all(|ring| ring_display_name(ring).is_some()));
+        assert_eq!(Some("integers"), ring_display_name("int"));
+        assert_eq!(None, ring_display_name("complex"));
+    }
+
+    const LIMITS: SafetyLimits = SafetyLimits { max_nodes: 10, max_depth: 5, max_operations: 3, max_integer_bits: 8 };
+
+    #[test]
+    fn safe_evaluate_within_limits() {
+        assert_eq!(Ok(-121), safe_evaluate_int("-(100 + 21) + 0", &LIMITS));
+        assert_eq!(Ok(i64::MIN), safe_evaluate_int("-9223372036854775807 - 1", &SafetyLimits { max_integer_bits: 64, ..LIMITS }));
+    }
+
+    #[test]
+    fn safe_evaluate_too_many_nodes() {
+        assert_eq!(Err(AritError::TooManyNodes { limit: 10 }), safe_evaluate_int("1 + 2 + 3 + 4 + 5 + 6", &LIMITS));
+    }
+
+    #[test]
+    fn safe_evaluate_too_deep() {
+        assert_eq!(Err(AritError::TooDeep { limit: 5 }), safe_evaluate_int("(((((1)))))", &LIMITS));
+    }
+
+    #[test]
+    fn safe_evaluate_too_many_operations() {
+        assert_eq!(Err(AritError::TooManyOperations { limit: 3 }), safe_evaluate_int("1 + 2 * 3 - 4 * 5", &LIMITS));
+    }
+
+    #[test]
+    fn safe_evaluate_integer_too_wide() {
+        let too_wide = Err(AritError::IntegerTooWide { limit: 8 });
+
+        assert_eq!(too_wide, safe_evaluate_int("128 - 1", &LIMITS));
+        assert_eq!(too_wide, safe_evaluate_int("100 + 100 - 100", &LIMITS));
+        assert_eq!(too_wide, safe_evaluate_int("-(-128)", &LIMITS));
+        assert_eq!(Ok(-128), safe_evaluate_int("-128", &LIMITS));
+    }
+
+    #[test]
+    fn safe_evaluate_errors() {
+        assert!(matches!(safe_evaluate_int("1 +", &LIMITS), Err(AritError::Parse(_))));
+        assert!(matches!(safe_evaluate_int("1 / 0", &LIMITS), Err(AritError::Evaluate(_))));
+    }
+}
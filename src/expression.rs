@@ -1,247 +1,2356 @@
-use std::fmt::{Formatter};
-use std::{error, result};
-use core::fmt;
-use crate::expression::ring::{Ring, RingError, RingResult};
-use crate::expression::ExpressionComponent::{RingElement, Addition, Subtraction, Multiplication, Division, Parentheses, UnaryMinus};
-use std::ops::DerefMut;
-
-pub mod ring;
-pub mod parser;
-
-#[derive(Debug, PartialEq, Eq, Clone, Hash)]
-pub struct EvaluateExpressionError {
-    pub message: String,
-    // pub position: usize
-}
-
-impl fmt::Display for EvaluateExpressionError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Error evaluating expression: {}", self.message)
-    }
-}
-
-impl error::Error for EvaluateExpressionError {
-}
-
-impl From<RingError> for EvaluateExpressionError {
-    fn from(err: RingError) -> Self {
-        EvaluateExpressionError {
-            message: err.message
-        }
-    }
-}
-
-pub type EvaluateExpressionResult<T> = result::Result<T, EvaluateExpressionError>;
-
-#[derive(Debug, PartialEq, Eq, Clone, Hash)]
-pub enum ExpressionComponent<R: Ring> {
-    RingElement(R::RingElementType),
-    Parentheses(Box<ExpressionComponent<R>>),
-    UnaryMinus(Box<ExpressionComponent<R>>),
-    Addition {
-        left: Box<ExpressionComponent<R>>,
-        right: Box<ExpressionComponent<R>>
-    },
-    Subtraction {
-        left: Box<ExpressionComponent<R>>,
-        right: Box<ExpressionComponent<R>>
-    },
-    Multiplication {
-        left: Box<ExpressionComponent<R>>,
-        right: Box<ExpressionComponent<R>>
-    },
-    Division {
-        left: Box<ExpressionComponent<R>>,
-        right: Box<ExpressionComponent<R>>
-    },
-}
-
-impl<R: Ring> ExpressionComponent<R> {
-    pub fn new_ring_element(element: R::RingElementType) -> ExpressionComponent<R> {
-        RingElement(element)
-    }
-
-    pub fn new_addition(expr1: Self, expr2: Self) -> ExpressionComponent<R> {
-        Addition {
-            left: Box::new(expr1),
-            right: Box::new(expr2)
-        }
-    }
-
-    pub fn new_subtraction(expr1: Self, expr2: Self) -> ExpressionComponent<R> {
-        Subtraction {
-            left: Box::new(expr1),
-            right: Box::new(expr2)
-        }
-    }
-
-    pub fn new_multiplication(expr1: Self, expr2: Self) -> ExpressionComponent<R> {
-        Multiplication {
-            left: Box::new(expr1),
-            right: Box::new(expr2)
-        }
-    }
-
-    pub fn new_division(expr1: Self, expr2: Self) -> ExpressionComponent<R> {
-        Division {
-            left: Box::new(expr1),
-            right: Box::new(expr2)
-        }
-    }
-
-    pub fn new_parenteses(expr: Self) -> ExpressionComponent<R> {
-        Parentheses(Box::new(expr))
-    }
-
-    pub fn new_unary_minus(expr: Self) -> ExpressionComponent<R> {
-        UnaryMinus(Box::new(expr))
-    }
-
-    fn is_operator(&self) -> bool {
-        match self {
-            RingElement(_) => false,
-            Addition { .. } => true,
-            Subtraction { .. } => true,
-            Multiplication { .. } => true,
-            Division { .. } => true,
-            Parentheses(_) => false,
-            UnaryMinus(_) => false,
-        }
-    }
-
-    fn precedence(&self) -> i32 {
-        match self {
-            RingElement(_) => i32::MAX,
-            Parentheses(_) => i32::MAX,
-            UnaryMinus(_) => i32::MAX,
-            Addition { .. } => 0,
-            Subtraction { .. } => 0,
-            Multiplication { .. } => 1,
-            Division { .. } => 1,
-        }
-    }
-
-    fn left_mut(&mut self) -> &mut ExpressionComponent<R> {
-        match self {
-            ExpressionComponent::Addition { left, .. } => left.deref_mut(),
-            ExpressionComponent::Subtraction { left, .. } => left.deref_mut(),
-            ExpressionComponent::Multiplication { left, .. } => left.deref_mut(),
-            ExpressionComponent::Division { left, .. } => left.deref_mut(),
-            _ => panic!("Not an operator"),
-        }
-    }
-
-    fn right_mut(&mut self) -> &mut ExpressionComponent<R> {
-        match self {
-            ExpressionComponent::Addition { right, .. } => right.deref_mut(),
-            ExpressionComponent::Subtraction { right, .. } => right.deref_mut(),
-            ExpressionComponent::Multiplication { right, .. } => right.deref_mut(),
-            ExpressionComponent::Division { right, .. } => right.deref_mut(),
-            _ => panic!("Not an operator"),
-        }
-    }
-}
-
-impl<R: Ring> ExpressionComponent<R> {
-    pub fn evaluate(&self) -> EvaluateExpressionResult<R::RingElementType> {
-        match self {
-            RingElement(r) => Ok(r.clone()),
-            Parentheses(inner) => inner.evaluate(),
-            UnaryMinus(inner) => panic!("implement"),
-            Addition {left, right} => {
-                Self::evaluate_binary_operation(R::add, &left, &right)
-            }
-            Subtraction {left, right} => {
-                Self::evaluate_binary_operation(R::sub, &left, &right)
-            }
-            Multiplication {left, right} => {
-                Self::evaluate_binary_operation(R::mul, &left, &right)
-            }
-            Division {left, right} => {
-                Self::evaluate_binary_operation(R::div, &left, &right)
-            }
-        }
-    }
-
-    fn evaluate_binary_operation(
-        binary_operation: fn(&R::RingElementType, &R::RingElementType) -> RingResult<R::RingElementType>,
-        left: &Box<ExpressionComponent<R>>,
-        right: &Box<ExpressionComponent<R>>) -> EvaluateExpressionResult<R::RingElementType>
-    {
-        Ok(binary_operation(&left.evaluate()?, &right.evaluate()?)?)
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::expression::ring::intring::{IntRingElement, IntRing};
-    use crate::expression::{ExpressionComponent, EvaluateExpressionError};
-
-    #[test]
-    fn simple_value() {
-        let element = IntRingElement::new(5);
-        let expression = ExpressionComponent::<IntRing>::new_ring_element(element.clone());
-
-        assert_eq!(Ok(element), expression.evaluate());
-    }
-
-    #[test]
-    fn addition() {
-        let expression =
-            ExpressionComponent::<IntRing>::new_addition(
-                ExpressionComponent::new_ring_element(IntRingElement::new(5)),
-                ExpressionComponent::new_ring_element(IntRingElement::new(7)));
-
-        assert_eq!(Ok(IntRingElement::new(12)), expression.evaluate());
-    }
-
-    #[test]
-    fn addition_overflow() {
-        let expression =
-            ExpressionComponent::<IntRing>::new_addition(
-                ExpressionComponent::new_ring_element(IntRingElement::new(i64::MAX)),
-                ExpressionComponent::new_ring_element(IntRingElement::new(7)));
-
-        assert_eq!(Err(EvaluateExpressionError {message: "Overflow".to_string()}), expression.evaluate());
-    }
-
-    #[test]
-    fn subtraction() {
-        let expression =
-            ExpressionComponent::<IntRing>::new_subtraction(
-                ExpressionComponent::new_ring_element(IntRingElement::new(5)),
-                ExpressionComponent::new_ring_element(IntRingElement::new(7)));
-
-        assert_eq!(Ok(IntRingElement::new(-2)), expression.evaluate());
-    }
-
-    #[test]
-    fn multiplication() {
-        let expression =
-            ExpressionComponent::<IntRing>::new_multiplication(
-                ExpressionComponent::new_ring_element(IntRingElement::new(5)),
-                ExpressionComponent::new_ring_element(IntRingElement::new(7)));
-
-        assert_eq!(Ok(IntRingElement::new(35)), expression.evaluate());
-    }
-
-    #[test]
-    fn division() {
-        let expression =
-            ExpressionComponent::<IntRing>::new_division(
-                ExpressionComponent::new_ring_element(IntRingElement::new(6)),
-                ExpressionComponent::new_ring_element(IntRingElement::new(2)));
-
-        assert_eq!(Ok(IntRingElement::new(3)), expression.evaluate());
-    }
-
-    #[test]
-    fn parenthesis() {
-        let expression =
-            ExpressionComponent::<IntRing>::new_parenteses(
-                ExpressionComponent::new_ring_element(IntRingElement::new(5)));
-
-        assert_eq!(Ok(IntRingElement::new(5)), expression.evaluate());
-    }
-
+use std::fmt::{Formatter};
+use std::{error, io, result};
+use core::fmt;
+use crate::expression::ring::{Ring, RingError, RingErrorKind, RingResult, RingFunction};
+use crate::expression::ExpressionComponent::{RingElement, Variable, FunctionCall, Addition, Subtraction, Multiplication, Division, Modulo, Power, LessThan, GreaterThan, Equal, Conditional, Parentheses, UnaryMinus};
+use std::ops::{Deref, DerefMut, Neg, Range};
+use std::iter::Peekable;
+use crate::token::{TokenIterator, TokenResult, TokenWithPos};
+use crate::token::intring::{IntRingTokenParser, IntRingToken};
+use crate::expression::parser::{ElementTokenRing, parse_literal};
+use std::borrow::Cow;
+use std::mem;
+use std::hash::{Hash, Hasher};
+use std::collections::HashMap;
+use std::cmp::Ordering;
+
+pub mod ring;
+pub mod parser;
+pub mod print;
+pub mod interned;
+pub mod constant;
+pub mod visit;
+#[cfg(feature = "bench")]
+pub mod random;
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct EvaluateExpressionError {
+    pub message: String,
+    pub kind: RingErrorKind,
+    /// Source position of the operator that failed, if known.
+    pub position: Option<usize>,
+    /// Source span of the operation that failed, including its operands, if known.
+    pub span: Option<Range<usize>>,
+}
+
+impl fmt::Display for EvaluateExpressionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Error evaluating expression: {}", self.message)
+    }
+}
+
+impl error::Error for EvaluateExpressionError {
+}
+
+impl From<RingError> for EvaluateExpressionError {
+    fn from(err: RingError) -> Self {
+        EvaluateExpressionError {
+            message: err.message,
+            kind: err.kind,
+            position: None,
+            span: None,
+        }
+    }
+}
+
+impl EvaluateExpressionError {
+    fn from_operation_error(operation: &str, position: SourcePosition, err: RingError) -> Self {
+        EvaluateExpressionError {
+            message: format!("{} during {}", err.message, operation),
+            kind: err.kind,
+            position: position.position,
+            span: position.span_range(),
+        }
+    }
+}
+
+impl From<EvaluateExpressionError> for io::Error {
+    fn from(err: EvaluateExpressionError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+pub type EvaluateExpressionResult<T> = result::Result<T, EvaluateExpressionError>;
+
+/// Error for expressions nested deeper than allowed.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct DepthError {
+    pub max_depth: usize
+}
+
+impl fmt::Display for DepthError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Expression nested deeper than {}", self.max_depth)
+    }
+}
+
+impl error::Error for DepthError {
+}
+
+/// Position in the source of an operator in a parsed expression. Positions are ignored when
+/// comparing and hashing expressions, such that parsed expressions are equal to expressions
+/// constructed without positions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SourcePosition {
+    /// Char offset of the operator or function name.
+    pub position: Option<usize>,
+    /// Start and end char offsets of the operation including its operands.
+    pub span: Option<(usize, usize)>,
+}
+
+impl SourcePosition {
+    /// Position of an operator at `position` whose span is not known.
+    pub fn at(position: usize) -> SourcePosition {
+        SourcePosition { position: Some(position), span: None }
+    }
+
+    /// Span of the operation as a range of char offsets.
+    pub fn span_range(&self) -> Option<Range<usize>> {
+        self.span.map(|(start, end)| start..end)
+    }
+}
+
+impl PartialEq for SourcePosition {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for SourcePosition {
+}
+
+impl Hash for SourcePosition {
+    fn hash<H: Hasher>(&self, _state: &mut H) {
+    }
+}
+
+/// Binary operator in an expression.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Operator {
+    Addition,
+    Subtraction,
+    Multiplication,
+    Division,
+    Modulo,
+    Power,
+    /// `<`, comparing the operands, see [Ring::compare].
+    LessThan,
+    /// `>`, comparing the operands, see [Ring::compare].
+    GreaterThan,
+    /// `==`, comparing the operands for equality.
+    Equal,
+}
+
+impl Operator {
+    /// Apply operator to the given ring elements.
+    pub fn apply<R: Ring>(self, elm1: &R::RingElementType, elm2: &R::RingElementType) -> RingResult<R::RingElementType> {
+        match self {
+            Operator::Addition => R::add(elm1, elm2),
+            Operator::Subtraction => R::sub(elm1, elm2),
+            Operator::Multiplication => R::mul(elm1, elm2),
+            Operator::Division => R::div(elm1, elm2),
+            Operator::Modulo => R::rem(elm1, elm2),
+            Operator::Power => R::pow(elm1, elm2),
+            Operator::LessThan => R::compare(elm1, elm2).map(|ordering| Self::truth_value::<R>(ordering == Ordering::Less)),
+            Operator::GreaterThan => R::compare(elm1, elm2).map(|ordering| Self::truth_value::<R>(ordering == Ordering::Greater)),
+            Operator::Equal => Ok(Self::truth_value::<R>(elm1 == elm2)),
+        }
+    }
+
+    /// [Ring::one] for true and [Ring::zero] for false, the values of comparisons.
+    fn truth_value<R: Ring>(value: bool) -> R::RingElementType {
+        if value { R::one() } else { R::zero() }
+    }
+
+    /// Name of the operation, e.g. "multiplication".
+    pub fn name(self) -> &'static str {
+        match self {
+            Operator::Addition => "addition",
+            Operator::Subtraction => "subtraction",
+            Operator::Multiplication => "multiplication",
+            Operator::Division => "division",
+            Operator::Modulo => "modulo",
+            Operator::Power => "exponentiation",
+            Operator::LessThan | Operator::GreaterThan | Operator::Equal => "comparison",
+        }
+    }
+}
+
+/// A ring operation performed while evaluating an expression, see [ExpressionComponent::evaluate_with_observer].
+pub struct RingOpEvent<'a, R: Ring> {
+    pub operator: Operator,
+    pub left: &'a R::RingElementType,
+    pub right: &'a R::RingElementType,
+    pub result: &'a RingResult<R::RingElementType>,
+}
+
+/// Record of the ring operations that produced the value of an expression, in evaluation order,
+/// see [ExpressionComponent::evaluate_with_provenance].
+#[derive(PartialEq, Eq, Clone, Hash)]
+pub struct Provenance<R: Ring> {
+    pub steps: Vec<ProvenanceStep<R>>,
+}
+
+impl<R: Ring + fmt::Debug> fmt::Debug for Provenance<R> where R::RingElementType: fmt::Debug {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Provenance").field("steps", &self.steps).finish()
+    }
+}
+
+/// Ring operation in a [Provenance] with its operands and result.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ProvenanceStep<R: Ring> {
+    pub operator: Operator,
+    pub left: R::RingElementType,
+    pub right: R::RingElementType,
+    pub result: R::RingElementType,
+}
+
+impl<R: Ring> fmt::Display for ProvenanceStep<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {} = {}", self.left, R::OPERATOR_SYMBOLS.symbol(self.operator), self.right, self.result)
+    }
+}
+
+/// One step per line.
+impl<R: Ring> fmt::Display for Provenance<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum ExpressionComponent<R: Ring> {
+    RingElement(R::RingElementType),
+    /// Variable, whose value is given when evaluating, see [ExpressionComponent::evaluate_with].
+    Variable(String),
+    /// Call of the function `name` of the ring, see [Ring::function].
+    FunctionCall {
+        name: String,
+        args: Vec<ExpressionComponent<R>>,
+        position: SourcePosition,
+    },
+    /// Parenthesized expression. The span of the source position covers the parentheses.
+    Parentheses {
+        inner: Box<ExpressionComponent<R>>,
+        position: SourcePosition,
+    },
+    UnaryMinus {
+        inner: Box<ExpressionComponent<R>>,
+        position: SourcePosition,
+    },
+    Addition {
+        left: Box<ExpressionComponent<R>>,
+        right: Box<ExpressionComponent<R>>,
+        position: SourcePosition,
+    },
+    Subtraction {
+        left: Box<ExpressionComponent<R>>,
+        right: Box<ExpressionComponent<R>>,
+        position: SourcePosition,
+    },
+    Multiplication {
+        left: Box<ExpressionComponent<R>>,
+        right: Box<ExpressionComponent<R>>,
+        position: SourcePosition,
+    },
+    Division {
+        left: Box<ExpressionComponent<R>>,
+        right: Box<ExpressionComponent<R>>,
+        position: SourcePosition,
+    },
+    Modulo {
+        left: Box<ExpressionComponent<R>>,
+        right: Box<ExpressionComponent<R>>,
+        position: SourcePosition,
+    },
+    /// Exponentiation, which is right-associative: `2 ^ 3 ^ 2 = 2 ^ (3 ^ 2)`. A unary minus
+    /// binds looser than `^` but tighter than `*` and `/`, so `-2 ^ 2` is `-(2 ^ 2) = -4` while
+    /// `-5 * 2` is `(-5) * 2`. Write `(-2) ^ 2` for `4`.
+    Power {
+        left: Box<ExpressionComponent<R>>,
+        right: Box<ExpressionComponent<R>>,
+        position: SourcePosition,
+    },
+    /// Comparison `left < right`, evaluating to [Ring::one] if true and [Ring::zero] if false.
+    /// Comparisons bind looser than the arithmetic operators.
+    LessThan {
+        left: Box<ExpressionComponent<R>>,
+        right: Box<ExpressionComponent<R>>,
+        position: SourcePosition,
+    },
+    /// Comparison `left > right`, see [LessThan].
+    GreaterThan {
+        left: Box<ExpressionComponent<R>>,
+        right: Box<ExpressionComponent<R>>,
+        position: SourcePosition,
+    },
+    /// Comparison `left == right`, see [LessThan].
+    Equal {
+        left: Box<ExpressionComponent<R>>,
+        right: Box<ExpressionComponent<R>>,
+        position: SourcePosition,
+    },
+    /// Conditional `condition ? then : otherwise`, evaluating to `then` if `condition` is not
+    /// [Ring::zero], e.g. a true comparison, and to `otherwise` if it is. Only the taken branch
+    /// is evaluated, so `1 > 0 ? 5 : 1 / 0` is `5`. Binds looser than all other operators and
+    /// nests to the right: `a ? b : c ? d : e` is `a ? b : (c ? d : e)`. The source position is
+    /// that of the `?`.
+    Conditional {
+        condition: Box<ExpressionComponent<R>>,
+        then: Box<ExpressionComponent<R>>,
+        otherwise: Box<ExpressionComponent<R>>,
+        position: SourcePosition,
+    },
+}
+
+impl<R: Ring> ExpressionComponent<R> {
+    pub fn new_ring_element(element: R::RingElementType) -> ExpressionComponent<R> {
+        RingElement(element)
+    }
+
+    pub fn new_variable(name: impl Into<String>) -> ExpressionComponent<R> {
+        Variable(name.into())
+    }
+
+    pub fn new_function_call(name: impl Into<String>, args: Vec<Self>) -> ExpressionComponent<R> {
+        FunctionCall {
+            name: name.into(),
+            args,
+            position: SourcePosition::default(),
+        }
+    }
+
+    pub fn new_addition(expr1: Self, expr2: Self) -> ExpressionComponent<R> {
+        Addition {
+            left: Box::new(expr1),
+            right: Box::new(expr2),
+            position: SourcePosition::default(),
+        }
+    }
+
+    pub fn new_subtraction(expr1: Self, expr2: Self) -> ExpressionComponent<R> {
+        Subtraction {
+            left: Box::new(expr1),
+            right: Box::new(expr2),
+            position: SourcePosition::default(),
+        }
+    }
+
+    pub fn new_multiplication(expr1: Self, expr2: Self) -> ExpressionComponent<R> {
+        Multiplication {
+            left: Box::new(expr1),
+            right: Box::new(expr2),
+            position: SourcePosition::default(),
+        }
+    }
+
+    pub fn new_division(expr1: Self, expr2: Self) -> ExpressionComponent<R> {
+        Division {
+            left: Box::new(expr1),
+            right: Box::new(expr2),
+            position: SourcePosition::default(),
+        }
+    }
+
+    pub fn new_modulo(expr1: Self, expr2: Self) -> ExpressionComponent<R> {
+        Modulo {
+            left: Box::new(expr1),
+            right: Box::new(expr2),
+            position: SourcePosition::default(),
+        }
+    }
+
+    pub fn new_power(expr1: Self, expr2: Self) -> ExpressionComponent<R> {
+        Power {
+            left: Box::new(expr1),
+            right: Box::new(expr2),
+            position: SourcePosition::default(),
+        }
+    }
+
+    pub fn new_less_than(expr1: Self, expr2: Self) -> ExpressionComponent<R> {
+        LessThan {
+            left: Box::new(expr1),
+            right: Box::new(expr2),
+            position: SourcePosition::default(),
+        }
+    }
+
+    pub fn new_greater_than(expr1: Self, expr2: Self) -> ExpressionComponent<R> {
+        GreaterThan {
+            left: Box::new(expr1),
+            right: Box::new(expr2),
+            position: SourcePosition::default(),
+        }
+    }
+
+    pub fn new_equal(expr1: Self, expr2: Self) -> ExpressionComponent<R> {
+        Equal {
+            left: Box::new(expr1),
+            right: Box::new(expr2),
+            position: SourcePosition::default(),
+        }
+    }
+
+    /// Binary operation `operator` applied to `expr1` and `expr2`.
+    pub fn new_binary_operation(operator: Operator, expr1: Self, expr2: Self) -> ExpressionComponent<R> {
+        match operator {
+            Operator::Addition => ExpressionComponent::new_addition(expr1, expr2),
+            Operator::Subtraction => ExpressionComponent::new_subtraction(expr1, expr2),
+            Operator::Multiplication => ExpressionComponent::new_multiplication(expr1, expr2),
+            Operator::Division => ExpressionComponent::new_division(expr1, expr2),
+            Operator::Modulo => ExpressionComponent::new_modulo(expr1, expr2),
+            Operator::Power => ExpressionComponent::new_power(expr1, expr2),
+            Operator::LessThan => ExpressionComponent::new_less_than(expr1, expr2),
+            Operator::GreaterThan => ExpressionComponent::new_greater_than(expr1, expr2),
+            Operator::Equal => ExpressionComponent::new_equal(expr1, expr2),
+        }
+    }
+
+    /// Fold `operands` left to right with the binary operation `operator`, e.g. operands `1, 2, 3`
+    /// with addition gives `(1 + 2) + 3`. A single operand is returned as is. Without operands,
+    /// the result is the identity of the operator, [Ring::zero] for addition and [Ring::one] for
+    /// multiplication, and `None` for the other operators, which have no identity.
+    pub fn from_operands(operator: Operator, operands: Vec<ExpressionComponent<R>>) -> Option<ExpressionComponent<R>> {
+        operands.into_iter()
+            .reduce(|left, right| ExpressionComponent::new_binary_operation(operator, left, right))
+            .or_else(|| match operator {
+                Operator::Addition => Some(RingElement(R::zero())),
+                Operator::Multiplication => Some(RingElement(R::one())),
+                _ => None,
+            })
+    }
+
+    /// Set source position of a binary operation, unary minus, function call, parentheses or
+    /// conditional. Other expressions are returned unchanged.
+    pub fn with_position(mut self, source_position: SourcePosition) -> ExpressionComponent<R> {
+        match &mut self {
+            Addition { position, .. }
+            | Subtraction { position, .. }
+            | Multiplication { position, .. }
+            | Division { position, .. }
+            | Modulo { position, .. }
+            | Power { position, .. }
+            | LessThan { position, .. }
+            | GreaterThan { position, .. }
+            | Equal { position, .. }
+            | UnaryMinus { position, .. }
+            | Parentheses { position, .. }
+            | Conditional { position, .. }
+            | FunctionCall { position, .. } => *position = source_position,
+            RingElement(_) | Variable(_) => (),
+        }
+        self
+    }
+
+    /// Source position of a binary operation, unary minus, function call, parentheses or
+    /// conditional.
+    pub fn position(&self) -> SourcePosition {
+        match self {
+            Addition { position, .. }
+            | Subtraction { position, .. }
+            | Multiplication { position, .. }
+            | Division { position, .. }
+            | Modulo { position, .. }
+            | Power { position, .. }
+            | LessThan { position, .. }
+            | GreaterThan { position, .. }
+            | Equal { position, .. }
+            | UnaryMinus { position, .. }
+            | Parentheses { position, .. }
+            | Conditional { position, .. }
+            | FunctionCall { position, .. } => *position,
+            RingElement(_) | Variable(_) => SourcePosition::default(),
+        }
+    }
+
+    pub fn new_parenteses(expr: Self) -> ExpressionComponent<R> {
+        Parentheses {
+            inner: Box::new(expr),
+            position: SourcePosition::default(),
+        }
+    }
+
+    pub fn new_conditional(condition: Self, then: Self, otherwise: Self) -> ExpressionComponent<R> {
+        Conditional {
+            condition: Box::new(condition),
+            then: Box::new(then),
+            otherwise: Box::new(otherwise),
+            position: SourcePosition::default(),
+        }
+    }
+
+    pub fn new_unary_minus(expr: Self) -> ExpressionComponent<R> {
+        UnaryMinus {
+            inner: Box::new(expr),
+            position: SourcePosition::default(),
+        }
+    }
+
+    fn is_operator(&self) -> bool {
+        match self {
+            RingElement(_) => false,
+            Variable(_) => false,
+            FunctionCall { .. } => false,
+            Addition { .. } => true,
+            Subtraction { .. } => true,
+            Multiplication { .. } => true,
+            Division { .. } => true,
+            Modulo { .. } => true,
+            Power { .. } => true,
+            LessThan { .. } => true,
+            GreaterThan { .. } => true,
+            Equal { .. } => true,
+            Conditional { .. } => true,
+            Parentheses { .. } => false,
+            UnaryMinus { .. } => false,
+        }
+    }
+
+    fn precedence(&self) -> i32 {
+        match self {
+            RingElement(_) => i32::MAX,
+            Variable(_) => i32::MAX,
+            FunctionCall { .. } => i32::MAX,
+            Parentheses { .. } => i32::MAX,
+            UnaryMinus { .. } => i32::MAX,
+            Conditional { .. } => -2,
+            LessThan { .. } => -1,
+            GreaterThan { .. } => -1,
+            Equal { .. } => -1,
+            Addition { .. } => 0,
+            Subtraction { .. } => 0,
+            Multiplication { .. } => 1,
+            Division { .. } => 1,
+            Modulo { .. } => 1,
+            Power { .. } => 2,
+        }
+    }
+
+    fn is_right_associative(&self) -> bool {
+        matches!(self, Power { .. })
+    }
+
+    /// Whether the operator `self` binds tighter than the operator `other` to its right, such that
+    /// `self` must take the right operand of `other` as its left operand.
+    fn binds_tighter_than(&self, other: &Self) -> bool {
+        other.is_operator()
+            && (other.precedence() < self.precedence()
+                || (other.precedence() == self.precedence() && self.is_right_associative()))
+    }
+
+    fn left_mut(&mut self) -> &mut ExpressionComponent<R> {
+        match self {
+            ExpressionComponent::Addition { left, .. } => left.deref_mut(),
+            ExpressionComponent::Subtraction { left, .. } => left.deref_mut(),
+            ExpressionComponent::Multiplication { left, .. } => left.deref_mut(),
+            ExpressionComponent::Division { left, .. } => left.deref_mut(),
+            ExpressionComponent::Modulo { left, .. } => left.deref_mut(),
+            ExpressionComponent::Power { left, .. } => left.deref_mut(),
+            ExpressionComponent::LessThan { left, .. } => left.deref_mut(),
+            ExpressionComponent::GreaterThan { left, .. } => left.deref_mut(),
+            ExpressionComponent::Equal { left, .. } => left.deref_mut(),
+            ExpressionComponent::Conditional { condition, .. } => condition.deref_mut(),
+            _ => panic!("Not an operator"),
+        }
+    }
+
+    fn right_mut(&mut self) -> &mut ExpressionComponent<R> {
+        match self {
+            ExpressionComponent::Addition { right, .. } => right.deref_mut(),
+            ExpressionComponent::Subtraction { right, .. } => right.deref_mut(),
+            ExpressionComponent::Multiplication { right, .. } => right.deref_mut(),
+            ExpressionComponent::Division { right, .. } => right.deref_mut(),
+            ExpressionComponent::Modulo { right, .. } => right.deref_mut(),
+            ExpressionComponent::Power { right, .. } => right.deref_mut(),
+            ExpressionComponent::LessThan { right, .. } => right.deref_mut(),
+            ExpressionComponent::GreaterThan { right, .. } => right.deref_mut(),
+            ExpressionComponent::Equal { right, .. } => right.deref_mut(),
+            ExpressionComponent::Conditional { otherwise, .. } => otherwise.deref_mut(),
+            _ => panic!("Not an operator"),
+        }
+    }
+}
+
+/// Maximum [depth](ExpressionComponent::depth) of expressions evaluated with recursion by
+/// [ExpressionComponent::evaluate]. Deeper expressions are evaluated with an explicit stack, see
+/// [ExpressionComponent::evaluate_with_recursion_threshold].
+pub const EVALUATE_RECURSION_THRESHOLD: usize = 256;
+
+/// Values of the variables and functions when evaluating an expression with
+/// [ExpressionComponent::evaluate_in]. Functions in the context are called instead of functions
+/// of the ring with the same name. Since the parser only accepts calls of functions of the ring,
+/// other functions can only be called in constructed expressions. Built by chaining e.g.
+/// `EvalContext::new().with_variable("x", x).with_function("f", f)`.
+pub struct EvalContext<R: Ring> {
+    variables: HashMap<String, R::RingElementType>,
+    functions: HashMap<String, RingFunction<R>>,
+}
+
+impl<R: Ring> Default for EvalContext<R> {
+    fn default() -> Self {
+        EvalContext::new()
+    }
+}
+
+impl<R: Ring> EvalContext<R> {
+    /// Context without variables and functions, in which only the functions of the ring can be
+    /// called.
+    pub fn new() -> EvalContext<R> {
+        EvalContext {
+            variables: HashMap::new(),
+            functions: HashMap::new(),
+        }
+    }
+
+    /// Bind the variable `name` to `value`.
+    pub fn with_variable(mut self, name: impl Into<String>, value: R::RingElementType) -> EvalContext<R> {
+        self.variables.insert(name.into(), value);
+        self
+    }
+
+    /// Register `function` under `name`.
+    pub fn with_function(mut self, name: impl Into<String>, function: RingFunction<R>) -> EvalContext<R> {
+        self.functions.insert(name.into(), function);
+        self
+    }
+
+    pub fn variable(&self, name: &str) -> Option<&R::RingElementType> {
+        self.variables.get(name)
+    }
+
+    pub fn function(&self, name: &str) -> Option<&RingFunction<R>> {
+        self.functions.get(name)
+    }
+}
+
+/// Values of the variables when evaluating an expression.
+enum Bindings<'a, R: Ring> {
+    None,
+    Env(&'a HashMap<String, R::RingElementType>),
+    Resolver(&'a dyn Fn(&str) -> Option<R::RingElementType>),
+    /// Variables and functions given by a context.
+    Context(&'a EvalContext<R>),
+}
+
+impl<R: Ring> ExpressionComponent<R> {
+    /// Evaluate expression. Operations are evaluated in the order given by the expression tree,
+    /// and since ring operations may fail (e.g. on overflow), this order can decide whether evaluation
+    /// succeeds. Explicit parentheses wrap a subtree and hence determine the evaluation order, so any
+    /// reordering of operations must respect [Parentheses] nodes.
+    pub fn evaluate(&self) -> EvaluateExpressionResult<R::RingElementType> {
+        self.evaluate_in(&EvalContext::new())
+    }
+
+    /// Evaluate expression with the variables and functions of `ctx`. Fails if a variable is not
+    /// bound in `ctx`, or a function is neither in `ctx` nor a function of the ring.
+    pub fn evaluate_in(&self, ctx: &EvalContext<R>) -> EvaluateExpressionResult<R::RingElementType> {
+        Ok(self.evaluate_observed(&Bindings::Context(ctx), &mut |_| Ok(()))?.into_owned())
+    }
+
+    /// Evaluate expression, using recursion only if the expression is at most `recursion_threshold`
+    /// deep, see [ExpressionComponent::depth]. Deeper expressions are evaluated with an explicit
+    /// stack, which is slower but cannot overflow the call stack. [ExpressionComponent::evaluate]
+    /// and the other evaluation methods use [EVALUATE_RECURSION_THRESHOLD].
+    pub fn evaluate_with_recursion_threshold(&self, recursion_threshold: usize) -> EvaluateExpressionResult<R::RingElementType> {
+        Ok(self.evaluate_with_threshold(&Bindings::None, &mut |_| Ok(()), recursion_threshold)?.into_owned())
+    }
+
+    /// Evaluate expression and convert the result to `T`, e.g. `i64` for
+    /// [IntRing](ring::intring::IntRing) expressions.
+    pub fn evaluate_into<T>(&self) -> EvaluateExpressionResult<T>
+        where R::RingElementType: Into<T>
+    {
+        Ok(self.evaluate()?.into())
+    }
+
+    /// Evaluate expression without cloning ring elements unnecessarily. If the expression is a
+    /// ring element, possibly in parentheses, the element is borrowed. An owned value is only
+    /// created if an operation is evaluated.
+    pub fn evaluate_cow(&self) -> EvaluateExpressionResult<Cow<'_, R::RingElementType>> {
+        self.evaluate_observed(&Bindings::None, &mut |_| Ok(()))
+    }
+
+    /// Evaluate expression with the values of variables given by `env`. Fails if a variable is
+    /// not in `env`. Expressions without variables evaluate as with [ExpressionComponent::evaluate].
+    pub fn evaluate_with(&self, env: &HashMap<String, R::RingElementType>) -> EvaluateExpressionResult<R::RingElementType> {
+        Ok(self.evaluate_observed(&Bindings::Env(env), &mut |_| Ok(()))?.into_owned())
+    }
+
+    /// Evaluate expression with the values of variables given by `resolver`, e.g. for named
+    /// constants of the ring. Fails if `resolver` returns `None` for a variable.
+    pub fn evaluate_with_resolver(
+        &self,
+        resolver: impl Fn(&str) -> Option<R::RingElementType>) -> EvaluateExpressionResult<R::RingElementType>
+    {
+        Ok(self.evaluate_observed(&Bindings::Resolver(&resolver), &mut |_| Ok(()))?.into_owned())
+    }
+
+    /// Evaluate expression and call `observer` with each binary ring operation performed, including
+    /// its operands and result, in evaluation order. Negations are not observed.
+    pub fn evaluate_with_observer(
+        &self,
+        observer: &mut impl FnMut(RingOpEvent<R>)) -> EvaluateExpressionResult<R::RingElementType>
+    {
+        self.evaluate_with_checked_observer(&mut |event| {
+            observer(event);
+            Ok(())
+        })
+    }
+
+    /// Evaluate expression and call `observer` with each binary ring operation performed, as for
+    /// [ExpressionComponent::evaluate_with_observer]. If `observer` returns an error for an
+    /// operation that succeeded, the operation fails with that error and evaluation stops, such
+    /// that e.g. a limit on the results can be enforced while evaluating.
+    pub fn evaluate_with_checked_observer(
+        &self,
+        observer: &mut impl FnMut(RingOpEvent<R>) -> RingResult<()>) -> EvaluateExpressionResult<R::RingElementType>
+    {
+        Ok(self.evaluate_observed(&Bindings::None, observer)?.into_owned())
+    }
+
+    /// Evaluate expression and record the binary ring operations performed, see [Provenance].
+    /// Negations are not recorded, as for [ExpressionComponent::evaluate_with_observer].
+    pub fn evaluate_with_provenance(&self) -> EvaluateExpressionResult<(R::RingElementType, Provenance<R>)> {
+        let mut steps = Vec::new();
+        let value = self.evaluate_with_observer(&mut |event| {
+            if let Ok(result) = event.result {
+                steps.push(ProvenanceStep { operator: event.operator, left: event.left.clone(), right: event.right.clone(), result: result.clone() });
+            }
+        })?;
+        Ok((value, Provenance { steps }))
+    }
+
+    /// Evaluate expression with `f` applied to each ring element before it is used. The
+    /// transform may fail, e.g. to enforce domain constraints on the leaves, in which case
+    /// evaluation fails.
+    pub fn evaluate_map_leaves(
+        &self,
+        f: impl Fn(&R::RingElementType) -> RingResult<R::RingElementType>) -> EvaluateExpressionResult<R::RingElementType>
+    {
+        self.evaluate_map_leaves_rec(&f)
+    }
+
+    fn evaluate_map_leaves_rec<F>(&self, f: &F) -> EvaluateExpressionResult<R::RingElementType>
+        where F: Fn(&R::RingElementType) -> RingResult<R::RingElementType>
+    {
+        let (operator, left, right, position) = match self {
+            RingElement(r) => return Ok(f(r)?),
+            Variable(name) => return Err(Self::unbound_variable(name)),
+            FunctionCall { name, args, position } => {
+                let values = args.iter().map(|arg| arg.evaluate_map_leaves_rec(f)).collect::<EvaluateExpressionResult<Vec<_>>>()?;
+                return Self::apply_function(name, *position, &values);
+            }
+            Parentheses { inner, .. } => return inner.evaluate_map_leaves_rec(f),
+            UnaryMinus { inner, position } => return Self::negate(&inner.evaluate_map_leaves_rec(f)?, *position),
+            Conditional { condition, then, otherwise, .. } => {
+                let branch = if Self::holds(&condition.evaluate_map_leaves_rec(f)?) { then } else { otherwise };
+                return branch.evaluate_map_leaves_rec(f);
+            }
+            Addition {left, right, position} => (Operator::Addition, left, right, *position),
+            Subtraction {left, right, position} => (Operator::Subtraction, left, right, *position),
+            Multiplication {left, right, position} => (Operator::Multiplication, left, right, *position),
+            Division {left, right, position} => (Operator::Division, left, right, *position),
+            Modulo {left, right, position} => (Operator::Modulo, left, right, *position),
+            Power {left, right, position} => (Operator::Power, left, right, *position),
+            LessThan {left, right, position} => (Operator::LessThan, left, right, *position),
+            GreaterThan {left, right, position} => (Operator::GreaterThan, left, right, *position),
+            Equal {left, right, position} => (Operator::Equal, left, right, *position),
+        };
+        let left_value = left.evaluate_map_leaves_rec(f)?;
+        let right_value = right.evaluate_map_leaves_rec(f)?;
+        operator.apply::<R>(&left_value, &right_value)
+            .map_err(|err| EvaluateExpressionError::from_operation_error(operator.name(), position, err))
+    }
+
+    /// Evaluate expression and, if evaluation fails, return the source span of the innermost
+    /// parentheses containing the failing operation. Returns `None` if evaluation succeeds, or if
+    /// the failing operation has no source position or is not in parentheses.
+    pub fn enclosing_paren_of_error(&self) -> Option<Range<usize>> {
+        let position = self.evaluate().err()?.position?;
+        self.iter()
+            .filter(|expr| matches!(expr, Parentheses { .. }))
+            .filter_map(|expr| expr.position().span_range())
+            .filter(|span| span.contains(&position))
+            .min_by_key(|span| span.len())
+    }
+
+}
+
+impl<R: ElementTokenRing> ExpressionComponent<R> {
+    /// Evaluate expression parsed from `src` and return the value of each subexpression together
+    /// with its span of char offsets in `src`, in evaluation order. The last entry is the whole
+    /// expression. The spans are found by matching the tokens of `src` with the expression, so
+    /// the expression must be parsed from `src` and not modified since, otherwise evaluation fails.
+    /// Literals must denote the ring elements in the expression as when parsing. Parentheses have
+    /// their own entry, and percentages such as `50%` have one entry.
+    pub fn evaluate_annotated(&self, src: &str) -> EvaluateExpressionResult<Vec<(Range<usize>, R::RingElementType)>> {
+        // accepts the literals and percent signs of all the parsers
+        let tokens: Vec<_> = TokenIterator::new(&src, IntRingTokenParser::new().with_calculator_mode(true).with_big_integers(true))
+            .collect::<TokenResult<_>>()
+            .map_err(|_| Self::source_mismatch())?;
+        let mut tokens = tokens.iter().peekable();
+        let mut annotations = Vec::new();
+        self.evaluate_annotated_rec(&mut tokens, &mut annotations)?;
+        if tokens.next().is_some() {
+            return Err(Self::source_mismatch());
+        }
+        Ok(annotations)
+    }
+
+    /// Evaluate expression while consuming its tokens from `tokens` and record the span and value
+    /// of it and its subexpressions in `annotations`.
+    fn evaluate_annotated_rec<'a>(
+        &self,
+        tokens: &mut Peekable<impl Iterator<Item=&'a TokenWithPos<IntRingToken>>>,
+        annotations: &mut Vec<(Range<usize>, R::RingElementType)>) -> EvaluateExpressionResult<(Range<usize>, R::RingElementType)>
+    {
+        let (span, value) = match self {
+            RingElement(element) => {
+                // a negative literal may be written as a minus sign and the literal
+                let minus = tokens.next_if(|twp| twp.token == IntRingToken::MinusSign);
+                let literal = tokens.next_if(|twp| matches!(twp.token, IntRingToken::DecimalInteger(_) | IntRingToken::BigDecimalInteger(_) | IntRingToken::DecimalFraction(_)))
+                    .ok_or_else(Self::source_mismatch)?;
+                let parsed = parse_literal::<R>(&literal.token, minus.is_some(), literal.position);
+                if !matches!(parsed, Ok(RingElement(ref parsed_element)) if parsed_element == element) {
+                    return Err(Self::source_mismatch());
+                }
+                (minus.unwrap_or(literal).position..literal.end, element.clone())
+            }
+            Variable(name) => return Err(Self::unbound_variable(name)),
+            FunctionCall { name, args, position } => {
+                let function_name = Self::expect_token(tokens, IntRingToken::FunctionName(name.clone()))?;
+                Self::expect_token(tokens, IntRingToken::LeftParenthesis)?;
+                let mut values = Vec::with_capacity(args.len());
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        Self::expect_token(tokens, IntRingToken::Comma)?;
+                    }
+                    values.push(arg.evaluate_annotated_rec(tokens, annotations)?.1);
+                }
+                let right_parenthesis = Self::expect_token(tokens, IntRingToken::RightParenthesis)?;
+                (function_name.position..right_parenthesis.end, Self::apply_function(name, *position, &values)?)
+            }
+            UnaryMinus { inner, position } => {
+                let minus = Self::expect_token(tokens, IntRingToken::MinusSign)?;
+                let (inner_span, inner_value) = inner.evaluate_annotated_rec(tokens, annotations)?;
+                (minus.position..inner_span.end, Self::negate(&inner_value, *position)?)
+            }
+            Parentheses { inner, .. } => match Self::expect_token(tokens, IntRingToken::LeftParenthesis) {
+                Ok(left_parenthesis) => {
+                    let (_, inner_value) = inner.evaluate_annotated_rec(tokens, annotations)?;
+                    let right_parenthesis = Self::expect_token(tokens, IntRingToken::RightParenthesis)?;
+                    (left_parenthesis.position..right_parenthesis.end, inner_value)
+                }
+                // a percentage is parsed as a division by 100 in parentheses
+                Err(err) => match inner.deref() {
+                    Division { left, right, position } => {
+                        let (left_span, left_value) = left.evaluate_annotated_rec(tokens, annotations)?;
+                        let percent = Self::expect_token(tokens, IntRingToken::Percent)?;
+                        let value = Operator::Division.apply::<R>(&left_value, &right.evaluate()?)
+                            .map_err(|err| EvaluateExpressionError::from_operation_error(Operator::Division.name(), *position, err))?;
+                        (left_span.start..percent.end, value)
+                    }
+                    _ => return Err(err),
+                },
+            },
+            Conditional { condition, then, otherwise, .. } => {
+                // the tokens of the untaken branch are skipped without evaluating it
+                let (condition_span, condition_value) = condition.evaluate_annotated_rec(tokens, annotations)?;
+                Self::expect_token(tokens, IntRingToken::QuestionMark)?;
+                let (end, value) = if Self::holds(&condition_value) {
+                    let (_, value) = then.evaluate_annotated_rec(tokens, annotations)?;
+                    Self::expect_token(tokens, IntRingToken::Colon)?;
+                    (Self::skip_branch(tokens)?, value)
+                } else {
+                    Self::skip_branch(tokens)?;
+                    Self::expect_token(tokens, IntRingToken::Colon)?;
+                    let (otherwise_span, value) = otherwise.evaluate_annotated_rec(tokens, annotations)?;
+                    (otherwise_span.end, value)
+                };
+                (condition_span.start..end, value)
+            }
+            Addition { left, right, position }
+            | Subtraction { left, right, position }
+            | Multiplication { left, right, position }
+            | Division { left, right, position }
+            | Modulo { left, right, position }
+            | Power { left, right, position }
+            | LessThan { left, right, position }
+            | GreaterThan { left, right, position }
+            | Equal { left, right, position } => {
+                let (operator, operator_token) = match self {
+                    Addition { .. } => (Operator::Addition, IntRingToken::PlusSign),
+                    Subtraction { .. } => (Operator::Subtraction, IntRingToken::MinusSign),
+                    Multiplication { .. } => (Operator::Multiplication, IntRingToken::MultiplicationSign),
+                    Division { .. } => (Operator::Division, IntRingToken::DivisionSign),
+                    Modulo { .. } => (Operator::Modulo, IntRingToken::Modulo),
+                    LessThan { .. } => (Operator::LessThan, IntRingToken::LessThanSign),
+                    GreaterThan { .. } => (Operator::GreaterThan, IntRingToken::GreaterThanSign),
+                    Equal { .. } => (Operator::Equal, IntRingToken::EqualsSign),
+                    _ => (Operator::Power, IntRingToken::CaretSign),
+                };
+                let (left_span, left_value) = left.evaluate_annotated_rec(tokens, annotations)?;
+                Self::expect_token(tokens, operator_token)?;
+                let (right_span, right_value) = right.evaluate_annotated_rec(tokens, annotations)?;
+                let value = operator.apply::<R>(&left_value, &right_value)
+                    .map_err(|err| EvaluateExpressionError::from_operation_error(operator.name(), *position, err))?;
+                (left_span.start..right_span.end, value)
+            }
+        };
+        annotations.push((span.clone(), value.clone()));
+        Ok((span, value))
+    }
+
+    fn expect_token<'a>(
+        tokens: &mut Peekable<impl Iterator<Item=&'a TokenWithPos<IntRingToken>>>,
+        token: IntRingToken) -> EvaluateExpressionResult<&'a TokenWithPos<IntRingToken>>
+    {
+        tokens.next_if(|twp| twp.token == token).ok_or_else(Self::source_mismatch)
+    }
+
+    /// Consume the tokens of a branch of a conditional, up to the `:` ending the branch or the end
+    /// of the enclosing expression, and return the end of the last token.
+    fn skip_branch<'a>(tokens: &mut Peekable<impl Iterator<Item=&'a TokenWithPos<IntRingToken>>>) -> EvaluateExpressionResult<usize> {
+        let mut end = None;
+        let mut parentheses = 0;
+        let mut conditionals = 0;
+        while let Some(twp) = tokens.next_if(|twp| parentheses > 0 || match twp.token {
+            IntRingToken::RightParenthesis | IntRingToken::Comma => false,
+            IntRingToken::Colon => conditionals > 0,
+            _ => true,
+        }) {
+            match twp.token {
+                IntRingToken::LeftParenthesis => parentheses += 1,
+                IntRingToken::RightParenthesis => parentheses -= 1,
+                IntRingToken::QuestionMark if parentheses == 0 => conditionals += 1,
+                IntRingToken::Colon if parentheses == 0 => conditionals -= 1,
+                _ => (),
+            }
+            end = Some(twp.end);
+        }
+        end.ok_or_else(Self::source_mismatch)
+    }
+
+    fn source_mismatch() -> EvaluateExpressionError {
+        EvaluateExpressionError {
+            message: "Expression does not match source".to_string(),
+            kind: RingErrorKind::Unspecified,
+            position: None,
+            span: None,
+        }
+    }
+}
+
+impl<R: Ring> ExpressionComponent<R> {
+    /// Evaluate constant subexpressions to ring elements. Operations whose result is not in the
+    /// ring, e.g. `7 / 2` in the integers, are kept with their operands folded, such that the
+    /// expression can still be evaluated in a larger ring. Other errors, e.g. overflow, are
+    /// returned. A conditional with a constant condition is folded to its taken branch. If the
+    /// condition is not constant, a branch is kept as is if folding it fails, since it may never
+    /// be evaluated.
+    pub fn fold_constants(&self) -> EvaluateExpressionResult<ExpressionComponent<R>> {
+        let (operator, left, right, position) = match self {
+            RingElement(element) => return Ok(RingElement(element.clone())),
+            Variable(name) => return Ok(Variable(name.clone())),
+            FunctionCall { name, args, position } => {
+                let folded = args.iter().map(|arg| arg.fold_constants()).collect::<EvaluateExpressionResult<Vec<_>>>()?;
+                let values: Option<Vec<_>> = folded.iter()
+                    .map(|arg| if let RingElement(element) = arg { Some(element.clone()) } else { None })
+                    .collect();
+                let unfolded = || ExpressionComponent::new_function_call(name.clone(), folded).with_position(*position);
+                return match values {
+                    Some(values) => Self::fold_result(Self::apply_function(name, *position, &values), unfolded),
+                    None => Ok(unfolded()),
+                };
+            }
+            Parentheses { inner, position } => {
+                let folded = inner.fold_constants()?;
+                return Ok(if let RingElement(_) = folded { folded } else { ExpressionComponent::new_parenteses(folded).with_position(*position) });
+            }
+            UnaryMinus { inner, position } => {
+                let folded = inner.fold_constants()?;
+                let negated = match &folded {
+                    RingElement(element) => Self::negate(element, *position),
+                    _ => return Ok(ExpressionComponent::new_unary_minus(folded).with_position(*position)),
+                };
+                return Self::fold_result(negated, || ExpressionComponent::new_unary_minus(folded).with_position(*position));
+            }
+            Conditional { condition, then, otherwise, position } => {
+                let condition = condition.fold_constants()?;
+                if let RingElement(value) = &condition {
+                    return if Self::holds(value) { then.fold_constants() } else { otherwise.fold_constants() };
+                }
+                let fold_branch = |branch: &ExpressionComponent<R>| branch.fold_constants()
+                    .unwrap_or_else(|_| branch.map_ring_elements(&R::RingElementType::clone));
+                return Ok(ExpressionComponent::new_conditional(condition, fold_branch(then.deref()), fold_branch(otherwise.deref())).with_position(*position));
+            }
+            Addition {left, right, position} => (Operator::Addition, left, right, *position),
+            Subtraction {left, right, position} => (Operator::Subtraction, left, right, *position),
+            Multiplication {left, right, position} => (Operator::Multiplication, left, right, *position),
+            Division {left, right, position} => (Operator::Division, left, right, *position),
+            Modulo {left, right, position} => (Operator::Modulo, left, right, *position),
+            Power {left, right, position} => (Operator::Power, left, right, *position),
+            LessThan {left, right, position} => (Operator::LessThan, left, right, *position),
+            GreaterThan {left, right, position} => (Operator::GreaterThan, left, right, *position),
+            Equal {left, right, position} => (Operator::Equal, left, right, *position),
+        };
+        let left = left.fold_constants()?;
+        let right = right.fold_constants()?;
+        let value = match (&left, &right) {
+            (RingElement(left_value), RingElement(right_value)) => operator.apply::<R>(left_value, right_value)
+                .map_err(|err| EvaluateExpressionError::from_operation_error(operator.name(), position, err)),
+            _ => return Ok(ExpressionComponent::new_binary_operation(operator, left, right).with_position(position)),
+        };
+        Self::fold_result(value, || ExpressionComponent::new_binary_operation(operator, left, right).with_position(position))
+    }
+
+    /// Ring element of a folded operation, or the operation given by `unfolded` if the result is
+    /// not in the ring.
+    fn fold_result(
+        value: EvaluateExpressionResult<R::RingElementType>,
+        unfolded: impl FnOnce() -> ExpressionComponent<R>) -> EvaluateExpressionResult<ExpressionComponent<R>>
+    {
+        match value {
+            Ok(element) => Ok(RingElement(element)),
+            Err(EvaluateExpressionError { kind: RingErrorKind::NotInRing { .. }, .. }) => Ok(unfolded()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Simplify the expression by the identities `x + 0 = 0 + x = x`, `x - 0 = x`,
+    /// `x * 1 = 1 * x = x`, `x * 0 = 0 * x = 0` and `x * -1 = -1 * x = -x`, where `0`, `1` and
+    /// `-1` are the literals equal to [Ring::zero], [Ring::one] and its negation by [Ring::neg],
+    /// by removing double negation `-(-x)`, and by replacing the negation of a literal by the
+    /// literal negated with [Ring::neg]. A negation that fails, e.g. of `i64::MIN` in the
+    /// integers, is kept, such that evaluation reports the error. Parentheses are kept, except
+    /// around a removed double negation. Simplification is applied bottom-up and is idempotent. Note that `x * 0`
+    /// simplifies to `0` even if evaluating `x` fails.
+    pub fn simplify_identities(&self) -> ExpressionComponent<R> {
+        self.simplify_rec(false)
+    }
+
+    /// Simplify the expression as [simplify_identities](Self::simplify_identities) and also remove
+    /// redundant parentheses. Parentheses are redundant around the whole expression and around
+    /// operands that are not operations, e.g. `(x)` or `((x + y))`. Simplification is idempotent.
+    pub fn simplify(&self) -> ExpressionComponent<R> {
+        let mut simplified = self.simplify_rec(true);
+        match simplified {
+            Parentheses { .. } => simplified.take_inner().expect("parentheses"),
+            _ => simplified,
+        }
+    }
+
+    fn simplify_rec(&self, remove_parentheses: bool) -> ExpressionComponent<R> {
+        let (operator, left, right, position) = match self {
+            RingElement(element) => return RingElement(element.clone()),
+            Variable(name) => return Variable(name.clone()),
+            FunctionCall { name, args, position } => {
+                return ExpressionComponent::new_function_call(name.clone(), args.iter().map(|arg| arg.simplify_rec(remove_parentheses)).collect())
+                    .with_position(*position);
+            }
+            Parentheses { inner, position } => {
+                let simplified = inner.simplify_rec(remove_parentheses);
+                return if !remove_parentheses || simplified.is_operator() || matches!(simplified, UnaryMinus { .. }) {
+                    ExpressionComponent::new_parenteses(simplified).with_position(*position)
+                } else {
+                    simplified
+                };
+            }
+            UnaryMinus { inner, position } => return inner.simplify_rec(remove_parentheses).simplified_negation(*position),
+            Conditional { condition, then, otherwise, position } => {
+                return ExpressionComponent::new_conditional(
+                    condition.simplify_rec(remove_parentheses),
+                    then.simplify_rec(remove_parentheses),
+                    otherwise.simplify_rec(remove_parentheses))
+                    .with_position(*position);
+            }
+            Addition {left, right, position} => (Operator::Addition, left, right, *position),
+            Subtraction {left, right, position} => (Operator::Subtraction, left, right, *position),
+            Multiplication {left, right, position} => (Operator::Multiplication, left, right, *position),
+            Division {left, right, position} => (Operator::Division, left, right, *position),
+            Modulo {left, right, position} => (Operator::Modulo, left, right, *position),
+            Power {left, right, position} => (Operator::Power, left, right, *position),
+            LessThan {left, right, position} => (Operator::LessThan, left, right, *position),
+            GreaterThan {left, right, position} => (Operator::GreaterThan, left, right, *position),
+            Equal {left, right, position} => (Operator::Equal, left, right, *position),
+        };
+        let left = left.simplify_rec(remove_parentheses);
+        let right = right.simplify_rec(remove_parentheses);
+        let zero = R::zero();
+        let one = R::one();
+        let minus_one = R::neg(&one).ok();
+        let is_minus_one = |expr: &ExpressionComponent<R>| minus_one.as_ref().is_some_and(|minus_one| expr.is_element(minus_one));
+        match operator {
+            Operator::Addition if left.is_element(&zero) => right,
+            Operator::Addition | Operator::Subtraction if right.is_element(&zero) => left,
+            Operator::Multiplication if left.is_element(&zero) || right.is_element(&zero) => RingElement(zero),
+            Operator::Multiplication if left.is_element(&one) => right,
+            Operator::Multiplication if right.is_element(&one) => left,
+            Operator::Multiplication if is_minus_one(&left) => right.simplified_negation(position),
+            Operator::Multiplication if is_minus_one(&right) => left.simplified_negation(position),
+            _ => ExpressionComponent::new_binary_operation(operator, left, right).with_position(position),
+        }
+    }
+
+    /// Negation at `position` of the simplified expression, removing double negation and negating
+    /// literals.
+    fn simplified_negation(mut self, position: SourcePosition) -> ExpressionComponent<R> {
+        if let Parentheses { inner: parenthesized, .. } = &mut self {
+            if matches!(**parenthesized, UnaryMinus { .. }) {
+                return parenthesized.take_inner().expect("unary minus");
+            }
+        }
+        match self {
+            UnaryMinus { .. } => self.take_inner().expect("unary minus"),
+            RingElement(ref element) => match R::neg(element) {
+                Ok(negated) => RingElement(negated),
+                Err(_) => ExpressionComponent::new_unary_minus(self).with_position(position),
+            },
+            _ => ExpressionComponent::new_unary_minus(self).with_position(position),
+        }
+    }
+
+    /// Rewrite subtractions from zero, `0 - x`, to negations, `-x`, where `0` is the literal equal
+    /// to [Ring::zero], possibly in parentheses. The canonical form of negation is hence
+    /// [UnaryMinus], which gets the source position of the subtraction. The two forms evaluate
+    /// identically but are structurally different, so passes comparing expressions should
+    /// canonicalize first.
+    pub fn canonicalize_negation(&self) -> ExpressionComponent<R> {
+        let (operator, left, right, position) = match self {
+            RingElement(element) => return RingElement(element.clone()),
+            Variable(name) => return Variable(name.clone()),
+            FunctionCall { name, args, position } => {
+                return ExpressionComponent::new_function_call(name.clone(), args.iter().map(ExpressionComponent::canonicalize_negation).collect())
+                    .with_position(*position);
+            }
+            Parentheses { inner, position } => return ExpressionComponent::new_parenteses(inner.canonicalize_negation()).with_position(*position),
+            UnaryMinus { inner, position } => return ExpressionComponent::new_unary_minus(inner.canonicalize_negation()).with_position(*position),
+            Conditional { condition, then, otherwise, position } => {
+                return ExpressionComponent::new_conditional(condition.canonicalize_negation(), then.canonicalize_negation(), otherwise.canonicalize_negation())
+                    .with_position(*position);
+            }
+            Subtraction { left, right, position } if left.is_element(&R::zero()) => {
+                return ExpressionComponent::new_unary_minus(right.canonicalize_negation()).with_position(*position);
+            }
+            Addition {left, right, position} => (Operator::Addition, left, right, *position),
+            Subtraction {left, right, position} => (Operator::Subtraction, left, right, *position),
+            Multiplication {left, right, position} => (Operator::Multiplication, left, right, *position),
+            Division {left, right, position} => (Operator::Division, left, right, *position),
+            Modulo {left, right, position} => (Operator::Modulo, left, right, *position),
+            Power {left, right, position} => (Operator::Power, left, right, *position),
+            LessThan {left, right, position} => (Operator::LessThan, left, right, *position),
+            GreaterThan {left, right, position} => (Operator::GreaterThan, left, right, *position),
+            Equal {left, right, position} => (Operator::Equal, left, right, *position),
+        };
+        ExpressionComponent::new_binary_operation(operator, left.canonicalize_negation(), right.canonicalize_negation())
+            .with_position(position)
+    }
+
+    /// Whether the expression is a literal equal to `element`, possibly in parentheses.
+    fn is_element(&self, element: &R::RingElementType) -> bool {
+        match self {
+            RingElement(value) => value == element,
+            Parentheses { inner, .. } => inner.is_element(element),
+            _ => false,
+        }
+    }
+
+    fn evaluate_observed<'a, O: FnMut(RingOpEvent<R>) -> RingResult<()>>(
+        &'a self,
+        bindings: &Bindings<'a, R>,
+        observer: &mut O) -> EvaluateExpressionResult<Cow<'a, R::RingElementType>>
+    {
+        self.evaluate_with_threshold(bindings, observer, EVALUATE_RECURSION_THRESHOLD)
+    }
+
+    fn evaluate_with_threshold<'a, O: FnMut(RingOpEvent<R>) -> RingResult<()>>(
+        &'a self,
+        bindings: &Bindings<'a, R>,
+        observer: &mut O,
+        recursion_threshold: usize) -> EvaluateExpressionResult<Cow<'a, R::RingElementType>>
+    {
+        if self.deeper_than(recursion_threshold) {
+            self.evaluate_iteratively(bindings, observer)
+        } else {
+            self.evaluate_recursively(bindings, observer)
+        }
+    }
+
+    /// Whether the [depth](Self::depth) of the expression exceeds `max_depth`. Recurses at most
+    /// `max_depth` levels, stops at the first node deeper than that and does not allocate.
+    fn deeper_than(&self, max_depth: usize) -> bool {
+        let Some(max_depth) = max_depth.checked_sub(1) else {
+            return true;
+        };
+        match self {
+            RingElement(_) | Variable(_) => false,
+            FunctionCall { args, .. } => args.iter().any(|arg| arg.deeper_than(max_depth)),
+            Parentheses { inner, .. } | UnaryMinus { inner, .. } => inner.deeper_than(max_depth),
+            Conditional { condition, then, otherwise, .. } => {
+                condition.deeper_than(max_depth) || then.deeper_than(max_depth) || otherwise.deeper_than(max_depth)
+            }
+            Addition { left, right, .. }
+            | Subtraction { left, right, .. }
+            | Multiplication { left, right, .. }
+            | Division { left, right, .. }
+            | Modulo { left, right, .. }
+            | Power { left, right, .. }
+            | LessThan { left, right, .. }
+            | GreaterThan { left, right, .. }
+            | Equal { left, right, .. } => left.deeper_than(max_depth) || right.deeper_than(max_depth),
+        }
+    }
+
+    fn evaluate_recursively<'a, O: FnMut(RingOpEvent<R>) -> RingResult<()>>(
+        &'a self,
+        bindings: &Bindings<'a, R>,
+        observer: &mut O) -> EvaluateExpressionResult<Cow<'a, R::RingElementType>>
+    {
+        // single call sites for operations keep the stack frame small for deep expressions
+        let (operator, left, right, position) = match self {
+            RingElement(r) => return Ok(Cow::Borrowed(r)),
+            Variable(name) => return Self::variable_value(name, bindings),
+            FunctionCall { name, args, position } => return Self::evaluate_function_call(name, args, *position, bindings, observer),
+            Parentheses { inner, .. } => return inner.evaluate_recursively(bindings, observer),
+            UnaryMinus { inner, position } => return Self::evaluate_negation(inner, *position, bindings, observer),
+            Conditional { condition, then, otherwise, .. } => return Self::evaluate_conditional(condition, then, otherwise, bindings, observer),
+            Addition {left, right, position} => (Operator::Addition, left, right, *position),
+            Subtraction {left, right, position} => (Operator::Subtraction, left, right, *position),
+            Multiplication {left, right, position} => (Operator::Multiplication, left, right, *position),
+            Division {left, right, position} => (Operator::Division, left, right, *position),
+            Modulo {left, right, position} => (Operator::Modulo, left, right, *position),
+            Power {left, right, position} => (Operator::Power, left, right, *position),
+            LessThan {left, right, position} => (Operator::LessThan, left, right, *position),
+            GreaterThan {left, right, position} => (Operator::GreaterThan, left, right, *position),
+            Equal {left, right, position} => (Operator::Equal, left, right, *position),
+        };
+        Self::evaluate_binary_operation(operator, left, right, position, bindings, observer)
+    }
+
+    fn evaluate_binary_operation<'a, O: FnMut(RingOpEvent<R>) -> RingResult<()>>(
+        operator: Operator,
+        left: &ExpressionComponent<R>,
+        right: &ExpressionComponent<R>,
+        position: SourcePosition,
+        bindings: &Bindings<R>,
+        observer: &mut O) -> EvaluateExpressionResult<Cow<'a, R::RingElementType>>
+    {
+        let left_value = left.evaluate_recursively(bindings, observer)?;
+        let right_value = right.evaluate_recursively(bindings, observer)?;
+        let result = operator.apply::<R>(&left_value, &right_value);
+        let observed = observer(RingOpEvent { operator, left: &left_value, right: &right_value, result: &result });
+        result.and_then(|value| observed.map(|()| value))
+            .map(Cow::Owned)
+            .map_err(|err| EvaluateExpressionError::from_operation_error(operator.name(), position, err))
+    }
+
+    fn evaluate_negation<'a, O: FnMut(RingOpEvent<R>) -> RingResult<()>>(
+        inner: &ExpressionComponent<R>,
+        position: SourcePosition,
+        bindings: &Bindings<R>,
+        observer: &mut O) -> EvaluateExpressionResult<Cow<'a, R::RingElementType>>
+    {
+        let value = inner.evaluate_recursively(bindings, observer)?;
+        Ok(Cow::Owned(Self::negate(&value, position)?))
+    }
+
+    fn evaluate_conditional<'a, O: FnMut(RingOpEvent<R>) -> RingResult<()>>(
+        condition: &'a ExpressionComponent<R>,
+        then: &'a ExpressionComponent<R>,
+        otherwise: &'a ExpressionComponent<R>,
+        bindings: &Bindings<'a, R>,
+        observer: &mut O) -> EvaluateExpressionResult<Cow<'a, R::RingElementType>>
+    {
+        let condition_value = condition.evaluate_recursively(bindings, observer)?;
+        let branch = if Self::holds(&condition_value) { then } else { otherwise };
+        branch.evaluate_recursively(bindings, observer)
+    }
+
+    fn evaluate_function_call<'a, O: FnMut(RingOpEvent<R>) -> RingResult<()>>(
+        name: &str,
+        args: &[ExpressionComponent<R>],
+        position: SourcePosition,
+        bindings: &Bindings<R>,
+        observer: &mut O) -> EvaluateExpressionResult<Cow<'a, R::RingElementType>>
+    {
+        let mut values = Vec::with_capacity(args.len());
+        for arg in args {
+            values.push(arg.evaluate_recursively(bindings, observer)?.into_owned());
+        }
+        Ok(Cow::Owned(Self::apply_bound_function(name, position, &values, bindings)?))
+    }
+
+    /// Evaluate expression with an explicit stack instead of recursion. Operations are evaluated
+    /// and observed in the same order as by [ExpressionComponent::evaluate_recursively].
+    fn evaluate_iteratively<'a, O: FnMut(RingOpEvent<R>) -> RingResult<()>>(
+        &'a self,
+        bindings: &Bindings<'a, R>,
+        observer: &mut O) -> EvaluateExpressionResult<Cow<'a, R::RingElementType>>
+    {
+        enum Step<'a, R: Ring> {
+            Visit(&'a ExpressionComponent<R>),
+            Negate(SourcePosition),
+            Apply(Operator, SourcePosition),
+            Call(&'a str, usize, SourcePosition),
+            /// Evaluate one of the branches of a conditional, whose condition is evaluated.
+            Branch(&'a ExpressionComponent<R>, &'a ExpressionComponent<R>),
+        }
+
+        let mut steps = vec![Step::Visit(self)];
+        let mut values: Vec<Cow<'a, R::RingElementType>> = Vec::new();
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Visit(expr) => {
+                    let (operator, left, right, position) = match expr {
+                        RingElement(r) => {
+                            values.push(Cow::Borrowed(r));
+                            continue;
+                        }
+                        Variable(name) => {
+                            values.push(Self::variable_value(name, bindings)?);
+                            continue;
+                        }
+                        FunctionCall { name, args, position } => {
+                            steps.push(Step::Call(name, args.len(), *position));
+                            steps.extend(args.iter().rev().map(Step::Visit));
+                            continue;
+                        }
+                        Parentheses { inner, .. } => {
+                            steps.push(Step::Visit(inner));
+                            continue;
+                        }
+                        UnaryMinus { inner, position } => {
+                            steps.push(Step::Negate(*position));
+                            steps.push(Step::Visit(inner));
+                            continue;
+                        }
+                        Conditional { condition, then, otherwise, .. } => {
+                            steps.push(Step::Branch(then, otherwise));
+                            steps.push(Step::Visit(condition));
+                            continue;
+                        }
+                        Addition {left, right, position} => (Operator::Addition, left, right, *position),
+                        Subtraction {left, right, position} => (Operator::Subtraction, left, right, *position),
+                        Multiplication {left, right, position} => (Operator::Multiplication, left, right, *position),
+                        Division {left, right, position} => (Operator::Division, left, right, *position),
+                        Modulo {left, right, position} => (Operator::Modulo, left, right, *position),
+                        Power {left, right, position} => (Operator::Power, left, right, *position),
+                        LessThan {left, right, position} => (Operator::LessThan, left, right, *position),
+                        GreaterThan {left, right, position} => (Operator::GreaterThan, left, right, *position),
+                        Equal {left, right, position} => (Operator::Equal, left, right, *position),
+                    };
+                    steps.push(Step::Apply(operator, position));
+                    steps.push(Step::Visit(right));
+                    steps.push(Step::Visit(left));
+                }
+                Step::Negate(position) => {
+                    let value = values.pop().unwrap();
+                    values.push(Cow::Owned(Self::negate(&value, position)?));
+                }
+                Step::Apply(operator, position) => {
+                    let right_value = values.pop().unwrap();
+                    let left_value = values.pop().unwrap();
+                    let result = operator.apply::<R>(&left_value, &right_value);
+                    let observed = observer(RingOpEvent { operator, left: &left_value, right: &right_value, result: &result });
+                    values.push(Cow::Owned(result.and_then(|value| observed.map(|()| value)).map_err(|err| EvaluateExpressionError::from_operation_error(operator.name(), position, err))?));
+                }
+                Step::Branch(then, otherwise) => {
+                    let condition_value = values.pop().unwrap();
+                    steps.push(Step::Visit(if Self::holds(&condition_value) { then } else { otherwise }));
+                }
+                Step::Call(name, arguments, position) => {
+                    let args: Vec<_> = values.drain(values.len() - arguments..).map(Cow::into_owned).collect();
+                    values.push(Cow::Owned(Self::apply_bound_function(name, position, &args, bindings)?));
+                }
+            }
+        }
+
+        debug_assert_eq!(1, values.len());
+        Ok(values.pop().unwrap())
+    }
+
+    /// Whether the condition of a [Conditional] with the value `value` holds, which is when it is
+    /// not [Ring::zero].
+    fn holds(value: &R::RingElementType) -> bool {
+        *value != R::zero()
+    }
+
+    fn negate(value: &R::RingElementType, position: SourcePosition) -> EvaluateExpressionResult<R::RingElementType> {
+        R::neg(value).map_err(|err| EvaluateExpressionError::from_operation_error("negation", position, err))
+    }
+
+    /// Apply the function `name` of the ring to `args`, failing if there is no such function or
+    /// it takes a different number of arguments.
+    fn apply_function(name: &str, position: SourcePosition, args: &[R::RingElementType]) -> EvaluateExpressionResult<R::RingElementType> {
+        Self::apply_bound_function(name, position, args, &Bindings::None)
+    }
+
+    /// Apply the function `name` to `args` as for [apply_function](Self::apply_function), using
+    /// functions in the context of `bindings` instead of the functions of the ring.
+    fn apply_bound_function(
+        name: &str,
+        position: SourcePosition,
+        args: &[R::RingElementType],
+        bindings: &Bindings<R>) -> EvaluateExpressionResult<R::RingElementType>
+    {
+        let context_function = match bindings {
+            Bindings::Context(ctx) => ctx.function(name),
+            _ => None,
+        };
+        let apply = match context_function {
+            Some(function) => function.check_arity(name, args.len()).map(|()| function.apply),
+            None => RingFunction::<R>::resolve(name, args.len()).map(|function| function.apply),
+        }.map_err(|err| EvaluateExpressionError { message: err.message, kind: err.kind, position: position.position, span: position.span_range() })?;
+        apply(args).map_err(|err| EvaluateExpressionError::from_operation_error(name, position, err))
+    }
+
+    fn variable_value<'a>(name: &str, bindings: &Bindings<'a, R>) -> EvaluateExpressionResult<Cow<'a, R::RingElementType>> {
+        let value = match bindings {
+            Bindings::None => None,
+            Bindings::Env(env) => env.get(name).map(Cow::Borrowed),
+            Bindings::Resolver(resolver) => resolver(name).map(Cow::Owned),
+            Bindings::Context(ctx) => ctx.variable(name).map(Cow::Borrowed),
+        };
+        value.ok_or_else(|| Self::unbound_variable(name))
+    }
+
+    fn unbound_variable(name: &str) -> EvaluateExpressionError {
+        EvaluateExpressionError {
+            message: format!("Unbound variable '{}'", name),
+            kind: RingErrorKind::Unspecified,
+            position: None,
+            span: None,
+        }
+    }
+}
+
+impl<R: Ring> ExpressionComponent<R> {
+    /// Clone expression without recursion, failing if the expression is nested deeper than
+    /// `max_depth`. A single ring element has depth 1. Can be used to safely clone expressions
+    /// from untrusted sources, where the derived [Clone] could overflow the stack.
+    pub fn try_clone_depth_limited(&self, max_depth: usize) -> Result<Self, DepthError> {
+        enum Step<'a, R: Ring> {
+            Visit(&'a ExpressionComponent<R>, usize),
+            Build(&'a ExpressionComponent<R>),
+        }
+
+        let mut steps = vec![Step::Visit(self, 1)];
+        let mut clones: Vec<ExpressionComponent<R>> = Vec::new();
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Visit(expr, depth) => {
+                    if depth > max_depth {
+                        return Err(DepthError { max_depth });
+                    }
+                    match expr {
+                        RingElement(element) => clones.push(RingElement(element.clone())),
+                        Variable(name) => clones.push(Variable(name.clone())),
+                        FunctionCall { args, .. } => {
+                            steps.push(Step::Build(expr));
+                            steps.extend(args.iter().rev().map(|arg| Step::Visit(arg, depth + 1)));
+                        }
+                        Parentheses { inner, .. } | UnaryMinus { inner, .. } => {
+                            steps.push(Step::Build(expr));
+                            steps.push(Step::Visit(inner, depth + 1));
+                        }
+                        Conditional { condition, then, otherwise, .. } => {
+                            steps.push(Step::Build(expr));
+                            steps.push(Step::Visit(otherwise, depth + 1));
+                            steps.push(Step::Visit(then, depth + 1));
+                            steps.push(Step::Visit(condition, depth + 1));
+                        }
+                        Addition { left, right, .. }
+                        | Subtraction { left, right, .. }
+                        | Multiplication { left, right, .. }
+                        | Division { left, right, .. }
+                        | Modulo { left, right, .. }
+                        | Power { left, right, .. }
+                        | LessThan { left, right, .. }
+                        | GreaterThan { left, right, .. }
+                        | Equal { left, right, .. } => {
+                            steps.push(Step::Build(expr));
+                            steps.push(Step::Visit(right, depth + 1));
+                            steps.push(Step::Visit(left, depth + 1));
+                        }
+                    }
+                }
+                Step::Build(expr) => {
+                    let clone = match expr {
+                        RingElement(_) | Variable(_) => panic!("Ring elements and variables are cloned when visited"),
+                        FunctionCall { name, args, position } => {
+                            let args = clones.split_off(clones.len() - args.len());
+                            ExpressionComponent::new_function_call(name.clone(), args).with_position(*position)
+                        }
+                        Parentheses { position, .. } => ExpressionComponent::new_parenteses(clones.pop().unwrap()).with_position(*position),
+                        UnaryMinus { position, .. } => ExpressionComponent::new_unary_minus(clones.pop().unwrap()).with_position(*position),
+                        Conditional { position, .. } => {
+                            let otherwise = clones.pop().unwrap();
+                            let then = clones.pop().unwrap();
+                            let condition = clones.pop().unwrap();
+                            ExpressionComponent::new_conditional(condition, then, otherwise).with_position(*position)
+                        }
+                        Addition { .. } | Subtraction { .. } | Multiplication { .. } | Division { .. } | Modulo { .. } | Power { .. } | LessThan { .. } | GreaterThan { .. } | Equal { .. } => {
+                            let right = clones.pop().unwrap();
+                            let left = clones.pop().unwrap();
+                            let construct_expression = match expr {
+                                Addition { .. } => ExpressionComponent::new_addition,
+                                Subtraction { .. } => ExpressionComponent::new_subtraction,
+                                Multiplication { .. } => ExpressionComponent::new_multiplication,
+                                Division { .. } => ExpressionComponent::new_division,
+                                Modulo { .. } => ExpressionComponent::new_modulo,
+                                LessThan { .. } => ExpressionComponent::new_less_than,
+                                GreaterThan { .. } => ExpressionComponent::new_greater_than,
+                                Equal { .. } => ExpressionComponent::new_equal,
+                                _ => ExpressionComponent::new_power,
+                            };
+                            construct_expression(left, right).with_position(expr.position())
+                        }
+                    };
+                    clones.push(clone);
+                }
+            }
+        }
+
+        debug_assert_eq!(1, clones.len());
+        Ok(clones.pop().unwrap())
+    }
+
+    /// Convert the expression to an expression in the ring `S` with the same structure, by
+    /// applying `f` to each ring element. Source positions are kept.
+    pub fn map_ring_elements<S: Ring, F>(&self, f: &F) -> ExpressionComponent<S>
+        where F: Fn(&R::RingElementType) -> S::RingElementType
+    {
+        match self {
+            RingElement(element) => ExpressionComponent::new_ring_element(f(element)),
+            Variable(name) => Variable(name.clone()),
+            FunctionCall { name, args, position } => {
+                ExpressionComponent::new_function_call(name.clone(), args.iter().map(|arg| arg.map_ring_elements(f)).collect()).with_position(*position)
+            }
+            Parentheses { inner, position } => ExpressionComponent::new_parenteses(inner.map_ring_elements(f)).with_position(*position),
+            UnaryMinus { inner, position } => ExpressionComponent::new_unary_minus(inner.map_ring_elements(f)).with_position(*position),
+            Conditional { condition, then, otherwise, position } => {
+                ExpressionComponent::new_conditional(condition.map_ring_elements(f), then.map_ring_elements(f), otherwise.map_ring_elements(f))
+                    .with_position(*position)
+            }
+            Addition { left, right, position } => {
+                ExpressionComponent::new_addition(left.map_ring_elements(f), right.map_ring_elements(f)).with_position(*position)
+            }
+            Subtraction { left, right, position } => {
+                ExpressionComponent::new_subtraction(left.map_ring_elements(f), right.map_ring_elements(f)).with_position(*position)
+            }
+            Multiplication { left, right, position } => {
+                ExpressionComponent::new_multiplication(left.map_ring_elements(f), right.map_ring_elements(f)).with_position(*position)
+            }
+            Division { left, right, position } => {
+                ExpressionComponent::new_division(left.map_ring_elements(f), right.map_ring_elements(f)).with_position(*position)
+            }
+            Modulo { left, right, position } => {
+                ExpressionComponent::new_modulo(left.map_ring_elements(f), right.map_ring_elements(f)).with_position(*position)
+            }
+            Power { left, right, position } => {
+                ExpressionComponent::new_power(left.map_ring_elements(f), right.map_ring_elements(f)).with_position(*position)
+            }
+            LessThan { left, right, position } => {
+                ExpressionComponent::new_less_than(left.map_ring_elements(f), right.map_ring_elements(f)).with_position(*position)
+            }
+            GreaterThan { left, right, position } => {
+                ExpressionComponent::new_greater_than(left.map_ring_elements(f), right.map_ring_elements(f)).with_position(*position)
+            }
+            Equal { left, right, position } => {
+                ExpressionComponent::new_equal(left.map_ring_elements(f), right.map_ring_elements(f)).with_position(*position)
+            }
+        }
+    }
+}
+
+impl<R: Ring> ExpressionComponent<R> {
+    fn operands_mut(&mut self) -> impl Iterator<Item=&mut ExpressionComponent<R>> {
+        let (operands, args): ([Option<&mut ExpressionComponent<R>>; 3], &mut [ExpressionComponent<R>]) = match self {
+            RingElement(_) | Variable(_) => ([None, None, None], &mut []),
+            FunctionCall { args, .. } => ([None, None, None], args),
+            Parentheses { inner, .. } | UnaryMinus { inner, .. } => ([Some(inner), None, None], &mut []),
+            Conditional { condition, then, otherwise, .. } => ([Some(condition), Some(then), Some(otherwise)], &mut []),
+            Addition { left, right, .. }
+            | Subtraction { left, right, .. }
+            | Multiplication { left, right, .. }
+            | Division { left, right, .. }
+            | Modulo { left, right, .. }
+            | Power { left, right, .. }
+            | LessThan { left, right, .. }
+            | GreaterThan { left, right, .. }
+            | Equal { left, right, .. } => ([Some(left), Some(right), None], &mut []),
+        };
+        operands.into_iter().flatten().chain(args)
+    }
+
+    fn is_leaf(&self) -> bool {
+        matches!(self, RingElement(_) | Variable(_))
+    }
+
+    fn has_nested_operands(&mut self) -> bool {
+        self.operands_mut().any(|operand| !operand.is_leaf())
+    }
+
+    /// Move the operand out of parentheses or a unary minus, leaving a placeholder, see
+    /// [detach_nested_operands](Self::detach_nested_operands).
+    fn take_inner(&mut self) -> Option<ExpressionComponent<R>> {
+        match self {
+            Parentheses { inner, .. } | UnaryMinus { inner, .. } => Some(mem::replace(inner, Variable(String::new()))),
+            _ => None,
+        }
+    }
+
+    /// Replace operands that are not leaves by a placeholder and move them to `detached`. The
+    /// placeholder is a variable with an empty name, which does not allocate.
+    fn detach_nested_operands(&mut self, detached: &mut Vec<ExpressionComponent<R>>) {
+        for operand in self.operands_mut() {
+            if !operand.is_leaf() {
+                detached.push(mem::replace(operand, Variable(String::new())));
+            }
+        }
+    }
+}
+
+/// Drop expression without recursion. The derived drop would recurse once per level of nesting
+/// and can overflow the stack for deeply nested expressions.
+impl<R: Ring> Drop for ExpressionComponent<R> {
+    fn drop(&mut self) {
+        if !self.has_nested_operands() {
+            return;
+        }
+
+        let mut detached = Vec::new();
+        self.detach_nested_operands(&mut detached);
+        while let Some(mut expr) = detached.pop() {
+            expr.detach_nested_operands(&mut detached);
+        }
+    }
+}
+
+impl<R: Ring> Neg for ExpressionComponent<R> {
+    type Output = ExpressionComponent<R>;
+
+    fn neg(self) -> Self::Output {
+        ExpressionComponent::new_unary_minus(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::ring::intring::{IntRingElement, IntRing};
+    use crate::expression::{ExpressionComponent, EvaluateExpressionError, Operator, DepthError, ProvenanceStep, EvalContext};
+    use crate::expression::ring::{RingError, DivisionRemainder, RingFunction};
+    use crate::expression::ring::RingErrorKind::{Overflow, NotInRing, Unspecified, DivisionByZero};
+    use crate::expression::parser::{parse_int_ring_expression, parse_int_ring_calculator_expression, parse_rational_ring_expression, parse_mod_ring_expression, parse_big_int_ring_expression};
+    use std::borrow::Cow;
+    use std::collections::HashMap;
+    use std::io;
+
+    #[test]
+    fn from_operands() {
+        let operands = (1..=4).map(ExpressionComponent::new_int_element).collect();
+
+        let expression = ExpressionComponent::from_operands(Operator::Addition, operands).expect("some");
+
+        assert_eq!(ExpressionComponent::new_addition(
+            ExpressionComponent::new_addition(
+                ExpressionComponent::new_addition(
+                    ExpressionComponent::new_int_element(1),
+                    ExpressionComponent::new_int_element(2)),
+                ExpressionComponent::new_int_element(3)),
+            ExpressionComponent::new_int_element(4)), expression);
+        assert_eq!(Ok(IntRingElement::new(10)), expression.evaluate());
+    }
+
+    #[test]
+    fn from_operands_empty_and_single() {
+        assert_eq!(None, ExpressionComponent::<IntRing>::from_operands(Operator::Subtraction, vec![]));
+        assert_eq!(Some(ExpressionComponent::new_int_element(5)),
+                   ExpressionComponent::from_operands(Operator::Multiplication, vec![ExpressionComponent::new_int_element(5)]));
+    }
+
+    #[test]
+    fn from_operands_empty_identity() {
+        assert_eq!(Some(ExpressionComponent::new_int_element(0)),
+                   ExpressionComponent::<IntRing>::from_operands(Operator::Addition, vec![]));
+        assert_eq!(Some(ExpressionComponent::new_int_element(1)),
+                   ExpressionComponent::<IntRing>::from_operands(Operator::Multiplication, vec![]));
+    }
+
+    #[test]
+    fn simple_value() {
+        let element = IntRingElement::new(5);
+        let expression = ExpressionComponent::<IntRing>::new_ring_element(element.clone());
+
+        assert_eq!(Ok(element), expression.evaluate());
+    }
+
+    #[test]
+    fn addition() {
+        let expression =
+            ExpressionComponent::<IntRing>::new_addition(
+                ExpressionComponent::new_ring_element(IntRingElement::new(5)),
+                ExpressionComponent::new_ring_element(IntRingElement::new(7)));
+
+        assert_eq!(Ok(IntRingElement::new(12)), expression.evaluate());
+    }
+
+    #[test]
+    fn addition_overflow() {
+        let expression =
+            ExpressionComponent::<IntRing>::new_addition(
+                ExpressionComponent::new_ring_element(IntRingElement::new(i64::MAX)),
+                ExpressionComponent::new_ring_element(IntRingElement::new(7)));
+
+        assert_eq!(Err(EvaluateExpressionError {message: "Overflow during addition".to_string(), kind: Overflow, position: None, span: None}), expression.evaluate());
+    }
+
+    #[test]
+    fn subtraction() {
+        let expression =
+            ExpressionComponent::<IntRing>::new_subtraction(
+                ExpressionComponent::new_ring_element(IntRingElement::new(5)),
+                ExpressionComponent::new_ring_element(IntRingElement::new(7)));
+
+        assert_eq!(Ok(IntRingElement::new(-2)), expression.evaluate());
+    }
+
+    #[test]
+    fn multiplication() {
+        let expression =
+            ExpressionComponent::<IntRing>::new_multiplication(
+                ExpressionComponent::new_ring_element(IntRingElement::new(5)),
+                ExpressionComponent::new_ring_element(IntRingElement::new(7)));
+
+        assert_eq!(Ok(IntRingElement::new(35)), expression.evaluate());
+    }
+
+    #[test]
+    fn division() {
+        let expression =
+            ExpressionComponent::<IntRing>::new_division(
+                ExpressionComponent::new_ring_element(IntRingElement::new(6)),
+                ExpressionComponent::new_ring_element(IntRingElement::new(2)));
+
+        assert_eq!(Ok(IntRingElement::new(3)), expression.evaluate());
+    }
+
+    #[test]
+    fn parenthesis() {
+        let expression =
+            ExpressionComponent::<IntRing>::new_parenteses(
+                ExpressionComponent::new_ring_element(IntRingElement::new(5)));
+
+        assert_eq!(Ok(IntRingElement::new(5)), expression.evaluate());
+    }
+
+    #[test]
+    fn neg() {
+        let expression = -ExpressionComponent::from(5);
+
+        assert_eq!(ExpressionComponent::new_unary_minus(ExpressionComponent::new_int_element(5)), expression);
+        assert_eq!(Ok(IntRingElement::new(-5)), expression.evaluate());
+    }
+
+    #[test]
+    fn evaluate_cow_element() {
+        let expression = ExpressionComponent::new_parenteses(ExpressionComponent::new_int_element(5));
+
+        let result = expression.evaluate_cow().expect("ok");
+
+        assert!(matches!(result, Cow::Borrowed(_)));
+        assert_eq!(IntRingElement::new(5), *result);
+    }
+
+    #[test]
+    fn evaluate_cow_operation() {
+        let expression = ExpressionComponent::new_addition(
+            ExpressionComponent::new_int_element(5),
+            ExpressionComponent::new_int_element(7));
+
+        let result = expression.evaluate_cow().expect("ok");
+
+        assert!(matches!(result, Cow::Owned(_)));
+        assert_eq!(IntRingElement::new(12), *result);
+    }
+
+    #[test]
+    fn into_io_error() {
+        let err = EvaluateExpressionError { message: "Overflow".to_string(), kind: Overflow, position: None, span: None };
+
+        let io_err: io::Error = err.into();
+
+        assert_eq!(io::ErrorKind::InvalidData, io_err.kind());
+        assert_eq!("Error evaluating expression: Overflow", io_err.to_string());
+    }
+
+    #[test]
+    fn evaluate_annotated() {
+        let src = "2 + 5 * 1";
+        let expression = parse_int_ring_expression(src).expect("ok");
+
+        let annotations = expression.evaluate_annotated(src).expect("ok");
+
+        assert_eq!(vec![
+            (0..1, IntRingElement::new(2)),
+            (4..5, IntRingElement::new(5)),
+            (8..9, IntRingElement::new(1)),
+            (4..9, IntRingElement::new(5)),
+            (0..9, IntRingElement::new(7))], annotations);
+    }
+
+    #[test]
+    fn evaluate_annotated_unary_parentheses_percent() {
+        let src = "-(3 - -4) + 200%";
+        let expression = parse_int_ring_calculator_expression(src).expect("ok");
+
+        let annotations = expression.evaluate_annotated(src).expect("ok");
+
+        assert_eq!(vec![
+            (2..3, IntRingElement::new(3)),
+            (6..8, IntRingElement::new(-4)),
+            (2..8, IntRingElement::new(7)),
+            (1..9, IntRingElement::new(7)),
+            (0..9, IntRingElement::new(-7)),
+            (12..15, IntRingElement::new(200)),
+            (12..16, IntRingElement::new(2)),
+            (0..16, IntRingElement::new(-5))], annotations);
+    }
+
+    #[test]
+    fn evaluate_annotated_function_call() {
+        let src = "1 + gcd(4, 6)";
+        let expression = parse_int_ring_expression(src).expect("ok");
+
+        let annotations = expression.evaluate_annotated(src).expect("ok");
+
+        assert_eq!(vec![
+            (0..1, IntRingElement::new(1)),
+            (8..9, IntRingElement::new(4)),
+            (11..12, IntRingElement::new(6)),
+            (4..13, IntRingElement::new(2)),
+            (0..13, IntRingElement::new(3))], annotations);
+    }
+
+    #[test]
+    fn evaluate_annotated_conditional() {
+        let src = "1 > 0 ? 5 : 1 / 0";
+        let expression = parse_int_ring_expression(src).expect("ok");
+
+        let annotations = expression.evaluate_annotated(src).expect("ok");
+
+        assert_eq!(vec![
+            (0..1, IntRingElement::new(1)),
+            (4..5, IntRingElement::new(0)),
+            (0..5, IntRingElement::new(1)),
+            (8..9, IntRingElement::new(5)),
+            (0..17, IntRingElement::new(5))], annotations);
+    }
+
+    #[test]
+    fn evaluate_annotated_source_mismatch() {
+        let expression = parse_int_ring_expression("2 + 5").expect("ok");
+
+        assert_eq!(Err(EvaluateExpressionError{message: "Expression does not match source".to_string(), kind: Unspecified, position: None, span: None}),
+                   expression.evaluate_annotated("2 * 5"));
+        assert!(expression.evaluate_annotated("2 + 5 + 1").is_err());
+    }
+
+    #[test]
+    fn evaluate_annotated_literal_mismatch() {
+        let expression = parse_int_ring_expression("2 + 5 * 1").expect("ok");
+
+        assert_eq!(Err(EvaluateExpressionError{message: "Expression does not match source".to_string(), kind: Unspecified, position: None, span: None}),
+                   expression.evaluate_annotated("2 + 5 * 3"));
+        assert!(parse_int_ring_expression("2 - -5").expect("ok").evaluate_annotated("2 - 5").is_err());
+        assert!(parse_int_ring_expression("2 - -5").expect("ok").evaluate_annotated("2 - -5").is_ok());
+    }
+
+    #[test]
+    fn evaluate_annotated_big_int_ring() {
+        let src = "100000000000000000000 * 2";
+        let expression = parse_big_int_ring_expression(src).expect("ok");
+
+        let annotations: Vec<_> = expression.evaluate_annotated(src).expect("ok").into_iter()
+            .map(|(span, value)| (span, value.to_string()))
+            .collect();
+
+        assert_eq!(vec![
+            (0..21, "100000000000000000000".to_string()),
+            (24..25, "2".to_string()),
+            (0..25, "200000000000000000000".to_string())], annotations);
+    }
+
+    #[test]
+    fn enclosing_paren_of_error() {
+        let expression = parse_int_ring_expression("3 + (10 / 0) * 2").expect("ok");
+
+        assert_eq!(Some(4..12), expression.enclosing_paren_of_error());
+        assert_eq!(Some(5..24), parse_int_ring_expression("(1 + ( 2 * 3 / (1 - 1) ) )").expect("ok").enclosing_paren_of_error());
+        assert_eq!(None, parse_int_ring_expression("(1 + 1) / 0").expect("ok").enclosing_paren_of_error());
+        assert_eq!(None, parse_int_ring_expression("(10 / 2)").expect("ok").enclosing_paren_of_error());
+    }
+
+    #[test]
+    fn fold_constants() {
+        let expression = parse_int_ring_expression("2 + 3 * 4").expect("ok");
+
+        assert_eq!(Ok(ExpressionComponent::new_int_element(14)), expression.fold_constants());
+    }
+
+    #[test]
+    fn fold_constants_not_in_ring() {
+        let expression = parse_int_ring_expression("7 / 2 * 2 + (1 + 1)").expect("ok");
+
+        assert_eq!(Ok(ExpressionComponent::new_addition(
+            ExpressionComponent::new_multiplication(
+                ExpressionComponent::new_division(
+                    ExpressionComponent::new_int_element(7),
+                    ExpressionComponent::new_int_element(2)),
+                ExpressionComponent::new_int_element(2)),
+            ExpressionComponent::new_int_element(2))), expression.fold_constants());
+    }
+
+    #[test]
+    fn fold_constants_conditional() {
+        let fold = |str| parse_int_ring_expression(str).expect("ok").fold_constants();
+
+        assert_eq!(Ok(ExpressionComponent::new_int_element(5)), fold("1 > 0 ? 5 : (1 / 0)"));
+        assert_eq!(Ok(ExpressionComponent::new_int_element(6)), fold("0 ? x : 2 * 3"));
+        assert_eq!(Ok(ExpressionComponent::new_conditional(
+            ExpressionComponent::new_variable("x"),
+            ExpressionComponent::new_int_element(3),
+            ExpressionComponent::new_division(
+                ExpressionComponent::new_int_element(1),
+                ExpressionComponent::new_int_element(0)))), fold("x ? 1 + 2 : 1 / 0"));
+    }
+
+    #[test]
+    fn fold_constants_overflow() {
+        let expression = parse_int_ring_expression("1 + 9223372036854775807 * 2").expect("ok");
+
+        assert_eq!(Err(EvaluateExpressionError { message: "Overflow during multiplication".to_string(), kind: Overflow, position: Some(24), span: Some(4..27) }),
+                   expression.fold_constants());
+    }
+
+    #[test]
+    fn simplify_identities() {
+        let simplify = |str| parse_int_ring_expression(str).expect("ok").simplify();
+        let expected = |str| parse_int_ring_expression(str).expect("ok");
+
+        assert_eq!(expected("x"), simplify("x + 0"));
+        assert_eq!(expected("x"), simplify("0 + x"));
+        assert_eq!(expected("x"), simplify("x - 0"));
+        assert_eq!(expected("0 - x"), simplify("0 - x"));
+        assert_eq!(expected("x"), simplify("x * 1"));
+        assert_eq!(expected("x"), simplify("1 * x"));
+        assert_eq!(expected("0"), simplify("x * 0"));
+        assert_eq!(expected("0"), simplify("0 * (x + y)"));
+        assert_eq!(expected("x / 1"), simplify("x / 1"));
+    }
+
+    #[test]
+    fn simplify_parentheses_and_negation() {
+        let simplify = |str| parse_int_ring_expression(str).expect("ok").simplify();
+        let expected = |str| parse_int_ring_expression(str).expect("ok");
+
+        assert_eq!(expected("x + y"), simplify("(x + y)"));
+        assert_eq!(expected("2 * x * (y + 1)"), simplify("2 * ((x)) * ((y + 1))"));
+        assert_eq!(expected("2 * (-x)"), simplify("2 * (-x)"));
+        assert_eq!(expected("x"), simplify("-(-x)"));
+        assert_eq!(expected("2 * (x + y)"), simplify("2 * -(-(x + y))"));
+        assert_eq!(expected("-x"), simplify("-(-(-x))"));
+    }
+
+    #[test]
+    fn simplify_negated_literal() {
+        let simplify = |str| parse_int_ring_expression(str).expect("ok").simplify();
+
+        assert_eq!(ExpressionComponent::new_int_element(5), simplify("--5"));
+        assert_eq!(ExpressionComponent::new_int_element(-5), simplify("- 5"));
+        assert_eq!(ExpressionComponent::new_int_element(-5), simplify("-(5)"));
+        assert_eq!(ExpressionComponent::new_int_element(-5), simplify("5 * -1"));
+        assert_eq!(parse_int_ring_expression("-(5)").expect("ok"), parse_int_ring_expression("-(5)").expect("ok").simplify_identities());
+    }
+
+    #[test]
+    fn simplify_negated_literal_overflow() {
+        let expression = -ExpressionComponent::new_int_element(i64::MIN);
+
+        assert_eq!(expression, expression.simplify());
+        assert_eq!(expression, expression.simplify_identities());
+        assert_eq!(Err(EvaluateExpressionError { message: "Overflow during negation".to_string(), kind: Overflow, position: None, span: None }),
+                   expression.simplify().evaluate());
+
+        let expression = ExpressionComponent::new_multiplication(
+            ExpressionComponent::new_int_element(1),
+            -ExpressionComponent::new_int_element(i64::MIN));
+        assert_eq!(-ExpressionComponent::new_int_element(i64::MIN), expression.simplify());
+    }
+
+    #[test]
+    fn simplify_combined() {
+        let expression = parse_int_ring_expression("(x + 0) * 1").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_variable("x"), expression.simplify());
+
+        for str in ["(x + 0) * 1", "2 * -(-(y * 1 - 0)) + (0 * z)", "((x) + (1 * (y + 0)))", "-(-(-(0 + x)))"] {
+            let simplified = parse_int_ring_expression(str).expect("ok").simplify();
+            assert_eq!(simplified, simplified.simplify(), "{}", str);
+        }
+    }
+
+    #[test]
+    fn simplify_identities_keeps_parentheses() {
+        let expression = parse_int_ring_expression("((x + 0) * 1) * -1 + -(-(y))").expect("ok");
+
+        assert_eq!(parse_int_ring_expression("-((x)) + (y)").expect("ok"), expression.simplify_identities());
+        assert_eq!(parse_int_ring_expression("-x + y").expect("ok"), expression.simplify());
+    }
+
+    #[test]
+    fn simplify_identities_in_other_rings() {
+        let expression = parse_rational_ring_expression("(x * 1 + 0) / 2 - 0 * y").expect("ok");
+        assert_eq!(parse_rational_ring_expression("(x) / 2").expect("ok"), expression.simplify_identities());
+
+        // 4 is -1 and 5 is 0 modulo 5
+        let expression = parse_mod_ring_expression::<5>("x * 4 + 5 * y").expect("ok");
+        assert_eq!(-ExpressionComponent::new_variable("x"), expression.simplify_identities());
+    }
+
+    #[test]
+    fn canonicalize_negation() {
+        let subtraction = parse_int_ring_expression("2 * (0 - 3)").expect("ok");
+        let negation = ExpressionComponent::new_multiplication(
+            ExpressionComponent::new_int_element(2),
+            ExpressionComponent::new_parenteses(-ExpressionComponent::new_int_element(3)));
+
+        assert_ne!(negation, subtraction);
+        assert_eq!(negation, subtraction.canonicalize_negation());
+        assert_eq!(negation, negation.canonicalize_negation());
+        assert_eq!(subtraction.evaluate(), subtraction.canonicalize_negation().evaluate());
+    }
+
+    #[test]
+    fn canonicalize_negation_keeps_position() {
+        let expression = parse_int_ring_expression("1 + (0 - (-9223372036854775807 - 1))").expect("ok");
+
+        let canonical = expression.canonicalize_negation();
+
+        assert_eq!(parse_int_ring_expression("1 + (-(-9223372036854775807 - 1))").expect("ok"), canonical);
+        assert_eq!(Err(EvaluateExpressionError { message: "Overflow during negation".to_string(), kind: Overflow, position: Some(7), span: Some(5..35) }),
+                   canonical.evaluate());
+    }
+
+    #[test]
+    fn canonicalize_negation_in_other_rings() {
+        let expression = parse_rational_ring_expression("(0) - x").expect("ok");
+        assert_eq!(-ExpressionComponent::new_variable("x"), expression.canonicalize_negation());
+
+        // 5 is 0 modulo 5
+        let expression = parse_mod_ring_expression::<5>("5 - x").expect("ok");
+        assert_eq!(-ExpressionComponent::new_variable("x"), expression.canonicalize_negation());
+    }
+
+    #[test]
+    fn evaluate_with_provenance() {
+        let expression = parse_int_ring_expression("2 * 3 + 4").expect("ok");
+
+        let (value, provenance) = expression.evaluate_with_provenance().expect("ok");
+
+        assert_eq!(IntRingElement::new(10), value);
+        assert_eq!(vec![
+            ProvenanceStep { operator: Operator::Multiplication, left: IntRingElement::new(2), right: IntRingElement::new(3), result: IntRingElement::new(6) },
+            ProvenanceStep { operator: Operator::Addition, left: IntRingElement::new(6), right: IntRingElement::new(4), result: IntRingElement::new(10) }],
+                   provenance.steps);
+        assert_eq!("2 * 3 = 6\n6 + 4 = 10\n", provenance.to_string());
+    }
+
+    #[test]
+    fn evaluate_with_observer() {
+        let expression = ExpressionComponent::new_multiplication(
+            ExpressionComponent::new_multiplication(
+                ExpressionComponent::new_int_element(2),
+                ExpressionComponent::new_int_element(3)),
+            ExpressionComponent::new_addition(
+                ExpressionComponent::new_int_element(4),
+                ExpressionComponent::new_int_element(0)));
+
+        let mut multiplications = 0;
+        let mut operations = Vec::new();
+        let result = expression.evaluate_with_observer(&mut |event| {
+            if event.operator == Operator::Multiplication {
+                multiplications += 1;
+            }
+            operations.push((event.operator, event.left.clone(), event.right.clone(), event.result.clone()));
+        });
+
+        assert_eq!(Ok(IntRingElement::new(24)), result);
+        assert_eq!(2, multiplications);
+        assert_eq!(vec![
+            (Operator::Multiplication, IntRingElement::new(2), IntRingElement::new(3), Ok(IntRingElement::new(6))),
+            (Operator::Addition, IntRingElement::new(4), IntRingElement::new(0), Ok(IntRingElement::new(4))),
+            (Operator::Multiplication, IntRingElement::new(6), IntRingElement::new(4), Ok(IntRingElement::new(24))),
+        ], operations);
+    }
+
+    #[test]
+    fn evaluate_with_observer_error() {
+        let expression = ExpressionComponent::new_division(
+            ExpressionComponent::new_int_element(5),
+            ExpressionComponent::new_int_element(2));
+
+        let mut results = Vec::new();
+        let result = expression.evaluate_with_observer(&mut |event| results.push(event.result.clone()));
+
+        assert_eq!(Err(EvaluateExpressionError { message: "Result not in ring during division".to_string(), kind: NotInRing { suggested_ring: Some("rational"), remainder: Some(Box::new(DivisionRemainder { quotient: "2".to_string(), remainder: "1".to_string() })) }, position: None, span: None }), result);
+        assert_eq!(vec![Err(RingError { message: "Result not in ring".to_string(), kind: NotInRing { suggested_ring: Some("rational"), remainder: Some(Box::new(DivisionRemainder { quotient: "2".to_string(), remainder: "1".to_string() })) } })], results);
+    }
+
+    #[test]
+    fn evaluate_with_checked_observer_stops() {
+        let expression = parse_int_ring_expression("2 * 3 + 4 * 5").expect("ok");
+
+        let mut operations = 0;
+        let result = expression.evaluate_with_checked_observer(&mut |event| {
+            operations += 1;
+            match event.result {
+                Ok(value) if value.value() > 5 => Err(RingError { message: "Too big".to_string(), kind: Overflow }),
+                _ => Ok(()),
+            }
+        });
+
+        assert_eq!(Err(EvaluateExpressionError { message: "Too big during multiplication".to_string(), kind: Overflow, position: Some(2), span: Some(0..5) }), result);
+        assert_eq!(1, operations);
+    }
+
+    #[test]
+    fn deeper_than() {
+        let expression = parse_int_ring_expression("1 + abs(-(2 * 3))").expect("ok");
+
+        assert_eq!(6, expression.depth());
+        assert!(expression.deeper_than(5));
+        assert!(!expression.deeper_than(6));
+        assert!(ExpressionComponent::new_int_element(1).deeper_than(0));
+        assert!(!ExpressionComponent::new_int_element(1).deeper_than(1));
+    }
+
+    #[test]
+    fn try_clone_depth_limited() {
+        let expression = ExpressionComponent::new_subtraction(
+            ExpressionComponent::new_parenteses(
+                ExpressionComponent::new_addition(
+                    ExpressionComponent::new_int_element(1),
+                    ExpressionComponent::new_unary_minus(ExpressionComponent::new_int_element(2)))),
+            ExpressionComponent::new_division(
+                ExpressionComponent::new_int_element(3),
+                ExpressionComponent::new_int_element(4)));
+
+        assert_eq!(Ok(expression.clone()), expression.try_clone_depth_limited(5));
+        assert_eq!(Err(DepthError { max_depth: 4 }), expression.try_clone_depth_limited(4));
+    }
+
+    #[test]
+    fn try_clone_depth_limited_deep() {
+        let mut expression = ExpressionComponent::new_int_element(0);
+        for i in 1..1000 {
+            expression = ExpressionComponent::new_addition(expression, ExpressionComponent::new_int_element(i));
+        }
+
+        let clone = expression.try_clone_depth_limited(1000).expect("ok");
+        assert_eq!(Ok(IntRingElement::new(999 * 1000 / 2)), clone.evaluate());
+        assert_eq!(Err(DepthError { max_depth: 999 }), expression.try_clone_depth_limited(999));
+    }
+
+    #[test]
+    fn depth() {
+        let expression = parse_int_ring_expression("1 + -(2 * 3)").expect("ok");
+
+        assert_eq!(1, ExpressionComponent::new_int_element(1).depth());
+        assert_eq!(5, expression.depth());
+    }
+
+    #[test]
+    fn evaluate_with_recursion_threshold_shallow() {
+        let expression = parse_int_ring_expression("2 * -(3 + 4) ^ 2 - 7 mod 4 + gcd(12, abs(-18))").expect("ok");
+
+        assert_eq!(Ok(IntRingElement::new(-95)), expression.evaluate_with_recursion_threshold(usize::MAX));
+        assert_eq!(Ok(IntRingElement::new(-95)), expression.evaluate_with_recursion_threshold(0));
+        assert_eq!(Err(EvaluateExpressionError { message: "Division by zero during division".to_string(), kind: DivisionByZero, position: Some(2), span: Some(0..11) }),
+                   parse_int_ring_expression("1 / (2 - 2)").expect("ok").evaluate_with_recursion_threshold(0));
+    }
+
+    #[test]
+    fn evaluate_with_recursion_threshold_conditional() {
+        for (str, value) in [("1 > 0 ? 5 : (1 / 0)", 5), ("1 < 0 ? 1 / 0 : 2 * 3", 6), ("0 ? 1 : 0 ? 1 / 0 : 3", 3)] {
+            let expression = parse_int_ring_expression(str).expect("ok");
+
+            assert_eq!(Ok(IntRingElement::new(value)), expression.evaluate_with_recursion_threshold(0), "{}", str);
+            assert_eq!(Ok(IntRingElement::new(value)), expression.evaluate_with_recursion_threshold(usize::MAX), "{}", str);
+        }
+        assert_eq!(Err(EvaluateExpressionError { message: "Division by zero during division".to_string(), kind: DivisionByZero, position: Some(10), span: Some(8..13) }),
+                   parse_int_ring_expression("0 ? 2 : 1 / 0").expect("ok").evaluate_with_recursion_threshold(0));
+    }
+
+    #[test]
+    fn evaluate_with_recursion_threshold_deep() {
+        let mut expression = ExpressionComponent::new_int_element(0);
+        for i in 1..200_000 {
+            expression = ExpressionComponent::new_parenteses(ExpressionComponent::new_subtraction(
+                ExpressionComponent::new_int_element(i),
+                -expression));
+        }
+
+        assert_eq!(Ok(IntRingElement::new(199_999 * 200_000 / 2)), expression.evaluate_with_recursion_threshold(0));
+        assert_eq!(expression.evaluate_with_recursion_threshold(0), expression.evaluate());
+    }
+
+    #[test]
+    fn drop_deep() {
+        let mut expression = ExpressionComponent::new_int_element(0);
+        for i in 1..200_000 {
+            expression = ExpressionComponent::new_parenteses(
+                ExpressionComponent::new_addition(expression, ExpressionComponent::new_int_element(i)));
+        }
+
+        drop(expression);
+    }
+
+    #[test]
+    fn evaluate_map_leaves() {
+        let expression = ExpressionComponent::<IntRing>::new_addition(
+            ExpressionComponent::new_int_element(3),
+            ExpressionComponent::new_int_element(5));
+
+        let result = expression.evaluate_map_leaves(|elm| Ok(IntRingElement::new(elm.value() * 2)));
+
+        assert_eq!(Ok(IntRingElement::new(16)), result);
+    }
+
+    #[test]
+    fn evaluate_map_leaves_error() {
+        let expression = ExpressionComponent::<IntRing>::new_addition(
+            ExpressionComponent::new_int_element(-3),
+            ExpressionComponent::new_int_element(5));
+        let reject_negative = |elm: &IntRingElement| if elm.value() < 0 {
+            Err(RingError { message: "Negative leaf".to_string(), kind: Unspecified })
+        } else {
+            Ok(elm.clone())
+        };
+
+        let result = expression.evaluate_map_leaves(reject_negative);
+
+        assert_eq!(Err(EvaluateExpressionError { message: "Negative leaf".to_string(), kind: Unspecified, position: None, span: None }), result);
+    }
+
+    #[test]
+    fn evaluate_error_names_operation() {
+        let expression = ExpressionComponent::new_multiplication(
+            ExpressionComponent::new_int_element(i64::MAX),
+            ExpressionComponent::new_int_element(2));
+
+        assert_eq!(Err(EvaluateExpressionError { message: "Overflow during multiplication".to_string(), kind: Overflow, position: None, span: None }), expression.evaluate());
+    }
+
+    #[test]
+    fn unary_minus() {
+        let expression = -ExpressionComponent::new_parenteses(ExpressionComponent::new_addition(
+            ExpressionComponent::new_int_element(2),
+            ExpressionComponent::new_int_element(3)));
+
+        assert_eq!(Ok(IntRingElement::new(-5)), expression.evaluate());
+        assert_eq!(Ok(IntRingElement::new(5)), (-expression).evaluate());
+    }
+
+    #[test]
+    fn unary_minus_overflow() {
+        let expression = -ExpressionComponent::new_int_element(i64::MIN);
+
+        assert_eq!(Err(EvaluateExpressionError { message: "Overflow during negation".to_string(), kind: Overflow, position: None, span: None }), expression.evaluate());
+    }
+
+    #[test]
+    fn evaluate_into() {
+        let expression = ExpressionComponent::new_multiplication(
+            ExpressionComponent::new_int_element(6),
+            ExpressionComponent::new_int_element(7));
+
+        assert_eq!(Ok(42i64), expression.evaluate_into::<i64>());
+        assert_eq!(Ok("42".to_string()), expression.evaluate_into::<String>());
+    }
+
+    #[test]
+    fn drop_deep_parentheses() {
+        let mut expression = ExpressionComponent::new_int_element(1);
+        for _ in 0..200_000 {
+            expression = ExpressionComponent::new_parenteses(expression);
+        }
+
+        drop(expression);
+    }
+
+    #[test]
+    fn evaluate_with_variable() {
+        let expression = parse_int_ring_expression("x + 1").expect("ok");
+        let env = HashMap::from([("x".to_string(), IntRingElement::new(4))]);
+
+        assert_eq!(ExpressionComponent::new_addition(
+            ExpressionComponent::new_variable("x"),
+            ExpressionComponent::new_int_element(1)), expression);
+        assert_eq!(Ok(IntRingElement::new(5)), expression.evaluate_with(&env));
+    }
+
+    #[test]
+    fn evaluate_with_resolver() {
+        let expression = parse_int_ring_expression("ten * ten").expect("ok");
+        let resolver = |name: &str| (name == "ten").then(|| IntRingElement::new(10));
+
+        assert_eq!(Ok(IntRingElement::new(100)), expression.evaluate_with_resolver(resolver));
+        assert_eq!(Err(EvaluateExpressionError { message: "Unbound variable 'ten'".to_string(), kind: Unspecified, position: None, span: None }),
+                   expression.evaluate_with_resolver(|_| None));
+    }
+
+    #[test]
+    fn evaluate_in_context() {
+        let expression = parse_int_ring_expression("gcd(x, 12)").expect("ok");
+        let ctx = EvalContext::new().with_variable("x", IntRingElement::new(18));
+
+        assert_eq!(Ok(IntRingElement::new(6)), expression.evaluate_in(&ctx));
+        assert_eq!(Err(EvaluateExpressionError { message: "Unbound variable 'x'".to_string(), kind: Unspecified, position: None, span: None }),
+                   expression.evaluate());
+    }
+
+    #[test]
+    fn evaluate_in_context_functions() {
+        let ctx = EvalContext::new()
+            .with_function("double", RingFunction { arity: 1, apply: |args| Operator::Addition.apply::<IntRing>(&args[0], &args[0]) })
+            .with_function("abs", RingFunction { arity: 1, apply: |_| Ok(IntRingElement::new(0)) });
+
+        let double = |args| ExpressionComponent::new_function_call("double", args);
+
+        assert_eq!(Ok(IntRingElement::new(14)), double(vec![ExpressionComponent::new_int_element(7)]).evaluate_in(&ctx));
+        assert_eq!(Ok(IntRingElement::new(0)), parse_int_ring_expression("abs(-7)").expect("ok").evaluate_in(&ctx));
+        assert_eq!(Ok(IntRingElement::new(6)), parse_int_ring_expression("gcd(18, 12)").expect("ok").evaluate_in(&ctx));
+        assert_eq!(Err(EvaluateExpressionError { message: "Function 'double' takes 1 argument, not 2".to_string(), kind: Unspecified, position: None, span: None }),
+                   double(vec![ExpressionComponent::new_int_element(1), ExpressionComponent::new_int_element(2)]).evaluate_in(&ctx));
+        assert_eq!(Err(EvaluateExpressionError { message: "Unknown function 'double'".to_string(), kind: Unspecified, position: None, span: None }),
+                   double(vec![ExpressionComponent::new_int_element(7)]).evaluate());
+    }
+
+    #[test]
+    fn evaluate_unbound_variable() {
+        let expression = parse_int_ring_expression("2 * (x + y)").expect("ok");
+        let env = HashMap::from([("x".to_string(), IntRingElement::new(4))]);
+
+        assert_eq!(Err(EvaluateExpressionError { message: "Unbound variable 'y'".to_string(), kind: Unspecified, position: None, span: None }),
+                   expression.evaluate_with(&env));
+        assert_eq!(Err(EvaluateExpressionError { message: "Unbound variable 'x'".to_string(), kind: Unspecified, position: None, span: None }),
+                   expression.evaluate());
+    }
+
 }
\ No newline at end of file
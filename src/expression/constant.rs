@@ -0,0 +1,345 @@
+use crate::expression::{EvaluateExpressionError, EvaluateExpressionResult, ExpressionComponent, Operator};
+use crate::expression::ExpressionComponent::{RingElement, Variable, FunctionCall, Addition, Subtraction, Multiplication, Division, Modulo, Power, LessThan, GreaterThan, Equal, Conditional, Parentheses, UnaryMinus};
+use crate::expression::ring::{Ring, RingErrorKind};
+use std::fmt::{Display, Formatter};
+use std::error;
+
+/// Maximum number of operands waiting for an operator in [evaluate_int_postfix].
+pub const MAX_POSTFIX_OPERANDS: usize = 32;
+
+/// Item of an expression in postfix notation with elements of type `E`, e.g. `2 * (3 + 4)` is
+/// `[Element(2), Element(3), Element(4), Operator(Addition), Operator(Multiplication)]`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum PostfixItem<E = i64> {
+    Element(E),
+    Operator(Operator),
+    UnaryMinus,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum ConstEvaluateErrorKind {
+    Overflow,
+    DivisionByZero,
+    /// Inexact division or negative exponent.
+    NotInRing,
+    /// More than [MAX_POSTFIX_OPERANDS] operands are waiting for an operator.
+    TooManyOperands,
+    /// Operator without enough operands, or no items at all.
+    MissingOperand,
+    /// Operands left without an operator at the end of the items.
+    MissingOperator,
+}
+
+/// Error evaluating a postfix expression with [evaluate_int_postfix].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct ConstEvaluateError {
+    pub kind: ConstEvaluateErrorKind,
+    /// Index of the item where evaluation failed.
+    pub position: usize,
+}
+
+impl ConstEvaluateError {
+    pub fn message(&self) -> &'static str {
+        match self.kind {
+            ConstEvaluateErrorKind::Overflow => "Overflow",
+            ConstEvaluateErrorKind::DivisionByZero => "Division by zero",
+            ConstEvaluateErrorKind::NotInRing => "Result not in ring",
+            ConstEvaluateErrorKind::TooManyOperands => "Too many operands waiting for an operator",
+            ConstEvaluateErrorKind::MissingOperand => "Missing operand",
+            ConstEvaluateErrorKind::MissingOperator => "Missing operator",
+        }
+    }
+}
+
+impl Display for ConstEvaluateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Error evaluating postfix expression at item {}: {}", self.position, self.message())
+    }
+}
+
+impl error::Error for ConstEvaluateError {
+}
+
+impl From<ConstEvaluateError> for EvaluateExpressionError {
+    fn from(err: ConstEvaluateError) -> Self {
+        let kind = match err.kind {
+            ConstEvaluateErrorKind::Overflow => RingErrorKind::Overflow,
+            ConstEvaluateErrorKind::DivisionByZero => RingErrorKind::DivisionByZero,
+            ConstEvaluateErrorKind::NotInRing => RingErrorKind::NotInRing { suggested_ring: Some("rational"), remainder: None },
+            _ => RingErrorKind::Unspecified,
+        };
+        EvaluateExpressionError { message: err.message().to_string(), kind, position: None, span: None }
+    }
+}
+
+/// Evaluate an [IntRing](crate::expression::ring::intring::IntRing) expression given in postfix
+/// notation. The operations are those of `IntRing`: they fail on overflow, division must be exact,
+/// modulo is euclidean and exponents must be nonnegative. Since this is a `const fn`, it can
+/// evaluate expressions at compile time, e.g. in the initializer of a `const`.
+///
+/// Limitations compared to [ExpressionComponent]:
+/// the expression is a flat array rather than a tree, so parentheses are implied by the order of
+/// the items, only `i64` elements are supported, and at most [MAX_POSTFIX_OPERANDS] operands can
+/// wait for an operator at any point. Errors carry the index of the failing item but, since
+/// `const fn` cannot allocate, no message beyond [ConstEvaluateError::message].
+pub const fn evaluate_int_postfix(items: &[PostfixItem]) -> Result<i64, ConstEvaluateError> {
+    let mut operands = [0i64; MAX_POSTFIX_OPERANDS];
+    let mut length = 0;
+    let mut position = 0;
+    while position < items.len() {
+        match items[position] {
+            PostfixItem::Element(value) => {
+                if length == MAX_POSTFIX_OPERANDS {
+                    return Err(ConstEvaluateError { kind: ConstEvaluateErrorKind::TooManyOperands, position });
+                }
+                operands[length] = value;
+                length += 1;
+            }
+            PostfixItem::UnaryMinus => {
+                if length == 0 {
+                    return Err(ConstEvaluateError { kind: ConstEvaluateErrorKind::MissingOperand, position });
+                }
+                operands[length - 1] = match operands[length - 1].checked_neg() {
+                    Some(value) => value,
+                    None => return Err(ConstEvaluateError { kind: ConstEvaluateErrorKind::Overflow, position }),
+                };
+            }
+            PostfixItem::Operator(operator) => {
+                if length < 2 {
+                    return Err(ConstEvaluateError { kind: ConstEvaluateErrorKind::MissingOperand, position });
+                }
+                length -= 1;
+                operands[length - 1] = match apply(operator, operands[length - 1], operands[length]) {
+                    Ok(value) => value,
+                    Err(kind) => return Err(ConstEvaluateError { kind, position }),
+                };
+            }
+        }
+        position += 1;
+    }
+    match length {
+        1 => Ok(operands[0]),
+        0 => Err(ConstEvaluateError { kind: ConstEvaluateErrorKind::MissingOperand, position }),
+        _ => Err(ConstEvaluateError { kind: ConstEvaluateErrorKind::MissingOperator, position }),
+    }
+}
+
+const fn apply(operator: Operator, left: i64, right: i64) -> Result<i64, ConstEvaluateErrorKind> {
+    let result = match operator {
+        Operator::Addition => left.checked_add(right),
+        Operator::Subtraction => left.checked_sub(right),
+        Operator::Multiplication => left.checked_mul(right),
+        Operator::Division | Operator::Modulo if right == 0 => return Err(ConstEvaluateErrorKind::DivisionByZero),
+        Operator::Division => match left.checked_rem_euclid(right) {
+            Some(0) | None => left.checked_div(right),
+            Some(_) => return Err(ConstEvaluateErrorKind::NotInRing),
+        },
+        // the remainder only fails for i64::MIN mod -1, which is zero
+        Operator::Modulo => match left.checked_rem_euclid(right) {
+            None => Some(0),
+            remainder => remainder,
+        },
+        Operator::Power if right < 0 => return Err(ConstEvaluateErrorKind::NotInRing),
+        // only the bases 0, 1 and -1 do not overflow for exponents beyond u32::MAX
+        Operator::Power if right > u32::MAX as i64 => match left {
+            0 | 1 => Some(left),
+            -1 => Some(if right % 2 == 0 { 1 } else { -1 }),
+            _ => None,
+        },
+        Operator::Power => left.checked_pow(right as u32),
+        Operator::LessThan => Some((left < right) as i64),
+        Operator::GreaterThan => Some((left > right) as i64),
+        Operator::Equal => Some((left == right) as i64),
+    };
+    match result {
+        Some(value) => Ok(value),
+        None => Err(ConstEvaluateErrorKind::Overflow),
+    }
+}
+
+/// Scratch stack of [ExpressionComponent::write_postfix], holding the nodes waiting to be
+/// written paired with whether their operands have been written.
+pub type PostfixStack<'a, R> = Vec<(&'a ExpressionComponent<R>, bool)>;
+
+impl<R: Ring> ExpressionComponent<R> {
+    /// Append the expression in postfix notation to `out`, using `stack` as scratch space, such
+    /// that the buffers can be reused for many expressions. Parentheses are implied by the order
+    /// of the items. Fails for expressions with variables, function calls or conditionals, which
+    /// have no postfix item, in which case the items already appended are left in `out`. The stack is
+    /// empty when the call returns. Allocates only when `out` or `stack` grows beyond its
+    /// capacity, the stack to at most twice the depth of the expression, when cloning ring
+    /// elements that allocate, and for the error message on failure.
+    pub fn write_postfix<'a>(&'a self, out: &mut Vec<PostfixItem<R::RingElementType>>, stack: &mut PostfixStack<'a, R>) -> EvaluateExpressionResult<()> {
+        stack.clear();
+        stack.push((self, false));
+        while let Some((expression, operands_written)) = stack.pop() {
+            let (operator, left, right) = match expression {
+                RingElement(element) => {
+                    out.push(PostfixItem::Element(element.clone()));
+                    continue;
+                }
+                Variable(name) => {
+                    stack.clear();
+                    return Err(Self::not_in_postfix(format!("Variable '{}'", name)));
+                }
+                FunctionCall { name, .. } => {
+                    stack.clear();
+                    return Err(Self::not_in_postfix(format!("Function call '{}'", name)));
+                }
+                Conditional { .. } => {
+                    stack.clear();
+                    return Err(Self::not_in_postfix("Conditional".to_string()));
+                }
+                Parentheses { inner, .. } => {
+                    stack.push((inner, false));
+                    continue;
+                }
+                UnaryMinus { .. } if operands_written => {
+                    out.push(PostfixItem::UnaryMinus);
+                    continue;
+                }
+                UnaryMinus { inner, .. } => {
+                    stack.push((expression, true));
+                    stack.push((inner, false));
+                    continue;
+                }
+                Addition {left, right, ..} => (Operator::Addition, left, right),
+                Subtraction {left, right, ..} => (Operator::Subtraction, left, right),
+                Multiplication {left, right, ..} => (Operator::Multiplication, left, right),
+                Division {left, right, ..} => (Operator::Division, left, right),
+                Modulo {left, right, ..} => (Operator::Modulo, left, right),
+                Power {left, right, ..} => (Operator::Power, left, right),
+                LessThan {left, right, ..} => (Operator::LessThan, left, right),
+                GreaterThan {left, right, ..} => (Operator::GreaterThan, left, right),
+                Equal {left, right, ..} => (Operator::Equal, left, right),
+            };
+            if operands_written {
+                out.push(PostfixItem::Operator(operator));
+            } else {
+                stack.push((expression, true));
+                stack.push((right, false));
+                stack.push((left, false));
+            }
+        }
+        Ok(())
+    }
+
+    fn not_in_postfix(what: String) -> EvaluateExpressionError {
+        EvaluateExpressionError {
+            message: format!("{} cannot be written in postfix notation", what),
+            kind: RingErrorKind::Unspecified,
+            position: None,
+            span: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::constant::{evaluate_int_postfix, PostfixItem, ConstEvaluateError, ConstEvaluateErrorKind, MAX_POSTFIX_OPERANDS};
+    use crate::expression::constant::PostfixItem::{Element, Operator, UnaryMinus};
+    use crate::expression::Operator::{Addition, Subtraction, Multiplication, Division, Modulo, Power};
+    use crate::expression::parser::parse_int_ring_expression;
+    use crate::expression::ring::intring::IntRingElement;
+    use crate::expression::EvaluateExpressionError;
+    use crate::expression::ring::RingErrorKind::{Overflow, Unspecified};
+
+    // 10 - -(3 ^ 4 mod 5) * 2 / 2 + 1
+    const EXPRESSION: [PostfixItem; 14] = [
+        Element(10), Element(3), Element(4), Operator(Power), Element(5), Operator(Modulo), UnaryMinus,
+        Element(2), Operator(Multiplication), Element(2), Operator(Division), Operator(Subtraction),
+        Element(1), Operator(Addition)];
+
+    #[test]
+    fn evaluate_in_const_context() {
+        const VALUE: Result<i64, ConstEvaluateError> = evaluate_int_postfix(&EXPRESSION);
+
+        assert_eq!(Ok(12), VALUE);
+    }
+
+    #[test]
+    fn same_as_int_ring() {
+        let expression = parse_int_ring_expression("10 - -(3 ^ 4 mod 5) * 2 / 2 + 1").expect("ok");
+
+        assert_eq!(expression.evaluate().map(|value: IntRingElement| value.value()), evaluate_int_postfix(&EXPRESSION).map_err(Into::into));
+    }
+
+    #[test]
+    fn operation_errors() {
+        let error = |kind, position| Err(ConstEvaluateError { kind, position });
+
+        assert_eq!(error(ConstEvaluateErrorKind::Overflow, 2), evaluate_int_postfix(&[Element(i64::MAX), Element(1), Operator(Addition)]));
+        assert_eq!(error(ConstEvaluateErrorKind::Overflow, 1), evaluate_int_postfix(&[Element(i64::MIN), UnaryMinus]));
+        assert_eq!(error(ConstEvaluateErrorKind::DivisionByZero, 2), evaluate_int_postfix(&[Element(1), Element(0), Operator(Modulo)]));
+        assert_eq!(error(ConstEvaluateErrorKind::NotInRing, 2), evaluate_int_postfix(&[Element(5), Element(2), Operator(Division)]));
+        assert_eq!(error(ConstEvaluateErrorKind::NotInRing, 2), evaluate_int_postfix(&[Element(2), Element(-1), Operator(Power)]));
+        assert_eq!(Ok(0), evaluate_int_postfix(&[Element(i64::MIN), Element(-1), Operator(Modulo)]));
+    }
+
+    #[test]
+    fn power_huge_exponent() {
+        assert_eq!(Ok(1), evaluate_int_postfix(&[Element(1), Element(5_000_000_000), Operator(Power)]));
+        assert_eq!(Ok(0), evaluate_int_postfix(&[Element(0), Element(5_000_000_000), Operator(Power)]));
+        assert_eq!(Ok(1), evaluate_int_postfix(&[Element(-1), Element(5_000_000_000), Operator(Power)]));
+        assert_eq!(Ok(-1), evaluate_int_postfix(&[Element(-1), Element(5_000_000_001), Operator(Power)]));
+        assert_eq!(Err(ConstEvaluateError { kind: ConstEvaluateErrorKind::Overflow, position: 2 }),
+                   evaluate_int_postfix(&[Element(2), Element(5_000_000_000), Operator(Power)]));
+    }
+
+    #[test]
+    fn malformed_expressions() {
+        let error = |kind, position| Err(ConstEvaluateError { kind, position });
+
+        assert_eq!(error(ConstEvaluateErrorKind::MissingOperand, 0), evaluate_int_postfix(&[]));
+        assert_eq!(error(ConstEvaluateErrorKind::MissingOperand, 1), evaluate_int_postfix(&[Element(1), Operator(Addition)]));
+        assert_eq!(error(ConstEvaluateErrorKind::MissingOperator, 2), evaluate_int_postfix(&[Element(1), Element(2)]));
+        assert_eq!(error(ConstEvaluateErrorKind::TooManyOperands, MAX_POSTFIX_OPERANDS),
+                   evaluate_int_postfix(&[Element(1); MAX_POSTFIX_OPERANDS + 1]));
+    }
+
+    #[test]
+    fn into_evaluate_expression_error() {
+        let err = evaluate_int_postfix(&[Element(i64::MAX), Element(2), Operator(Multiplication)]).unwrap_err();
+
+        assert_eq!(EvaluateExpressionError { message: "Overflow".to_string(), kind: Overflow, position: None, span: None }, err.into());
+    }
+
+    #[test]
+    fn write_postfix() {
+        let expression = parse_int_ring_expression("10 - -(3 ^ 4 mod 5) * 2 / 2 + 1").expect("ok");
+        let mut out = Vec::new();
+        expression.write_postfix(&mut out, &mut Vec::new()).expect("ok");
+
+        assert_eq!(EXPRESSION.map(|item| match item {
+            Element(value) => Element(IntRingElement::new(value)),
+            Operator(operator) => Operator(operator),
+            UnaryMinus => UnaryMinus,
+        }).to_vec(), out);
+    }
+
+    #[test]
+    fn write_postfix_reuse_buffer() {
+        let expressions = ["2 * (3 + 4)", "5 - 1"].map(|str| parse_int_ring_expression(str).expect("ok"));
+        let mut out = Vec::new();
+        let mut stack = Vec::new();
+        expressions[0].write_postfix(&mut out, &mut stack).expect("ok");
+        let (out_capacity, stack_capacity) = (out.capacity(), stack.capacity());
+        out.clear();
+        expressions[1].write_postfix(&mut out, &mut stack).expect("ok");
+
+        assert_eq!(vec![Element(IntRingElement::new(5)), Element(IntRingElement::new(1)), Operator(Subtraction)], out);
+        assert_eq!((out_capacity, stack_capacity), (out.capacity(), stack.capacity()));
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn write_postfix_variable() {
+        let expression = parse_int_ring_expression("1 + x").expect("ok");
+        let mut out = Vec::new();
+        let mut stack = Vec::new();
+        let res = expression.write_postfix(&mut out, &mut stack);
+
+        assert_eq!(Err(EvaluateExpressionError { message: "Variable 'x' cannot be written in postfix notation".to_string(), kind: Unspecified, position: None, span: None }), res);
+        assert!(stack.is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+use crate::expression::{ExpressionComponent, EvaluateExpressionResult, EvaluateExpressionError, Operator, SourcePosition};
+use crate::expression::ExpressionComponent::{RingElement, Variable, FunctionCall, Addition, Subtraction, Multiplication, Division, Modulo, Power, LessThan, GreaterThan, Equal, Conditional, Parentheses, UnaryMinus};
+use crate::expression::ring::Ring;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Expression where equal ring elements in the leaves share storage behind [Rc]. Constructed
+/// by [ExpressionComponent::intern].
+///
+/// Interning pays off for large expressions with many repeated ring elements whose storage is
+/// larger than a pointer, since each distinct element is only stored once. For small elements,
+/// such as `i64`, the pointer and reference counts make each leaf larger, and interning
+/// requires a hash map of the distinct elements while the expression is constructed.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum InternedExpression<R: Ring> {
+    RingElement(Rc<R::RingElementType>),
+    Variable(String),
+    FunctionCall {
+        name: String,
+        args: Vec<InternedExpression<R>>,
+    },
+    Parentheses(Box<InternedExpression<R>>),
+    UnaryMinus(Box<InternedExpression<R>>),
+    Conditional {
+        condition: Box<InternedExpression<R>>,
+        then: Box<InternedExpression<R>>,
+        otherwise: Box<InternedExpression<R>>,
+    },
+    BinaryOperation {
+        operator: Operator,
+        left: Box<InternedExpression<R>>,
+        right: Box<InternedExpression<R>>
+    },
+}
+
+impl<R: Ring> ExpressionComponent<R> {
+    /// Convert the expression to an [InternedExpression] where equal ring elements share storage.
+    pub fn intern(&self) -> InternedExpression<R> {
+        self.intern_rec(&mut HashMap::new())
+    }
+
+    fn intern_rec(&self, elements: &mut HashMap<R::RingElementType, Rc<R::RingElementType>>) -> InternedExpression<R> {
+        let (operator, left, right) = match self {
+            RingElement(element) => {
+                let interned = elements.entry(element.clone()).or_insert_with(|| Rc::new(element.clone()));
+                return InternedExpression::RingElement(interned.clone());
+            }
+            Variable(name) => return InternedExpression::Variable(name.clone()),
+            FunctionCall { name, args, .. } => {
+                let args = args.iter().map(|arg| arg.intern_rec(elements)).collect();
+                return InternedExpression::FunctionCall { name: name.clone(), args };
+            }
+            Parentheses { inner, .. } => return InternedExpression::Parentheses(Box::new(inner.intern_rec(elements))),
+            UnaryMinus { inner, .. } => return InternedExpression::UnaryMinus(Box::new(inner.intern_rec(elements))),
+            Conditional { condition, then, otherwise, .. } => {
+                return InternedExpression::Conditional {
+                    condition: Box::new(condition.intern_rec(elements)),
+                    then: Box::new(then.intern_rec(elements)),
+                    otherwise: Box::new(otherwise.intern_rec(elements)),
+                };
+            }
+            Addition {left, right, ..} => (Operator::Addition, left, right),
+            Subtraction {left, right, ..} => (Operator::Subtraction, left, right),
+            Multiplication {left, right, ..} => (Operator::Multiplication, left, right),
+            Division {left, right, ..} => (Operator::Division, left, right),
+            Modulo {left, right, ..} => (Operator::Modulo, left, right),
+            Power {left, right, ..} => (Operator::Power, left, right),
+            LessThan {left, right, ..} => (Operator::LessThan, left, right),
+            GreaterThan {left, right, ..} => (Operator::GreaterThan, left, right),
+            Equal {left, right, ..} => (Operator::Equal, left, right),
+        };
+        InternedExpression::BinaryOperation {
+            operator,
+            left: Box::new(left.intern_rec(elements)),
+            right: Box::new(right.intern_rec(elements)),
+        }
+    }
+}
+
+impl<R: Ring> InternedExpression<R> {
+    /// Evaluate expression, see [ExpressionComponent::evaluate].
+    pub fn evaluate(&self) -> EvaluateExpressionResult<R::RingElementType> {
+        match self {
+            InternedExpression::RingElement(element) => Ok(element.as_ref().clone()),
+            InternedExpression::Variable(name) => Err(ExpressionComponent::<R>::unbound_variable(name)),
+            InternedExpression::FunctionCall { name, args } => {
+                let values = args.iter().map(InternedExpression::evaluate).collect::<EvaluateExpressionResult<Vec<_>>>()?;
+                ExpressionComponent::<R>::apply_function(name, SourcePosition::default(), &values)
+            }
+            InternedExpression::Parentheses(inner) => inner.evaluate(),
+            InternedExpression::UnaryMinus(inner) => {
+                R::neg(&inner.evaluate()?).map_err(|err| EvaluateExpressionError::from_operation_error("negation", SourcePosition::default(), err))
+            }
+            InternedExpression::Conditional { condition, then, otherwise } => {
+                if ExpressionComponent::<R>::holds(&condition.evaluate()?) { then.evaluate() } else { otherwise.evaluate() }
+            }
+            InternedExpression::BinaryOperation { operator, left, right } => {
+                operator.apply::<R>(&left.evaluate()?, &right.evaluate()?)
+                    .map_err(|err| EvaluateExpressionError::from_operation_error(operator.name(), SourcePosition::default(), err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::interned::InternedExpression;
+    use crate::expression::ring::intring::{IntRing, IntRingElement};
+    use crate::expression::ExpressionComponent;
+    use crate::expression::parser::parse_int_ring_expression;
+    use std::rc::Rc;
+
+    #[test]
+    fn intern_shares_equal_elements() {
+        let mut expression = ExpressionComponent::<IntRing>::new_int_element(7);
+        for _ in 1..100 {
+            expression = ExpressionComponent::new_addition(expression, ExpressionComponent::new_int_element(7));
+        }
+        let expression = ExpressionComponent::new_multiplication(expression, ExpressionComponent::new_int_element(2));
+
+        let interned = expression.intern();
+
+        let mut node = &interned;
+        while let InternedExpression::BinaryOperation { left, .. } = node {
+            node = left;
+        }
+        if let InternedExpression::RingElement(element) = node {
+            assert_eq!(100, Rc::strong_count(element));
+        } else {
+            panic!("should be ring element");
+        }
+        assert_eq!(Ok(IntRingElement::new(1400)), interned.evaluate());
+        assert_eq!(expression.evaluate(), interned.evaluate());
+    }
+
+    #[test]
+    fn evaluate_conditional() {
+        let expression = parse_int_ring_expression("1 > 0 ? 5 : 1 / 0").expect("ok");
+
+        assert_eq!(Ok(IntRingElement::new(5)), expression.intern().evaluate());
+        assert!(parse_int_ring_expression("1 < 0 ? 5 : 1 / 0").expect("ok").intern().evaluate().is_err());
+    }
+}
@@ -1,452 +1,1777 @@
-use crate::token::{TokenIterator, TokenError, TokenResult, TokenWithPos};
-use crate::token::intring::{IntRingTokenParser, IntRingToken};
-use crate::expression::ExpressionComponent;
-use crate::expression::ring::intring::{IntRing};
-use core::fmt;
-use std::fmt::Formatter;
-use std::{error, result};
-use crate::expression::parser::ParseExpressionErrorKind::{TokenParseError, Unspecified, NoExpression};
-use std::mem::swap;
-use std::iter::Peekable;
-use std::fs::set_permissions;
-
-#[derive(Debug, PartialEq, Eq, Clone, Hash)]
-pub struct ParseExpressionError {
-    pub message: String,
-    pub position: usize,
-    pub kind: ParseExpressionErrorKind,
-}
-
-#[derive(Debug, PartialEq, Eq, Clone, Hash)]
-pub enum ParseExpressionErrorKind {
-    Unspecified,
-    TokenParseError,
-    NoExpression,
-}
-
-impl fmt::Display for ParseExpressionError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Error parsing expression at position {}: {}", self.position, self.message)
-    }
-}
-
-impl error::Error for ParseExpressionError {
-}
-
-impl From<TokenError> for ParseExpressionError {
-    fn from(err: TokenError) -> Self {
-        ParseExpressionError {
-            message: err.message,
-            position: err.position,
-            kind: TokenParseError,
-        }
-    }
-}
-
-pub type ParseExpressionResult<T> = result::Result<T, ParseExpressionError>;
-
-fn create_err<T>(format_args: fmt::Arguments, position: usize, kind: ParseExpressionErrorKind) -> ParseExpressionResult<T> {
-    Err(ParseExpressionError{message: format_args.to_string(), position, kind})
-}
-
-pub fn parse_int_ring_expression(
-    str: impl AsRef<str>)
-    -> ParseExpressionResult<ExpressionComponent<IntRing>>
-{
-    let tokens_result: TokenResult<Vec<TokenWithPos<IntRingToken>>> =
-        TokenIterator::new(&str, IntRingTokenParser::new()).collect();
-    let tokens = tokens_result?;
-
-    parse_int_ring_expression_from_tokens(tokens)
-}
-
-/// Parse expression from `tokens`
-pub fn parse_int_ring_expression_from_tokens(
-    tokens: Vec<TokenWithPos<IntRingToken>>)
-    -> ParseExpressionResult<ExpressionComponent<IntRing>>
-{
-    // TODO try implement polish notation intermediate result, simpler?
-
-    let mut parsed_expression: Option<ExpressionComponent<IntRing>> = None;
-    let mut tokens_iter = tokens.iter().rev().peekable();
-    let result = parse_int_ring_expression_from_tokens_rec
-        (&mut tokens_iter, &mut parsed_expression, false);
-
-    if let Ok(_) = result {
-        debug_assert!(tokens_iter.next().is_none());
-    }
-
-    match result {
-        Ok(Some(expr)) => Ok(expr),
-        Err(err) => Err(err),
-        Ok(None) => create_err(format_args!("No expression"), 0, NoExpression)
-    }
-}
-
-/// Parse and consume `tokens` in order to parse an expression. The token iterator may start
-/// inside an expression where a potential right hand side for an operator is already parsed
-/// into `parsed_expression`. The iterator may also start inside a parenthesis in which
-/// case `has_open_parenthesis` is `true`.
-///
-fn parse_int_ring_expression_from_tokens_rec<'a, I>(
-    tokens: &mut Peekable<I>,
-    parsed_expression: &mut Option<ExpressionComponent<IntRing>>,
-    has_open_parenthesis: bool)
-    -> ParseExpressionResult<Option<ExpressionComponent<IntRing>>>
-    where I: Iterator<Item=&'a TokenWithPos<IntRingToken>>
-{
-    let token_option = tokens.peek();
-
-    if token_option.is_none() {
-        if let Some(expr) = parsed_expression.take() {
-            return Ok(Some(expr));
-        } else {
-            return Ok(None);
-        }
-    }
-
-    let position = token_option.unwrap().position;
-    let token = &token_option.unwrap().token;
-
-    match &token {
-        IntRingToken::DecimalInteger(d) => {
-            tokens.next();
-            if let Some(_) = parsed_expression.replace(ExpressionComponent::new_int_element(*d)) {
-                return create_err(format_args!("Ring element cannot be followed by another ring element in expression"), position, Unspecified);
-            }
-            let rest = parse_int_ring_expression_from_tokens_rec(tokens, parsed_expression, has_open_parenthesis)?;
-            if let Some(_) = rest {
-                debug_assert!(parsed_expression.is_none());
-                Ok(rest)
-            } else {
-                Ok(Some(parsed_expression.take().unwrap()))
-            }
-        },
-        operator @ (IntRingToken::PlusSign | IntRingToken::MinusSign | IntRingToken::MultiplicationSign | IntRingToken::DivisionSign) => {
-            tokens.next();
-            let construct_expression = match operator {
-                IntRingToken::PlusSign => ExpressionComponent::new_addition,
-                IntRingToken::MinusSign => ExpressionComponent::new_subtraction,
-                IntRingToken::MultiplicationSign => ExpressionComponent::new_multiplication,
-                IntRingToken::DivisionSign => ExpressionComponent::new_division,
-                _ => panic!("Unhandled token: {}", operator)
-            };
-
-            if let Some(rhs_expression) = parsed_expression.take() {
-                let lhs_expression_option =
-                    parse_int_ring_expression_from_tokens_rec(tokens, parsed_expression, has_open_parenthesis)?;
-
-                if lhs_expression_option.is_none() {
-                    return create_err(format_args!("Missing left hand side expression for operator"), position, Unspecified);
-                }
-
-                let mut lhs_expression = lhs_expression_option.unwrap();
-
-                let mut operator_expression = construct_expression(
-                    ExpressionComponent::new_int_element(0), // dummy value
-                    rhs_expression);
-
-                if lhs_expression.is_operator()
-                    && lhs_expression.precedence() < operator_expression.precedence() {
-                    swap(operator_expression.left_mut(), lhs_expression.right_mut());
-                    swap(lhs_expression.right_mut(), &mut operator_expression);
-                    Ok(Some(lhs_expression))
-                } else {
-                    swap(operator_expression.left_mut(), &mut lhs_expression);
-                    Ok(Some(operator_expression))
-                }
-            } else {
-                return create_err(format_args!("Missing right hand side expression for operator"), position, Unspecified)
-            }
-        },
-        IntRingToken::RightParenthesis => {
-            tokens.next();
-            if let Some(inner) = parse_int_ring_expression_from_tokens_rec(tokens, parsed_expression, true)? {
-                if let Some(IntRingToken::LeftParenthesis) = tokens.next().map(|twp| &twp.token) {
-                    parsed_expression.replace(ExpressionComponent::new_parenteses(inner));
-                    parse_int_ring_expression_from_tokens_rec(tokens, parsed_expression, has_open_parenthesis)
-                } else {
-                    create_err(format_args!("Missing left parenthesis for right parenthesis"), position, Unspecified)
-                }
-            } else {
-                create_err(format_args!("No expression"), position, NoExpression)
-            }
-        }
-        IntRingToken::LeftParenthesis if has_open_parenthesis => Ok(None),
-        IntRingToken::LeftParenthesis if !has_open_parenthesis => create_err(format_args!("Missing right parenthesis for left parenthesis"), position, Unspecified),
-        _ => create_err(format_args!("Unhandled token: {}", token), position, Unspecified)
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::expression::ring::intring::{IntRingElement};
-    use crate::expression::{ExpressionComponent};
-    use crate::expression::parser::{parse_int_ring_expression, ParseExpressionError};
-    use crate::expression::parser::ParseExpressionErrorKind::{NoExpression, TokenParseError, Unspecified};
-
-    #[test]
-    fn simple_value() {
-        let expression = parse_int_ring_expression("34").expect("ok");
-
-        assert_eq!(Ok(IntRingElement::new(34)), expression.evaluate());
-    }
-
-    #[test]
-    fn two_simple_values() {
-        let expression_result = parse_int_ring_expression("1 2");
-
-        assert_eq!(Err(ParseExpressionError{message: "Ring element cannot be followed by another ring element in expression".to_string(), position: 0, kind: Unspecified}), expression_result);
-    }
-
-    #[test]
-    fn empty() {
-        let expression_result = parse_int_ring_expression("  ");
-
-        assert_eq!(Err(ParseExpressionError{message: "No expression".to_string(), position: 0, kind: NoExpression}), expression_result);
-    }
-
-    #[test]
-    fn token_parse_error() {
-        let expression_result = parse_int_ring_expression("5 hest");
-
-        assert_eq!(Err(ParseExpressionError{message: "Invalid token".to_string(), position: 2, kind: TokenParseError}), expression_result);
-        expression_result.unwrap_err().
-    }
-
-    #[test]
-    fn add() {
-        let expression = parse_int_ring_expression("2 + 5").expect("ok");
-
-        assert_eq!(Ok(IntRingElement::new(7)), expression.evaluate());
-    }
-
-    #[test]
-    fn sub() {
-        let expression = parse_int_ring_expression("2 - 5").expect("ok");
-
-        assert_eq!(Ok(IntRingElement::new(-3)), expression.evaluate());
-    }
-
-    #[test]
-    fn mul() {
-        let expression = parse_int_ring_expression("2 * 5").expect("ok");
-
-        assert_eq!(Ok(IntRingElement::new(10)), expression.evaluate());
-    }
-
-    #[test]
-    fn div() {
-        let expression = parse_int_ring_expression("6 / 2").expect("ok");
-
-        assert_eq!(Ok(IntRingElement::new(3)), expression.evaluate());
-    }
-
-    #[test]
-    fn add_missing_rhs() {
-        let expression_result = parse_int_ring_expression("2 + ");
-
-        assert_eq!(Err(ParseExpressionError{message: "Missing right hand side expression for operator".to_string(), position: 2, kind: Unspecified}), expression_result);
-    }
-
-    #[test]
-    fn add_missing_lhs() {
-        let expression_result = parse_int_ring_expression(" + 5");
-
-        assert_eq!(Err(ParseExpressionError{message: "Missing left hand side expression for operator".to_string(), position: 1, kind: Unspecified}), expression_result);
-    }
-
-    #[test]
-    fn add_twice() {
-        let expression = parse_int_ring_expression("2 + 5 + 1").expect("ok");
-
-        assert_eq!(Ok(IntRingElement::new(8)), expression.evaluate());
-    }
-
-    #[test]
-    fn add_left_associative() {
-        let expression = parse_int_ring_expression("2 + 5 + 1").expect("ok");
-
-        assert!(matches!(expression, ExpressionComponent::Addition{..}));
-        if let ExpressionComponent::Addition{right, ..} = expression {
-            assert_eq!(ExpressionComponent::new_int_element(1), *right);
-        } else {
-            assert!(false, "should be addition");
-        }
-    }
-
-    #[test]
-    fn precedence_structure() {
-        let expression = parse_int_ring_expression("2 + 5 * 1").expect("ok");
-
-        assert_eq!(ExpressionComponent::new_addition(
-            ExpressionComponent::new_int_element(2),
-            ExpressionComponent::new_multiplication(
-                ExpressionComponent::new_int_element(5),
-                ExpressionComponent::new_int_element(1))
-        ), expression);
-
-        assert_eq!(Ok(IntRingElement::new(7)), expression.evaluate())
-    }
-
-    #[test]
-    fn precedence_structure2() {
-        let expression = parse_int_ring_expression("2 + 5 * 1 * 3").expect("ok");
-
-        assert_eq!(ExpressionComponent::new_addition(
-            ExpressionComponent::new_int_element(2),
-            ExpressionComponent::new_multiplication(
-                ExpressionComponent::new_multiplication(
-                    ExpressionComponent::new_int_element(5),
-                    ExpressionComponent::new_int_element(1)),
-                ExpressionComponent::new_int_element(3))
-        ), expression);
-
-        assert_eq!(Ok(IntRingElement::new(2 + 5 * 1 * 3)), expression.evaluate())
-    }
-
-    #[test]
-    fn precedence_structure_parentheses() {
-        let expression = parse_int_ring_expression("(2 + 5) * 1 * 3").expect("ok");
-
-        assert_eq!(ExpressionComponent::new_multiplication(
-            ExpressionComponent::new_multiplication(
-                ExpressionComponent::new_parenteses(ExpressionComponent::new_addition(
-                    ExpressionComponent::new_int_element(2),
-                    ExpressionComponent::new_int_element(5))),
-                ExpressionComponent::new_int_element(1)),
-            ExpressionComponent::new_int_element(3),
-        ), expression);
-
-        assert_eq!(Ok(IntRingElement::new((2 + 5) * 1 * 3)), expression.evaluate())
-    }
-
-    #[test]
-    fn precedence_structure_parentheses2() {
-        let expression = parse_int_ring_expression("(2 + (5)) * 1 * (3 + 4)").expect("ok");
-
-        assert_eq!(ExpressionComponent::new_multiplication(
-            ExpressionComponent::new_multiplication(
-                ExpressionComponent::new_parenteses(ExpressionComponent::new_addition(
-                    ExpressionComponent::new_int_element(2),
-                    ExpressionComponent::new_parenteses(ExpressionComponent::new_int_element(5)))),
-                ExpressionComponent::new_int_element(1)),
-            ExpressionComponent::new_parenteses(
-                ExpressionComponent::new_addition(
-                    ExpressionComponent::new_int_element(3),
-                    ExpressionComponent::new_int_element(4),
-                ))
-
-        ), expression);
-
-        assert_eq!(Ok(IntRingElement::new((2 + (5)) * 1 * (3 + 4))), expression.evaluate())
-    }
-
-    #[test]
-    fn add_lower_precedence_than_mul() {
-        let expression = parse_int_ring_expression("2 * 5 + 1").expect("ok");
-
-        assert!(matches!(expression, ExpressionComponent::Addition{..}));
-        if let ExpressionComponent::Addition{right, ..} = expression {
-            assert_eq!(ExpressionComponent::new_int_element(1), *right);
-        } else {
-            assert!(false, "should be addition");
-        }
-    }
-
-    #[test]
-    fn mul_higher_precedence_than_add() {
-        let expression = parse_int_ring_expression("2 + 5 * 1").expect("ok");
-
-        assert!(matches!(expression, ExpressionComponent::Addition{..}));
-        if let ExpressionComponent::Addition{left, ..} = expression {
-            assert_eq!(ExpressionComponent::new_int_element(2), *left);
-        } else {
-            assert!(false, "should be addition");
-        }
-    }
-
-    #[test]
-    fn div_higher_precedence_than_add() {
-        let expression = parse_int_ring_expression("2 + 5 / 1").expect("ok");
-
-        assert!(matches!(expression, ExpressionComponent::Addition{..}));
-        if let ExpressionComponent::Addition{left, ..} = expression {
-            assert_eq!(ExpressionComponent::new_int_element(2), *left);
-        } else {
-            assert!(false, "should be addition");
-        }
-    }
-
-    #[test]
-    fn mul_higher_precedence_than_sub() {
-        let expression = parse_int_ring_expression("2 - 5 * 1").expect("ok");
-
-        assert!(matches!(expression, ExpressionComponent::Subtraction{..}));
-        if let ExpressionComponent::Subtraction{left, ..} = expression {
-            assert_eq!(ExpressionComponent::new_int_element(2), *left);
-        } else {
-            assert!(false, "should be subtraction");
-        }
-    }
-
-    #[test]
-    fn div_higher_precedence_than_sub() {
-        let expression = parse_int_ring_expression("2 - 5 / 1").expect("ok");
-
-        assert!(matches!(expression, ExpressionComponent::Subtraction{..}));
-        if let ExpressionComponent::Subtraction{left, ..} = expression {
-            assert_eq!(ExpressionComponent::new_int_element(2), *left);
-        } else {
-            assert!(false, "should be subtraction");
-        }
-    }
-
-    #[test]
-    fn missing_left_parenthesis() {
-        let expression_result = parse_int_ring_expression("3 + 5)");
-
-        assert_eq!(Err(ParseExpressionError{message: "Missing left parenthesis for right parenthesis".to_string(), position: 5, kind: Unspecified}), expression_result);
-    }
-
-    #[test]
-    fn missing_left_parenthesis2() {
-        let expression_result = parse_int_ring_expression("(3 + 5))");
-
-        assert_eq!(Err(ParseExpressionError{message: "Missing left parenthesis for right parenthesis".to_string(), position: 7, kind: Unspecified}), expression_result);
-    }
-
-    #[test]
-    fn missing_right_parenthesis() {
-        let expression_result = parse_int_ring_expression("3 + (3 + 5");
-
-        assert_eq!(Err(ParseExpressionError{message: "Missing right parenthesis for left parenthesis".to_string(), position: 4, kind: Unspecified}), expression_result);
-    }
-
-    #[test]
-    fn missing_right_parenthesis2() {
-        let expression_result = parse_int_ring_expression("(3 + (3 + 5)");
-
-        assert_eq!(Err(ParseExpressionError{message: "Missing right parenthesis for left parenthesis".to_string(), position: 0, kind: Unspecified}), expression_result);
-    }
-
-    #[test]
-    fn emtpy_expression_in_parenthesis() {
-        let expression_result = parse_int_ring_expression("8 + () * 8");
-
-        assert_eq!(Err(ParseExpressionError{message: "No expression".to_string(), position: 5, kind: NoExpression}), expression_result);
-    }
-
-    #[test]
-    fn unary_minus() {
-        let expression = parse_int_ring_expression("2 * (-5)").expect("ok");
-
-        assert_eq!(ExpressionComponent::new_multiplication(
-            ExpressionComponent::new_int_element(2),
-            ExpressionComponent::new_parenteses(
-                ExpressionComponent::new_int_element(-5))
-        ), expression);
-
-        assert_eq!(Ok(IntRingElement::new(-10)), expression.evaluate())
-    }
+use crate::token::{TokenIterator, TokenError, TokenResult, TokenWithPos};
+use crate::token::intring::{IntRingTokenParser, IntRingToken};
+use crate::expression::{ExpressionComponent, SourcePosition};
+use crate::expression::ring::intring::{IntRing};
+use crate::expression::ring::modring::ModRing;
+use crate::expression::ring::{Ring, RingResult, RingFunction};
+use crate::expression::ring::rational::RationalRing;
+use crate::expression::ring::bigint::BigIntRing;
+use core::fmt;
+use std::fmt::Formatter;
+use std::{error, io, result};
+use crate::expression::parser::ParseExpressionErrorKind::{TokenParseError, Unspecified, NoExpression, UnexpectedEndOfInput, InvalidLiteral, DepthExceeded};
+use std::mem::swap;
+use std::ops::Deref;
+use std::iter::Peekable;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ParseExpressionError {
+    pub message: String,
+    pub position: usize,
+    pub kind: ParseExpressionErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum ParseExpressionErrorKind {
+    Unspecified,
+    TokenParseError,
+    NoExpression,
+    /// The input ended while an expression was still expected. The position is right after the
+    /// last token.
+    UnexpectedEndOfInput,
+    /// A literal is not a valid element of the ring the expression is parsed in, e.g. `6.5` in
+    /// the integers. The message is given by the ring.
+    InvalidLiteral,
+    /// The expression is nested deeper than allowed by [ParseLimits].
+    DepthExceeded,
+}
+
+impl fmt::Display for ParseExpressionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Error parsing expression at position {}: {}", self.position, self.message)
+    }
+}
+
+impl error::Error for ParseExpressionError {
+}
+
+impl From<TokenError> for ParseExpressionError {
+    fn from(err: TokenError) -> Self {
+        ParseExpressionError {
+            message: err.message,
+            position: err.position,
+            kind: TokenParseError,
+        }
+    }
+}
+
+impl From<ParseExpressionError> for io::Error {
+    fn from(err: ParseExpressionError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+pub type ParseExpressionResult<T> = result::Result<T, ParseExpressionError>;
+
+fn create_err<T>(format_args: fmt::Arguments, position: usize, kind: ParseExpressionErrorKind) -> ParseExpressionResult<T> {
+    Err(ParseExpressionError{message: format_args.to_string(), position, kind})
+}
+
+/// Ring whose elements can be written as the integer literals of [IntRingToken], such that
+/// expressions in the ring are parsed with the shared expression grammar, see [parse_expression].
+pub trait ElementTokenRing: Ring {
+    /// Ring element for the literal `value`. A literal folded with a unary minus is negative,
+    /// see [NegativeLiterals].
+    fn element_from_token(value: i64) -> RingResult<Self::RingElementType>;
+}
+
+/// When a unary minus directly before a literal is folded into a negative literal instead of
+/// being parsed as [UnaryMinus](ExpressionComponent::UnaryMinus). A unary minus before anything
+/// else, e.g. `-(5)` or `-(2 + 3)`, is always parsed as `UnaryMinus`, and so is a unary minus
+/// before the base of a power, since it negates the power, see [Power](ExpressionComponent::Power).
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
+pub enum NegativeLiterals {
+    /// Fold only if the minus is immediately followed by the literal: `-5` is the literal `-5`
+    /// while `- 5` is the negation of `5`.
+    #[default]
+    Adjacent,
+    /// Fold regardless of whitespace, both `-5` and `- 5` are the literal `-5`.
+    Always,
+    /// Never fold, `-5` is the negation of `5`.
+    Never,
+}
+
+/// Options for parsing expressions, see [parse_expression_with_options].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
+pub struct ParseOptions {
+    pub negative_literals: NegativeLiterals,
+    /// Parse a postfix `%` as division of the preceding operand by 100, see
+    /// [IntRingTokenParser::with_calculator_mode]. E.g. `50%` is parsed as `(50 / 100)`, which is
+    /// `1/2` in the rationals. Modulo is written `mod` in both modes, so `%` never means modulo.
+    pub calculator_mode: bool,
+}
+
+/// Limits on parsed expressions, such that expressions from untrusted input can be evaluated and
+/// printed without exhausting the stack, see [parse_int_ring_expression_with_limits].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct ParseLimits {
+    /// Maximum depth of nested operations and parentheses. A single ring element has depth 1,
+    /// as for [ExpressionComponent::try_clone_depth_limited].
+    pub max_depth: usize,
+}
+
+/// Parse expression from `str` as an expression in the ring `R`.
+pub fn parse_expression<R: ElementTokenRing>(
+    str: impl AsRef<str>)
+    -> ParseExpressionResult<ExpressionComponent<R>>
+{
+    parse_expression_with_options(str, &ParseOptions::default())
+}
+
+/// Parse expression from `str` as an expression in the ring `R` with the given options.
+pub fn parse_expression_with_options<R: ElementTokenRing>(
+    str: impl AsRef<str>,
+    options: &ParseOptions)
+    -> ParseExpressionResult<ExpressionComponent<R>>
+{
+    let (_, expression) = parse_with_token_parser(str, IntRingTokenParser::new(), options)?;
+    Ok(expression)
+}
+
+pub fn parse_int_ring_expression(
+    str: impl AsRef<str>)
+    -> ParseExpressionResult<ExpressionComponent<IntRing>>
+{
+    parse_expression(str)
+}
+
+/// Parse expression from `str` and fail with [DepthExceeded] if it is nested deeper than
+/// allowed by `limits`. The error position is that of the innermost operator enclosing the too
+/// deeply nested subexpression, or 0 if there is none.
+pub fn parse_int_ring_expression_with_limits(
+    str: impl AsRef<str>,
+    limits: &ParseLimits)
+    -> ParseExpressionResult<ExpressionComponent<IntRing>>
+{
+    let expression = parse_expression(str)?;
+    check_depth(&expression, limits.max_depth)?;
+    Ok(expression)
+}
+
+/// Parse expression from `str` as an expression in the integers modulo `N`. Ring elements are
+/// parsed as integers and reduced modulo `N`.
+pub fn parse_mod_ring_expression<const N: u64>(
+    str: impl AsRef<str>)
+    -> ParseExpressionResult<ExpressionComponent<ModRing<N>>>
+{
+    parse_expression(str)
+}
+
+/// Parse expression from `str` as an expression in the rational numbers. Ring elements are
+/// parsed as integers, fractions are written as divisions, e.g. `1 / 3`.
+pub fn parse_rational_ring_expression(
+    str: impl AsRef<str>)
+    -> ParseExpressionResult<ExpressionComponent<RationalRing>>
+{
+    parse_expression(str)
+}
+
+impl FromStr for ExpressionComponent<IntRing> {
+    type Err = ParseExpressionError;
+
+    fn from_str(str: &str) -> ParseExpressionResult<Self> {
+        parse_int_ring_expression(str)
+    }
+}
+
+/// Parse expression from `str` as an expression in the integers of arbitrary size. Integer
+/// literals too big for `i64` are read as [BigDecimalInteger](IntRingToken::BigDecimalInteger)
+/// tokens, see [IntRingTokenParser::with_big_integers].
+pub fn parse_big_int_ring_expression(
+    str: impl AsRef<str>)
+    -> ParseExpressionResult<ExpressionComponent<BigIntRing>>
+{
+    let (_, expression) = parse_with_token_parser(str, IntRingTokenParser::new().with_big_integers(true), &ParseOptions::default())?;
+    Ok(expression)
+}
+
+/// Parse expression from `str` in calculator mode, where a postfix `%` divides the preceding
+/// operand by 100, see [ParseOptions::calculator_mode]. E.g. `200%` is parsed as `(200 / 100)`.
+/// Modulo is written `mod` in both modes.
+pub fn parse_int_ring_calculator_expression(
+    str: impl AsRef<str>)
+    -> ParseExpressionResult<ExpressionComponent<IntRing>>
+{
+    parse_expression_with_options(str, &ParseOptions { calculator_mode: true, ..ParseOptions::default() })
+}
+
+/// Parse expression from `str` and return both the tokens and the parsed expression. The input
+/// is only tokenized once.
+pub fn parse_int_ring_with_tokens(
+    str: impl AsRef<str>)
+    -> ParseExpressionResult<(Vec<TokenWithPos<IntRingToken>>, ExpressionComponent<IntRing>)>
+{
+    parse_with_token_parser(str, IntRingTokenParser::new(), &ParseOptions::default())
+}
+
+fn parse_with_token_parser<R: ElementTokenRing>(
+    str: impl AsRef<str>,
+    token_parser: IntRingTokenParser,
+    options: &ParseOptions)
+    -> ParseExpressionResult<(Vec<TokenWithPos<IntRingToken>>, ExpressionComponent<R>)>
+{
+    let tokens_result: TokenResult<Vec<TokenWithPos<IntRingToken>>> =
+        TokenIterator::new(&str, token_parser.with_calculator_mode(options.calculator_mode)).collect();
+    let tokens = tokens_result?;
+
+    let expression = parse_expression_from_tokens_with_options(&tokens, options)?;
+    Ok((tokens, expression))
+}
+
+/// Parse expression from `tokens`
+pub fn parse_int_ring_expression_from_tokens(
+    tokens: Vec<TokenWithPos<IntRingToken>>)
+    -> ParseExpressionResult<ExpressionComponent<IntRing>>
+{
+    parse_expression_from_tokens(&tokens)
+}
+
+/// Parse expression in the ring `R` from `tokens`
+pub fn parse_expression_from_tokens<R: ElementTokenRing>(
+    tokens: &[TokenWithPos<IntRingToken>])
+    -> ParseExpressionResult<ExpressionComponent<R>>
+{
+    parse_expression_from_tokens_with_options(tokens, &ParseOptions::default())
+}
+
+fn parse_expression_from_tokens_with_options<R: ElementTokenRing>(
+    tokens: &[TokenWithPos<IntRingToken>],
+    options: &ParseOptions)
+    -> ParseExpressionResult<ExpressionComponent<R>>
+{
+    if let Some(last) = tokens.last() {
+        if !matches!(last.token, IntRingToken::DecimalInteger(_) | IntRingToken::BigDecimalInteger(_) | IntRingToken::DecimalFraction(_) | IntRingToken::Identifier(_) | IntRingToken::RightParenthesis | IntRingToken::Percent) {
+            return create_err(format_args!("Unexpected end of input"), last.end, UnexpectedEndOfInput);
+        }
+    }
+
+    let mut tokens_iter = tokens.iter().rev().peekable();
+    let result = parse_reversed_tokens(&mut tokens_iter, options);
+
+    if result.is_ok() {
+        debug_assert!(tokens_iter.next().is_none());
+    }
+
+    match result {
+        Ok(Some(mut expr)) => {
+            set_spans(&mut expr, tokens);
+            Ok(expr)
+        }
+        Err(err) => Err(err),
+        Ok(None) => create_err(format_args!("No expression"), 0, NoExpression)
+    }
+}
+
+/// Operation waiting for an operand while parsing, see [parse_reversed_tokens].
+enum PendingOperation<R: Ring> {
+    /// Binary operation of the operator at `position` whose right hand side is parsed, waiting
+    /// for the left hand side. The left hand side of `expression` is a dummy value.
+    Operator { expression: ExpressionComponent<R>, position: usize },
+    /// Right parenthesis at `position`, waiting for the expression inside the parentheses. If the
+    /// parentheses enclose the arguments of a function call, the arguments after the first are
+    /// parsed into `arguments`, rightmost first.
+    Parentheses { position: usize, arguments: Vec<ExpressionComponent<R>> },
+    /// Percent sign at `position`, waiting for its operand.
+    Percent { position: usize },
+    /// Colon of a conditional at `position` with the parsed expression `otherwise` to its right,
+    /// waiting for the expression between the question mark and the colon.
+    Conditional { position: usize, otherwise: ExpressionComponent<R> },
+}
+
+/// Parse and consume `tokens`, which are in reverse order, such that the expression is parsed
+/// from right to left. Operands, possibly negated, are parsed until a binary operator, a left
+/// parenthesis or the end of input. At a binary operator, the expression to the left of the
+/// operator is parsed as its left hand side. At a right parenthesis, the expression inside the
+/// parentheses is parsed. At the colon of a conditional, the expression between it and the
+/// question mark is parsed and the conditional then waits for its condition like a binary
+/// operator waits for its left hand side. The operations waiting for these expressions are kept on a stack on
+/// the heap instead of the call stack, such that the nesting depth of the expression is not
+/// limited by the size of the call stack.
+fn parse_reversed_tokens<'a, R, I>(
+    tokens: &mut Peekable<I>,
+    options: &ParseOptions)
+    -> ParseExpressionResult<Option<ExpressionComponent<R>>>
+    where R: ElementTokenRing, I: Iterator<Item=&'a TokenWithPos<IntRingToken>> + Clone
+{
+    let mut pending: Vec<PendingOperation<R>> = Vec::new();
+    let mut open_parentheses = 0;
+    // the operands parsed so far in the current expression
+    let mut parsed_expression: Option<ExpressionComponent<R>> = None;
+    // whether no tokens are consumed in the current expression yet
+    let mut at_expression_start = true;
+
+    loop {
+        let mut expression = match tokens.peek() {
+            None => parsed_expression.take(),
+            Some(&TokenWithPos { token: IntRingToken::LeftParenthesis, position, .. }) => {
+                if open_parentheses == 0 {
+                    return create_err(format_args!("Missing right parenthesis for left parenthesis"), *position, Unspecified);
+                }
+                if at_expression_start { None } else { parsed_expression.take() }
+            }
+            Some(&TokenWithPos { token: IntRingToken::Comma, position, .. }) => {
+                if open_parentheses == 0 {
+                    return create_err(format_args!("Comma outside function call"), *position, Unspecified);
+                }
+                if at_expression_start { None } else { parsed_expression.take() }
+            }
+            Some(&token_with_pos) => {
+                let position = token_with_pos.position;
+                let token = &token_with_pos.token;
+                tokens.next();
+                match token {
+                    literal @ (IntRingToken::DecimalInteger(_) | IntRingToken::BigDecimalInteger(_) | IntRingToken::DecimalFraction(_)) => {
+                        // a unary minus before the base of a power negates the power, so it is not folded
+                        let negated = !is_power_base(&pending, at_expression_start)
+                            && is_negated_literal(tokens, position, options.negative_literals);
+                        if negated {
+                            tokens.next();
+                        }
+                        if parsed_expression.replace(parse_literal(literal, negated, position)?).is_some() {
+                            return create_err(format_args!("Ring element cannot be followed by another ring element in expression"), position, Unspecified);
+                        }
+                    }
+                    IntRingToken::Identifier(name) => {
+                        if parsed_expression.replace(ExpressionComponent::new_variable(name.clone())).is_some() {
+                            return create_err(format_args!("Ring element cannot be followed by another ring element in expression"), position, Unspecified);
+                        }
+                    }
+                    IntRingToken::MinusSign if is_unary_minus(tokens.peek().map(|twp| &twp.token)) => {
+                        let Some(mut operand) = parsed_expression.take() else {
+                            return create_err(format_args!("Missing expression for unary minus"), position, Unspecified);
+                        };
+                        // a unary minus binds looser than exponentiation and negates the powers to its right
+                        while is_power_base(&pending, true) {
+                            let Some(PendingOperation::Operator { expression, .. }) = pending.pop() else {
+                                panic!("Power waiting for its base");
+                            };
+                            operand = combine_with_lhs(expression, operand);
+                        }
+                        parsed_expression = Some(ExpressionComponent::new_unary_minus(operand).with_position(SourcePosition::at(position)));
+                    }
+                    operator @ (IntRingToken::PlusSign | IntRingToken::MinusSign | IntRingToken::MultiplicationSign | IntRingToken::DivisionSign | IntRingToken::Modulo | IntRingToken::CaretSign
+                    | IntRingToken::LessThanSign | IntRingToken::GreaterThanSign | IntRingToken::EqualsSign) => {
+                        let construct_expression = match operator {
+                            IntRingToken::PlusSign => ExpressionComponent::new_addition,
+                            IntRingToken::MinusSign => ExpressionComponent::new_subtraction,
+                            IntRingToken::MultiplicationSign => ExpressionComponent::new_multiplication,
+                            IntRingToken::DivisionSign => ExpressionComponent::new_division,
+                            IntRingToken::Modulo => ExpressionComponent::new_modulo,
+                            IntRingToken::CaretSign => ExpressionComponent::new_power,
+                            IntRingToken::LessThanSign => ExpressionComponent::new_less_than,
+                            IntRingToken::GreaterThanSign => ExpressionComponent::new_greater_than,
+                            IntRingToken::EqualsSign => ExpressionComponent::new_equal,
+                            _ => panic!("Unhandled token: {}", operator)
+                        };
+
+                        let Some(rhs_expression) = parsed_expression.take() else {
+                            return create_err(format_args!("Missing right hand side expression for operator"), position, Unspecified);
+                        };
+                        let expression = construct_expression(
+                            ExpressionComponent::new_ring_element(R::zero()), // dummy value
+                            rhs_expression)
+                            .with_position(SourcePosition::at(position));
+                        pending.push(PendingOperation::Operator { expression, position });
+                        at_expression_start = true;
+                        continue;
+                    }
+                    IntRingToken::Colon => {
+                        let Some(otherwise) = parsed_expression.take() else {
+                            return create_err(format_args!("Missing expression after colon"), position, Unspecified);
+                        };
+                        let otherwise = complete_operators(otherwise, &mut pending);
+                        pending.push(PendingOperation::Conditional { position, otherwise });
+                        at_expression_start = true;
+                        continue;
+                    }
+                    IntRingToken::QuestionMark => {
+                        let Some(then) = parsed_expression.take() else {
+                            return create_err(format_args!("Missing expression after question mark"), position, Unspecified);
+                        };
+                        let then = complete_operators(then, &mut pending);
+                        let Some(PendingOperation::Conditional { otherwise, .. }) = pending.pop() else {
+                            return create_err(format_args!("Missing colon for question mark"), position, Unspecified);
+                        };
+                        let expression = ExpressionComponent::new_conditional(
+                            ExpressionComponent::new_ring_element(R::zero()), // dummy value
+                            then,
+                            otherwise)
+                            .with_position(SourcePosition::at(position));
+                        pending.push(PendingOperation::Operator { expression, position });
+                        at_expression_start = true;
+                        continue;
+                    }
+                    IntRingToken::RightParenthesis => {
+                        pending.push(PendingOperation::Parentheses { position, arguments: Vec::new() });
+                        open_parentheses += 1;
+                        at_expression_start = true;
+                        continue;
+                    }
+                    IntRingToken::Percent => {
+                        if parsed_expression.is_some() {
+                            return create_err(format_args!("Ring element cannot be followed by another ring element in expression"), position, Unspecified);
+                        }
+                        match parse_percent(tokens, position, &mut pending)? {
+                            Some(operand) => parsed_expression = Some(complete_percents(operand, &mut pending)?),
+                            None => {
+                                open_parentheses += 1;
+                                at_expression_start = true;
+                                continue;
+                            }
+                        }
+                    }
+                    _ => return create_err(format_args!("Unhandled token: {}", token), position, Unspecified),
+                }
+                at_expression_start = false;
+                continue;
+            }
+        };
+
+        // the current expression is parsed, pass it to the operations waiting for it
+        loop {
+            match pending.pop() {
+                None => return Ok(expression),
+                Some(PendingOperation::Operator { expression: operator_expression, position }) => {
+                    let Some(lhs_expression) = expression else {
+                        return create_err(format_args!("Missing left hand side expression for operator"), position, Unspecified);
+                    };
+                    // the operation is the expression the left hand side was parsed in
+                    expression = Some(combine_with_lhs(operator_expression, lhs_expression));
+                }
+                Some(PendingOperation::Parentheses { position, mut arguments }) => {
+                    let Some(inner) = expression else {
+                        return create_err(format_args!("No expression"), position, NoExpression);
+                    };
+                    let left_parenthesis_position = match tokens.next() {
+                        Some(TokenWithPos { token: IntRingToken::Comma, .. }) => {
+                            // parse the argument to the left of the comma
+                            arguments.push(inner);
+                            pending.push(PendingOperation::Parentheses { position, arguments });
+                            at_expression_start = true;
+                            break;
+                        }
+                        Some(TokenWithPos { token: IntRingToken::LeftParenthesis, position, .. }) => *position,
+                        _ => return create_err(format_args!("Missing left parenthesis for right parenthesis"), position, Unspecified),
+                    };
+                    open_parentheses -= 1;
+                    let expression = match tokens.next_if(|twp| matches!(twp.token, IntRingToken::FunctionName(_))) {
+                        Some(TokenWithPos { token: IntRingToken::FunctionName(name), position: name_position, .. }) => {
+                            arguments.push(inner);
+                            arguments.reverse();
+                            parse_function_call(name, arguments, *name_position)?
+                        }
+                        _ if !arguments.is_empty() => {
+                            return create_err(format_args!("Comma outside function call"), left_parenthesis_position, Unspecified);
+                        }
+                        _ => ExpressionComponent::new_parenteses(inner),
+                    };
+                    parsed_expression = Some(complete_percents(expression, &mut pending)?);
+                    at_expression_start = false;
+                    break;
+                }
+                Some(PendingOperation::Percent { .. }) => panic!("Percent sign waiting for expression"),
+                Some(PendingOperation::Conditional { position, .. }) => {
+                    return create_err(format_args!("Missing question mark for colon"), position, Unspecified);
+                }
+            }
+        }
+    }
+}
+
+/// Combine the binary operation `operator_expression`, whose right hand side is parsed, with the
+/// expression to the left of the operator, `lhs_expression`, according to operator precedence.
+fn combine_with_lhs<R: Ring>(
+    mut operator_expression: ExpressionComponent<R>,
+    mut lhs_expression: ExpressionComponent<R>)
+    -> ExpressionComponent<R>
+{
+    if operator_expression.binds_tighter_than(&lhs_expression) {
+        // the operator takes the rightmost operand it binds tighter than
+        let mut target = &mut lhs_expression;
+        while operator_expression.binds_tighter_than(target.right_mut()) {
+            target = target.right_mut();
+        }
+        swap(operator_expression.left_mut(), target.right_mut());
+        swap(target.right_mut(), &mut operator_expression);
+        lhs_expression
+    } else {
+        swap(operator_expression.left_mut(), &mut lhs_expression);
+        operator_expression
+    }
+}
+
+/// Apply the binary operations waiting on top of `pending` to `expression`, which is the
+/// expression to the left of their operators.
+fn complete_operators<R: Ring>(
+    mut expression: ExpressionComponent<R>,
+    pending: &mut Vec<PendingOperation<R>>)
+    -> ExpressionComponent<R>
+{
+    while let Some(PendingOperation::Operator { .. }) = pending.last() {
+        let Some(PendingOperation::Operator { expression: operator_expression, .. }) = pending.pop() else {
+            unreachable!();
+        };
+        expression = combine_with_lhs(operator_expression, expression);
+    }
+    expression
+}
+
+/// Parse the operand of a postfix percent sign at `position`, which is consumed. The operand is
+/// a ring element, a variable, an expression in parentheses or another percentage, such that the
+/// percent sign binds tighter than any binary operator. The percent signs are pushed to `pending`
+/// and applied by [complete_percents]. Returns the operand if it is a ring element or a variable.
+/// If it is an expression in parentheses, the right parenthesis is pushed to `pending` and `None`
+/// is returned.
+fn parse_percent<'a, R, I>(
+    tokens: &mut Peekable<I>,
+    mut position: usize,
+    pending: &mut Vec<PendingOperation<R>>)
+    -> ParseExpressionResult<Option<ExpressionComponent<R>>>
+    where R: ElementTokenRing, I: Iterator<Item=&'a TokenWithPos<IntRingToken>> + Clone
+{
+    loop {
+        pending.push(PendingOperation::Percent { position });
+        match tokens.next() {
+            Some(TokenWithPos { token: literal @ (IntRingToken::DecimalInteger(_) | IntRingToken::BigDecimalInteger(_) | IntRingToken::DecimalFraction(_)), position, .. }) => {
+                return parse_literal(literal, false, *position).map(Some);
+            }
+            Some(TokenWithPos { token: IntRingToken::Identifier(name), .. }) => {
+                return Ok(Some(ExpressionComponent::new_variable(name.clone())));
+            }
+            Some(TokenWithPos { token: IntRingToken::RightParenthesis, position, .. }) => {
+                pending.push(PendingOperation::Parentheses { position: *position, arguments: Vec::new() });
+                return Ok(None);
+            }
+            Some(TokenWithPos { token: IntRingToken::Percent, position: percent_position, .. }) => position = *percent_position,
+            _ => return create_err(format_args!("Missing expression for percent"), position, Unspecified),
+        }
+    }
+}
+
+/// Call of the function `name` of the ring at `position`. Fails if the ring has no such function
+/// or it takes a different number of arguments.
+fn parse_function_call<R: ElementTokenRing>(
+    name: &str,
+    args: Vec<ExpressionComponent<R>>,
+    position: usize)
+    -> ParseExpressionResult<ExpressionComponent<R>>
+{
+    if let Err(err) = RingFunction::<R>::resolve(name, args.len()) {
+        return create_err(format_args!("{}", err.message), position, Unspecified);
+    }
+    Ok(ExpressionComponent::new_function_call(name, args).with_position(SourcePosition::at(position)))
+}
+
+/// Apply the percent signs waiting on top of `pending` to their `operand`, dividing it by 100 in
+/// parentheses for each percent sign.
+fn complete_percents<R: ElementTokenRing>(
+    mut operand: ExpressionComponent<R>,
+    pending: &mut Vec<PendingOperation<R>>)
+    -> ParseExpressionResult<ExpressionComponent<R>>
+{
+    while let Some(&PendingOperation::Percent { position }) = pending.last() {
+        pending.pop();
+        operand = ExpressionComponent::new_parenteses(ExpressionComponent::new_division(operand, parse_element(100, position)?)
+            .with_position(SourcePosition::at(position)));
+    }
+    Ok(operand)
+}
+
+/// Fail if `expression` is nested deeper than `max_depth`, without recursion.
+fn check_depth<R: Ring>(expression: &ExpressionComponent<R>, max_depth: usize) -> ParseExpressionResult<()> {
+    // subexpressions with their depth and the position of the innermost operator enclosing them
+    let mut subexpressions = vec![(expression, 1, 0)];
+    while let Some((expr, depth, position)) = subexpressions.pop() {
+        if depth > max_depth {
+            return create_err(format_args!("Expression nested deeper than {}", max_depth), position, DepthExceeded);
+        }
+        let position = expr.position().position.unwrap_or(position);
+        match expr {
+            ExpressionComponent::RingElement(_) | ExpressionComponent::Variable(_) => (),
+            ExpressionComponent::FunctionCall { args, .. } => {
+                subexpressions.extend(args.iter().map(|arg| (arg, depth + 1, position)));
+            }
+            ExpressionComponent::Parentheses { inner, .. } | ExpressionComponent::UnaryMinus { inner, .. } => {
+                subexpressions.push((inner, depth + 1, position));
+            }
+            ExpressionComponent::Conditional { condition, then, otherwise, .. } => {
+                subexpressions.push((otherwise, depth + 1, position));
+                subexpressions.push((then, depth + 1, position));
+                subexpressions.push((condition, depth + 1, position));
+            }
+            ExpressionComponent::Addition { left, right, .. }
+            | ExpressionComponent::Subtraction { left, right, .. }
+            | ExpressionComponent::Multiplication { left, right, .. }
+            | ExpressionComponent::Division { left, right, .. }
+            | ExpressionComponent::Modulo { left, right, .. }
+            | ExpressionComponent::Power { left, right, .. }
+            | ExpressionComponent::LessThan { left, right, .. }
+            | ExpressionComponent::GreaterThan { left, right, .. }
+            | ExpressionComponent::Equal { left, right, .. } => {
+                subexpressions.push((right, depth + 1, position));
+                subexpressions.push((left, depth + 1, position));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Step of the traversal of an expression in [set_spans].
+enum SpanStep<'a, R: Ring> {
+    /// Consume the tokens of the expression.
+    Expression(&'a ExpressionComponent<R>),
+    /// Consume a parenthesis or comma.
+    Token,
+    /// Consume the operator of a binary operation, and then the tokens of its right hand side
+    /// unless the operator is a percent sign.
+    Operator(&'a ExpressionComponent<R>),
+    /// The tokens of the operation with the span at `index`, from the token at `start`, are
+    /// consumed.
+    End { start: usize, index: usize },
+}
+
+/// Set the source spans of the operations, function calls and parentheses in `expression` parsed from
+/// `tokens`, without recursion. The tokens are matched with the expression from left to right,
+/// and the span of an operation is from its first to its last token.
+fn set_spans<R: Ring>(expression: &mut ExpressionComponent<R>, tokens: &[TokenWithPos<IntRingToken>]) {
+    // spans of the operations in pre-order, found by traversing the expression in source order
+    let mut spans = Vec::new();
+    let mut next_token = 0;
+    let mut steps = vec![SpanStep::Expression(&*expression)];
+    while let Some(step) = steps.pop() {
+        match step {
+            SpanStep::Token => next_token += 1,
+            SpanStep::Operator(right) => {
+                if !matches!(tokens.get(next_token), Some(TokenWithPos { token: IntRingToken::Percent, .. })) {
+                    steps.push(SpanStep::Expression(right));
+                }
+                next_token += 1;
+            }
+            SpanStep::End { start, index } => {
+                spans[index] = tokens.get(start).zip(tokens.get(next_token.wrapping_sub(1)))
+                    .map(|(first, last)| (first.position, last.end));
+            }
+            SpanStep::Expression(expr) => match expr {
+                ExpressionComponent::RingElement(_) => {
+                    // a negative literal may be written as a minus sign and the literal
+                    if matches!(tokens.get(next_token), Some(TokenWithPos { token: IntRingToken::MinusSign, .. })) {
+                        next_token += 1;
+                    }
+                    next_token += 1;
+                }
+                ExpressionComponent::Variable(_) => next_token += 1,
+                ExpressionComponent::Parentheses { inner, .. } => {
+                    // a percentage is parsed as a division by 100 in parentheses without tokens
+                    let is_percentage = matches!(inner.deref(), ExpressionComponent::Division { .. })
+                        && inner.position().position
+                            .and_then(|position| tokens.binary_search_by_key(&position, |twp| twp.position).ok())
+                            .is_some_and(|index| tokens[index].token == IntRingToken::Percent);
+                    if is_percentage {
+                        spans.push(None);
+                    } else {
+                        steps.push(SpanStep::End { start: next_token, index: spans.len() });
+                        spans.push(None);
+                        next_token += 1;
+                        steps.push(SpanStep::Token);
+                    }
+                    steps.push(SpanStep::Expression(inner));
+                }
+                ExpressionComponent::UnaryMinus { inner, .. } => {
+                    steps.push(SpanStep::End { start: next_token, index: spans.len() });
+                    spans.push(None);
+                    next_token += 1;
+                    steps.push(SpanStep::Expression(inner));
+                }
+                ExpressionComponent::Conditional { condition, then, otherwise, .. } => {
+                    steps.push(SpanStep::End { start: next_token, index: spans.len() });
+                    spans.push(None);
+                    // the condition is followed by `?` and the first branch by `:`
+                    steps.push(SpanStep::Expression(otherwise));
+                    steps.push(SpanStep::Token);
+                    steps.push(SpanStep::Expression(then));
+                    steps.push(SpanStep::Token);
+                    steps.push(SpanStep::Expression(condition));
+                }
+                ExpressionComponent::FunctionCall { args, .. } => {
+                    steps.push(SpanStep::End { start: next_token, index: spans.len() });
+                    spans.push(None);
+                    // the function name and the left parenthesis
+                    next_token += 2;
+                    // each argument is followed by a comma or the right parenthesis
+                    for arg in args.iter().rev() {
+                        steps.push(SpanStep::Token);
+                        steps.push(SpanStep::Expression(arg));
+                    }
+                }
+                ExpressionComponent::Addition { left, right, .. }
+                | ExpressionComponent::Subtraction { left, right, .. }
+                | ExpressionComponent::Multiplication { left, right, .. }
+                | ExpressionComponent::Division { left, right, .. }
+                | ExpressionComponent::Modulo { left, right, .. }
+                | ExpressionComponent::Power { left, right, .. }
+                | ExpressionComponent::LessThan { left, right, .. }
+                | ExpressionComponent::GreaterThan { left, right, .. }
+                | ExpressionComponent::Equal { left, right, .. } => {
+                    steps.push(SpanStep::End { start: next_token, index: spans.len() });
+                    spans.push(None);
+                    steps.push(SpanStep::Operator(right));
+                    steps.push(SpanStep::Expression(left));
+                }
+            },
+        }
+    }
+
+    let mut spans = spans.into_iter();
+    let mut subexpressions = vec![expression];
+    while let Some(expr) = subexpressions.pop() {
+        match expr {
+            ExpressionComponent::RingElement(_) | ExpressionComponent::Variable(_) => (),
+            ExpressionComponent::FunctionCall { args, position, .. } => {
+                position.span = spans.next().flatten();
+                subexpressions.extend(args.iter_mut().rev());
+            }
+            ExpressionComponent::Parentheses { inner, position } | ExpressionComponent::UnaryMinus { inner, position } => {
+                position.span = spans.next().flatten();
+                subexpressions.push(inner);
+            }
+            ExpressionComponent::Conditional { condition, then, otherwise, position } => {
+                position.span = spans.next().flatten();
+                subexpressions.push(otherwise);
+                subexpressions.push(then);
+                subexpressions.push(condition);
+            }
+            ExpressionComponent::Addition { left, right, position }
+            | ExpressionComponent::Subtraction { left, right, position }
+            | ExpressionComponent::Multiplication { left, right, position }
+            | ExpressionComponent::Division { left, right, position }
+            | ExpressionComponent::Modulo { left, right, position }
+            | ExpressionComponent::Power { left, right, position }
+            | ExpressionComponent::LessThan { left, right, position }
+            | ExpressionComponent::GreaterThan { left, right, position }
+            | ExpressionComponent::Equal { left, right, position } => {
+                position.span = spans.next().flatten();
+                subexpressions.push(right);
+                subexpressions.push(left);
+            }
+        }
+    }
+}
+
+/// Ring element for the literal token `literal` at `position`, negated if `negated`. Big integers
+/// and decimal fractions are parsed by [Ring::parse_element], such that the ring decides whether
+/// they are valid.
+pub(crate) fn parse_literal<R: ElementTokenRing>(literal: &IntRingToken, negated: bool, position: usize) -> ParseExpressionResult<ExpressionComponent<R>> {
+    match literal {
+        IntRingToken::DecimalInteger(d) => parse_element(if negated { -*d } else { *d }, position),
+        IntRingToken::BigDecimalInteger(str) | IntRingToken::DecimalFraction(str) => {
+            let str = if negated { format!("-{}", str) } else { str.clone() };
+            match R::parse_element(&str) {
+                Ok(element) => Ok(ExpressionComponent::new_ring_element(element)),
+                Err(err) => create_err(format_args!("{}", err.message), position, InvalidLiteral),
+            }
+        }
+        _ => panic!("Not a literal: {}", literal),
+    }
+}
+
+/// Ring element for the literal `value` at `position`.
+fn parse_element<R: ElementTokenRing>(value: i64, position: usize) -> ParseExpressionResult<ExpressionComponent<R>> {
+    match R::element_from_token(value) {
+        Ok(element) => Ok(ExpressionComponent::new_ring_element(element)),
+        Err(err) => create_err(format_args!("{}", err.message), position, InvalidLiteral),
+    }
+}
+
+/// Whether a minus sign preceded by `preceding_token` is a unary minus. This is the case if there
+/// is no left hand side for a subtraction.
+fn is_unary_minus(preceding_token: Option<&IntRingToken>) -> bool {
+    !matches!(preceding_token, Some(IntRingToken::DecimalInteger(_) | IntRingToken::BigDecimalInteger(_) | IntRingToken::DecimalFraction(_) | IntRingToken::Identifier(_) | IntRingToken::RightParenthesis | IntRingToken::Percent))
+}
+
+/// Whether the expression parsed next is the base of a power waiting on top of `pending`, which
+/// is the case at the start of the expression to the left of a `^`.
+fn is_power_base<R: Ring>(pending: &[PendingOperation<R>], at_expression_start: bool) -> bool {
+    at_expression_start
+        && matches!(pending.last(), Some(PendingOperation::Operator { expression: ExpressionComponent::Power { .. }, .. }))
+}
+
+/// Whether the next token is a unary minus that is folded into the literal just consumed at
+/// `literal_position`, such that the literal is parsed as negative instead of as a
+/// [UnaryMinus](ExpressionComponent::UnaryMinus).
+fn is_negated_literal<'a, I>(tokens: &Peekable<I>, literal_position: usize, negative_literals: NegativeLiterals) -> bool
+    where I: Iterator<Item=&'a TokenWithPos<IntRingToken>> + Clone
+{
+    let mut lookahead = tokens.clone();
+    let folds = match lookahead.next() {
+        Some(TokenWithPos { token: IntRingToken::MinusSign, position, .. }) => match negative_literals {
+            NegativeLiterals::Adjacent => position + 1 == literal_position,
+            NegativeLiterals::Always => true,
+            NegativeLiterals::Never => false,
+        },
+        _ => false,
+    };
+    folds && is_unary_minus(lookahead.peek().map(|twp| &twp.token))
+}
+
+#[cfg(test)]
+#[allow(clippy::identity_op)]
+mod tests {
+    use crate::expression::ring::intring::{IntRingElement, IntRing};
+    use crate::expression::{ExpressionComponent, EvaluateExpressionError};
+    use crate::expression::parser::{parse_int_ring_expression, ParseExpressionError, parse_int_ring_with_tokens, parse_int_ring_calculator_expression};
+    use crate::expression::parser::{parse_expression_with_options, ParseOptions, NegativeLiterals};
+    use crate::expression::parser::{parse_int_ring_expression_with_limits, ParseLimits, parse_mod_ring_expression, parse_rational_ring_expression};
+    use crate::token::TokenIterator;
+    use crate::token::intring::IntRingTokenParser;
+    use crate::expression::parser::ParseExpressionErrorKind::{NoExpression, TokenParseError, Unspecified, UnexpectedEndOfInput, InvalidLiteral, DepthExceeded};
+    use std::io;
+    use crate::expression::ring::DivisionRemainder;
+    use crate::expression::ring::RingErrorKind::{Overflow, NotInRing, DivisionByZero, Unspecified as RingUnspecified};
+
+    #[test]
+    fn simple_value() {
+        let expression = parse_int_ring_expression("34").expect("ok");
+
+        assert_eq!(Ok(IntRingElement::new(34)), expression.evaluate());
+    }
+
+    #[test]
+    fn two_simple_values() {
+        let expression_result = parse_int_ring_expression("1 2");
+
+        assert_eq!(Err(ParseExpressionError{message: "Ring element cannot be followed by another ring element in expression".to_string(), position: 0, kind: Unspecified}), expression_result);
+    }
+
+    #[test]
+    fn variables() {
+        let expression = parse_int_ring_calculator_expression("-x * y% - 2").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_subtraction(
+            ExpressionComponent::new_multiplication(
+                ExpressionComponent::new_unary_minus(ExpressionComponent::new_variable("x")),
+                ExpressionComponent::new_parenteses(ExpressionComponent::new_division(
+                    ExpressionComponent::new_variable("y"),
+                    ExpressionComponent::new_int_element(100)))),
+            ExpressionComponent::new_int_element(2)), expression);
+        assert_eq!(Err(ParseExpressionError{message: "Ring element cannot be followed by another ring element in expression".to_string(), position: 0, kind: Unspecified}),
+                   parse_int_ring_expression("x y"));
+    }
+
+    #[test]
+    fn function_calls() {
+        let expression = parse_int_ring_expression("2 * gcd(12, 3 + 15) - abs(-5)").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_subtraction(
+            ExpressionComponent::new_multiplication(
+                ExpressionComponent::new_int_element(2),
+                ExpressionComponent::new_function_call("gcd", vec![
+                    ExpressionComponent::new_int_element(12),
+                    ExpressionComponent::new_addition(
+                        ExpressionComponent::new_int_element(3),
+                        ExpressionComponent::new_int_element(15))])),
+            ExpressionComponent::new_function_call("abs", vec![ExpressionComponent::new_int_element(-5)])), expression);
+        assert_eq!(Ok(IntRingElement::new(7)), expression.evaluate());
+    }
+
+    #[test]
+    fn function_call_errors() {
+        assert_eq!(Err(ParseExpressionError{message: "Function 'gcd' takes 2 arguments, not 1".to_string(), position: 2, kind: Unspecified}),
+                   parse_int_ring_expression("1+gcd(12)"));
+        assert_eq!(Err(ParseExpressionError{message: "Function 'abs' takes 1 argument, not 3".to_string(), position: 0, kind: Unspecified}),
+                   parse_int_ring_expression("abs(1, 2, 3)"));
+        assert_eq!(Err(ParseExpressionError{message: "Unknown function 'foo'".to_string(), position: 0, kind: Unspecified}),
+                   parse_int_ring_expression("foo(1)"));
+        assert_eq!(Err(ParseExpressionError{message: "Comma outside function call".to_string(), position: 0, kind: Unspecified}),
+                   parse_int_ring_expression("(1, 2)"));
+        assert_eq!(Err(ParseExpressionError{message: "Comma outside function call".to_string(), position: 1, kind: Unspecified}),
+                   parse_int_ring_expression("1, 2"));
+        assert_eq!(Err(ParseExpressionError{message: "No expression".to_string(), position: 7, kind: NoExpression}),
+                   parse_int_ring_expression("gcd(1, )"));
+    }
+
+    #[test]
+    fn empty() {
+        let expression_result = parse_int_ring_expression("  ");
+
+        assert_eq!(Err(ParseExpressionError{message: "No expression".to_string(), position: 0, kind: NoExpression}), expression_result);
+    }
+
+    #[test]
+    fn token_parse_error() {
+        let expression_result = parse_int_ring_expression("5 $");
+
+        assert_eq!(Err(ParseExpressionError{message: "Unexpected '$'; expected a digit, a letter, an operator or a parenthesis".to_string(), position: 2, kind: TokenParseError}), expression_result);
+    }
+
+    #[test]
+    fn add() {
+        let expression = parse_int_ring_expression("2 + 5").expect("ok");
+
+        assert_eq!(Ok(IntRingElement::new(7)), expression.evaluate());
+    }
+
+    #[test]
+    fn sub() {
+        let expression = parse_int_ring_expression("2 - 5").expect("ok");
+
+        assert_eq!(Ok(IntRingElement::new(-3)), expression.evaluate());
+    }
+
+    #[test]
+    fn mul() {
+        let expression = parse_int_ring_expression("2 * 5").expect("ok");
+
+        assert_eq!(Ok(IntRingElement::new(10)), expression.evaluate());
+    }
+
+    #[test]
+    fn div() {
+        let expression = parse_int_ring_expression("6 / 2").expect("ok");
+
+        assert_eq!(Ok(IntRingElement::new(3)), expression.evaluate());
+    }
+
+    #[test]
+    fn add_missing_rhs() {
+        let expression_result = parse_int_ring_expression("2 + ");
+
+        assert_eq!(Err(ParseExpressionError{message: "Unexpected end of input".to_string(), position: 3, kind: UnexpectedEndOfInput}), expression_result);
+    }
+
+    #[test]
+    fn add_missing_lhs() {
+        let expression_result = parse_int_ring_expression(" + 5");
+
+        assert_eq!(Err(ParseExpressionError{message: "Missing left hand side expression for operator".to_string(), position: 1, kind: Unspecified}), expression_result);
+    }
+
+    #[test]
+    fn add_twice() {
+        let expression = parse_int_ring_expression("2 + 5 + 1").expect("ok");
+
+        assert_eq!(Ok(IntRingElement::new(8)), expression.evaluate());
+    }
+
+    #[test]
+    fn add_left_associative() {
+        let expression = parse_int_ring_expression("2 + 5 + 1").expect("ok");
+
+        assert!(matches!(expression, ExpressionComponent::Addition{..}));
+        if let ExpressionComponent::Addition{right, ..} = &expression {
+            assert_eq!(ExpressionComponent::new_int_element(1), **right);
+        } else {
+            panic!("should be addition");
+        }
+    }
+
+    #[test]
+    fn precedence_structure() {
+        let expression = parse_int_ring_expression("2 + 5 * 1").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_addition(
+            ExpressionComponent::new_int_element(2),
+            ExpressionComponent::new_multiplication(
+                ExpressionComponent::new_int_element(5),
+                ExpressionComponent::new_int_element(1))
+        ), expression);
+
+        assert_eq!(Ok(IntRingElement::new(7)), expression.evaluate())
+    }
+
+    #[test]
+    fn precedence_structure2() {
+        let expression = parse_int_ring_expression("2 + 5 * 1 * 3").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_addition(
+            ExpressionComponent::new_int_element(2),
+            ExpressionComponent::new_multiplication(
+                ExpressionComponent::new_multiplication(
+                    ExpressionComponent::new_int_element(5),
+                    ExpressionComponent::new_int_element(1)),
+                ExpressionComponent::new_int_element(3))
+        ), expression);
+
+        assert_eq!(Ok(IntRingElement::new(2 + 5 * 1 * 3)), expression.evaluate())
+    }
+
+    #[test]
+    fn precedence_structure_parentheses() {
+        let expression = parse_int_ring_expression("(2 + 5) * 1 * 3").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_multiplication(
+            ExpressionComponent::new_multiplication(
+                ExpressionComponent::new_parenteses(ExpressionComponent::new_addition(
+                    ExpressionComponent::new_int_element(2),
+                    ExpressionComponent::new_int_element(5))),
+                ExpressionComponent::new_int_element(1)),
+            ExpressionComponent::new_int_element(3),
+        ), expression);
+
+        assert_eq!(Ok(IntRingElement::new((2 + 5) * 1 * 3)), expression.evaluate())
+    }
+
+    #[test]
+    fn precedence_structure_parentheses2() {
+        let expression = parse_int_ring_expression("(2 + (5)) * 1 * (3 + 4)").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_multiplication(
+            ExpressionComponent::new_multiplication(
+                ExpressionComponent::new_parenteses(ExpressionComponent::new_addition(
+                    ExpressionComponent::new_int_element(2),
+                    ExpressionComponent::new_parenteses(ExpressionComponent::new_int_element(5)))),
+                ExpressionComponent::new_int_element(1)),
+            ExpressionComponent::new_parenteses(
+                ExpressionComponent::new_addition(
+                    ExpressionComponent::new_int_element(3),
+                    ExpressionComponent::new_int_element(4),
+                ))
+
+        ), expression);
+
+        assert_eq!(Ok(IntRingElement::new((2 + (5)) * 1 * (3 + 4))), expression.evaluate())
+    }
+
+    #[test]
+    fn add_lower_precedence_than_mul() {
+        let expression = parse_int_ring_expression("2 * 5 + 1").expect("ok");
+
+        assert!(matches!(expression, ExpressionComponent::Addition{..}));
+        if let ExpressionComponent::Addition{right, ..} = &expression {
+            assert_eq!(ExpressionComponent::new_int_element(1), **right);
+        } else {
+            panic!("should be addition");
+        }
+    }
+
+    #[test]
+    fn mul_higher_precedence_than_add() {
+        let expression = parse_int_ring_expression("2 + 5 * 1").expect("ok");
+
+        assert!(matches!(expression, ExpressionComponent::Addition{..}));
+        if let ExpressionComponent::Addition{left, ..} = &expression {
+            assert_eq!(ExpressionComponent::new_int_element(2), **left);
+        } else {
+            panic!("should be addition");
+        }
+    }
+
+    #[test]
+    fn div_higher_precedence_than_add() {
+        let expression = parse_int_ring_expression("2 + 5 / 1").expect("ok");
+
+        assert!(matches!(expression, ExpressionComponent::Addition{..}));
+        if let ExpressionComponent::Addition{left, ..} = &expression {
+            assert_eq!(ExpressionComponent::new_int_element(2), **left);
+        } else {
+            panic!("should be addition");
+        }
+    }
+
+    #[test]
+    fn mul_higher_precedence_than_sub() {
+        let expression = parse_int_ring_expression("2 - 5 * 1").expect("ok");
+
+        assert!(matches!(expression, ExpressionComponent::Subtraction{..}));
+        if let ExpressionComponent::Subtraction{left, ..} = &expression {
+            assert_eq!(ExpressionComponent::new_int_element(2), **left);
+        } else {
+            panic!("should be subtraction");
+        }
+    }
+
+    #[test]
+    fn div_higher_precedence_than_sub() {
+        let expression = parse_int_ring_expression("2 - 5 / 1").expect("ok");
+
+        assert!(matches!(expression, ExpressionComponent::Subtraction{..}));
+        if let ExpressionComponent::Subtraction{left, ..} = &expression {
+            assert_eq!(ExpressionComponent::new_int_element(2), **left);
+        } else {
+            panic!("should be subtraction");
+        }
+    }
+
+    #[test]
+    fn missing_left_parenthesis() {
+        let expression_result = parse_int_ring_expression("3 + 5)");
+
+        assert_eq!(Err(ParseExpressionError{message: "Missing left parenthesis for right parenthesis".to_string(), position: 5, kind: Unspecified}), expression_result);
+    }
+
+    #[test]
+    fn missing_left_parenthesis2() {
+        let expression_result = parse_int_ring_expression("(3 + 5))");
+
+        assert_eq!(Err(ParseExpressionError{message: "Missing left parenthesis for right parenthesis".to_string(), position: 7, kind: Unspecified}), expression_result);
+    }
+
+    #[test]
+    fn missing_right_parenthesis() {
+        let expression_result = parse_int_ring_expression("3 + (3 + 5");
+
+        assert_eq!(Err(ParseExpressionError{message: "Missing right parenthesis for left parenthesis".to_string(), position: 4, kind: Unspecified}), expression_result);
+    }
+
+    #[test]
+    fn missing_right_parenthesis2() {
+        let expression_result = parse_int_ring_expression("(3 + (3 + 5)");
+
+        assert_eq!(Err(ParseExpressionError{message: "Missing right parenthesis for left parenthesis".to_string(), position: 0, kind: Unspecified}), expression_result);
+    }
+
+    #[test]
+    fn emtpy_expression_in_parenthesis() {
+        let expression_result = parse_int_ring_expression("8 + () * 8");
+
+        assert_eq!(Err(ParseExpressionError{message: "No expression".to_string(), position: 5, kind: NoExpression}), expression_result);
+    }
+
+    #[test]
+    fn unary_minus() {
+        let expression = parse_int_ring_expression("2 * (-5)").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_multiplication(
+            ExpressionComponent::new_int_element(2),
+            ExpressionComponent::new_parenteses(
+                ExpressionComponent::new_int_element(-5))
+        ), expression);
+
+        assert_eq!(Ok(IntRingElement::new(-10)), expression.evaluate())
+    }
+
+    #[test]
+    fn leading_unary_minus() {
+        let expression = parse_int_ring_expression("-5").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_int_element(-5), expression);
+    }
+
+    #[test]
+    fn unary_minus_parentheses() {
+        let expression = parse_int_ring_expression("-(3+4)").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_unary_minus(
+            ExpressionComponent::new_parenteses(ExpressionComponent::new_addition(
+                ExpressionComponent::new_int_element(3),
+                ExpressionComponent::new_int_element(4)))
+        ), expression);
+        assert_eq!(Ok(IntRingElement::new(-7)), expression.evaluate());
+    }
+
+    #[test]
+    fn unary_minus_literal_folding() {
+        let negation = |expr| ExpressionComponent::new_unary_minus(expr);
+
+        assert_eq!(Ok(ExpressionComponent::new_int_element(-5)), parse_int_ring_expression("-5"));
+        assert_eq!(Ok(negation(ExpressionComponent::new_int_element(5))), parse_int_ring_expression("- 5"));
+        assert_eq!(Ok(negation(ExpressionComponent::new_parenteses(ExpressionComponent::new_int_element(5)))),
+                   parse_int_ring_expression("-(5)"));
+        assert_eq!(Ok(negation(ExpressionComponent::new_parenteses(ExpressionComponent::new_addition(
+                       ExpressionComponent::new_int_element(2),
+                       ExpressionComponent::new_int_element(3))))),
+                   parse_int_ring_expression("-(2+3)"));
+        assert_eq!(Ok(ExpressionComponent::new_subtraction(
+                       ExpressionComponent::new_int_element(2),
+                       negation(ExpressionComponent::new_int_element(5)))),
+                   parse_int_ring_expression("2 - - 5"));
+    }
+
+    #[test]
+    fn unary_minus_literal_folding_options() {
+        let always = ParseOptions { negative_literals: NegativeLiterals::Always, ..ParseOptions::default() };
+        assert_eq!(Ok(ExpressionComponent::new_int_element(-5)), parse_expression_with_options::<IntRing>("- 5", &always));
+
+        let never = ParseOptions { negative_literals: NegativeLiterals::Never, ..ParseOptions::default() };
+        let expression = parse_expression_with_options::<IntRing>("-5", &never).expect("ok");
+        assert_eq!(ExpressionComponent::new_unary_minus(ExpressionComponent::new_int_element(5)), expression);
+        assert_eq!(Ok(IntRingElement::new(-5)), expression.evaluate());
+    }
+
+    #[test]
+    fn unary_minus_after_operator() {
+        let expression = parse_int_ring_expression("2 - -3").expect("ok");
+        assert_eq!(ExpressionComponent::new_subtraction(
+            ExpressionComponent::new_int_element(2),
+            ExpressionComponent::new_int_element(-3)
+        ), expression);
+        assert_eq!(Ok(IntRingElement::new(5)), expression.evaluate());
+
+        let expression = parse_int_ring_expression("2 * -5").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(-10)), expression.evaluate());
+
+        let expression = parse_int_ring_expression("5 - -2 * 3").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(11)), expression.evaluate());
+
+        let expression = parse_int_ring_expression("-2 * 3 - 1").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(-7)), expression.evaluate());
+    }
+
+    #[test]
+    fn unary_minus_after_each_operator() {
+        let cases = [("3 - -5", 8), ("3 - - 5", 8), ("3 + -5", -2), ("3 * -5", -15), ("15 / -5", -3),
+            ("7 mod -3", 1), ("(-2) ^ - -3", -8), ("3 > -5", 1), ("3 < - 5", 0), ("-5 == -5", 1)];
+        for (str, value) in cases {
+            let expression = parse_int_ring_expression(str).expect("ok");
+
+            assert_eq!(Ok(IntRingElement::new(value)), expression.evaluate(), "{}", str);
+        }
+    }
+
+    #[test]
+    fn double_unary_minus() {
+        let expression = parse_int_ring_expression("- -5").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_unary_minus(ExpressionComponent::new_int_element(-5)), expression);
+        assert_eq!(Ok(IntRingElement::new(5)), expression.evaluate());
+    }
+
+    #[test]
+    fn parentheses_avoid_intermediate_overflow() {
+        let expression = parse_int_ring_expression("9223372036854775807 + (1 - 1)").expect("ok");
+
+        assert_eq!(Ok(IntRingElement::new(i64::MAX)), expression.evaluate());
+    }
+
+    #[test]
+    fn intermediate_overflow_without_parentheses() {
+        let expression = parse_int_ring_expression("9223372036854775807 + 1 - 1").expect("ok");
+
+        assert_eq!(Err(EvaluateExpressionError{message: "Overflow during addition".to_string(), kind: Overflow, position: Some(20), span: Some(0..23)}), expression.evaluate());
+    }
+
+    #[test]
+    fn parentheses_avoid_intermediate_overflow_multiplication() {
+        let expression = parse_int_ring_expression("4611686018427387904 * (2 / 2)").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(4611686018427387904)), expression.evaluate());
+
+        let expression = parse_int_ring_expression("4611686018427387904 * 2 / 2").expect("ok");
+        assert_eq!(Err(EvaluateExpressionError{message: "Overflow during multiplication".to_string(), kind: Overflow, position: Some(20), span: Some(0..23)}), expression.evaluate());
+    }
+
+    #[test]
+    fn from_str() {
+        let expression: ExpressionComponent<IntRing> = "2 + 3".parse().expect("ok");
+        assert_eq!(Ok(IntRingElement::new(5)), expression.evaluate());
+
+        let result = "2 +".parse::<ExpressionComponent<IntRing>>();
+        assert_eq!(Err(ParseExpressionError{message: "Unexpected end of input".to_string(), position: 3, kind: UnexpectedEndOfInput}), result);
+    }
+
+    #[test]
+    fn into_io_error() {
+        let err = parse_int_ring_expression("5 $").unwrap_err();
+
+        let io_err: io::Error = err.into();
+
+        assert_eq!(io::ErrorKind::InvalidData, io_err.kind());
+        assert!(io_err.to_string().contains("at position 2"), "{}", io_err);
+    }
+
+    #[test]
+    fn div_left_associative() {
+        let expression = parse_int_ring_expression("8 / 4 / 2").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_division(
+            ExpressionComponent::new_division(
+                ExpressionComponent::new_int_element(8),
+                ExpressionComponent::new_int_element(4)),
+            ExpressionComponent::new_int_element(2),
+        ), expression);
+        assert_eq!(Ok(IntRingElement::new(1)), expression.evaluate());
+    }
+
+    #[test]
+    fn div_not_associative() {
+        let expression = parse_int_ring_expression("8 / (4 / 2)").expect("ok");
+
+        assert_eq!(Ok(IntRingElement::new(4)), expression.evaluate());
+    }
+
+    #[test]
+    fn div_not_in_ring_intermediate() {
+        let expression = parse_int_ring_expression("8 / 3 / 2").expect("ok");
+
+        let mut divisions = Vec::new();
+        let result = expression.evaluate_with_observer(&mut |event| divisions.push((event.left.clone(), event.right.clone())));
+
+        assert_eq!(Err(EvaluateExpressionError{message: "Result not in ring during division".to_string(), kind: NotInRing { suggested_ring: Some("rational"), remainder: Some(Box::new(DivisionRemainder { quotient: "2".to_string(), remainder: "2".to_string() })) }, position: Some(2), span: Some(0..5)}), result);
+        assert_eq!(vec![(IntRingElement::new(8), IntRingElement::new(3))], divisions);
+    }
+
+    #[test]
+    fn with_tokens() {
+        let str = "(2 + 5) * 3";
+
+        let (tokens, expression) = parse_int_ring_with_tokens(str).expect("ok");
+
+        let expected_tokens: Vec<_> = TokenIterator::new(&str, IntRingTokenParser::new())
+            .collect::<Result<_, _>>().expect("ok");
+        assert_eq!(expected_tokens, tokens);
+        assert_eq!(parse_int_ring_expression(str).expect("ok"), expression);
+    }
+
+    #[test]
+    fn with_tokens_error() {
+        let expression_result = parse_int_ring_with_tokens("2 +");
+
+        assert!(expression_result.is_err());
+    }
+
+    #[test]
+    fn unexpected_end_of_input() {
+        for (str, position) in [("2 +", 3), ("(", 1), ("2 *", 3), ("3 * (2 -", 8)] {
+            let err = parse_int_ring_expression(str).unwrap_err();
+
+            assert_eq!(UnexpectedEndOfInput, err.kind, "{}", str);
+            assert_eq!(position, err.position, "{}", str);
+        }
+    }
+
+    #[test]
+    fn modulo() {
+        let expression = parse_int_ring_expression("7 mod 3").expect("ok");
+        assert_eq!(ExpressionComponent::new_modulo(
+            ExpressionComponent::new_int_element(7),
+            ExpressionComponent::new_int_element(3)), expression);
+        assert_eq!(Ok(IntRingElement::new(1)), expression.evaluate());
+
+        let expression = parse_int_ring_expression("(0 - 1) mod 3").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(2)), expression.evaluate());
+    }
+
+    #[test]
+    fn modulo_precedence() {
+        let expression = parse_int_ring_expression("2 + 7 mod 3 * 2").expect("ok");
+
+        assert_eq!(Ok(IntRingElement::new(4)), expression.evaluate());
+    }
+
+    #[test]
+    fn comparisons() {
+        for (str, value) in [("3 > 2", 1), ("2 == 2", 1), ("1 < 0", 0), ("2 > 3", 0), ("2 == 3", 0), ("-1 < 0", 1)] {
+            let expression = parse_int_ring_expression(str).expect("ok");
+
+            assert_eq!(Ok(IntRingElement::new(value)), expression.evaluate(), "{}", str);
+        }
+    }
+
+    #[test]
+    fn comparison_precedence() {
+        let expression = parse_int_ring_expression("1 + 2 > 2 * 1").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_greater_than(
+            ExpressionComponent::new_addition(
+                ExpressionComponent::new_int_element(1),
+                ExpressionComponent::new_int_element(2)),
+            ExpressionComponent::new_multiplication(
+                ExpressionComponent::new_int_element(2),
+                ExpressionComponent::new_int_element(1)),
+        ), expression);
+        assert_eq!(Ok(IntRingElement::new(1)), expression.evaluate());
+        assert_eq!("1 + 2 > 2 * 1", expression.to_string());
+
+        let expression = parse_int_ring_expression("2 ^ 3 == 8 mod 9 - 0").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(1)), expression.evaluate());
+    }
+
+    #[test]
+    fn comparisons_left_associative() {
+        let expression = parse_int_ring_expression("3 > 2 == 1").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_equal(
+            ExpressionComponent::new_greater_than(
+                ExpressionComponent::new_int_element(3),
+                ExpressionComponent::new_int_element(2)),
+            ExpressionComponent::new_int_element(1),
+        ), expression);
+        assert_eq!(Ok(IntRingElement::new(1)), expression.evaluate());
+    }
+
+    #[test]
+    fn comparison_not_supported() {
+        let expression = parse_mod_ring_expression::<5>("1 < 2").expect("ok");
+
+        assert_eq!(Err(EvaluateExpressionError{message: "Comparison not supported during comparison".to_string(), kind: RingUnspecified, position: Some(2), span: Some(0..5)}), expression.evaluate());
+        assert!(parse_mod_ring_expression::<5>("1 == 6").expect("ok").evaluate().is_ok());
+    }
+
+    #[test]
+    fn conditional() {
+        let expression = parse_int_ring_expression("1 > 0 ? 5 : (1/0)").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_conditional(
+            ExpressionComponent::new_greater_than(
+                ExpressionComponent::new_int_element(1),
+                ExpressionComponent::new_int_element(0)),
+            ExpressionComponent::new_int_element(5),
+            ExpressionComponent::new_parenteses(ExpressionComponent::new_division(
+                ExpressionComponent::new_int_element(1),
+                ExpressionComponent::new_int_element(0))),
+        ), expression);
+        assert_eq!(Ok(IntRingElement::new(5)), expression.evaluate());
+        assert_eq!("1 > 0 ? 5 : (1 / 0)", expression.to_string());
+    }
+
+    #[test]
+    fn conditional_branches() {
+        for (str, value) in [("0 > 1 ? 1 / 0 : 7", 7), ("2 == 2 ? 3 : 1 / 0", 3), ("0 ? 1 : 2", 2), ("-1 ? 1 : 2", 1), ("1 ? -1 : -2", -1)] {
+            let expression = parse_int_ring_expression(str).expect("ok");
+
+            assert_eq!(Ok(IntRingElement::new(value)), expression.evaluate(), "{}", str);
+        }
+
+        let err = parse_int_ring_expression("1 < 0 ? 5 : 1 / 0").expect("ok").evaluate().unwrap_err();
+        assert_eq!(DivisionByZero, err.kind);
+        assert_eq!(Some(14), err.position);
+    }
+
+    #[test]
+    fn conditional_precedence() {
+        let expression = parse_int_ring_expression("1 + 1 ? 2 * 3 : 4 - 5").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_conditional(
+            ExpressionComponent::new_addition(
+                ExpressionComponent::new_int_element(1),
+                ExpressionComponent::new_int_element(1)),
+            ExpressionComponent::new_multiplication(
+                ExpressionComponent::new_int_element(2),
+                ExpressionComponent::new_int_element(3)),
+            ExpressionComponent::new_subtraction(
+                ExpressionComponent::new_int_element(4),
+                ExpressionComponent::new_int_element(5)),
+        ), expression);
+        assert_eq!(Ok(IntRingElement::new(6)), expression.evaluate());
+
+        let expression = parse_int_ring_expression("(0 ? 1 : 2) + 3").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(5)), expression.evaluate());
+    }
+
+    #[test]
+    fn conditional_nested() {
+        let expression = parse_int_ring_expression("0 ? 1 : 0 ? 2 : 3").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_conditional(
+            ExpressionComponent::new_int_element(0),
+            ExpressionComponent::new_int_element(1),
+            ExpressionComponent::new_conditional(
+                ExpressionComponent::new_int_element(0),
+                ExpressionComponent::new_int_element(2),
+                ExpressionComponent::new_int_element(3)),
+        ), expression);
+        assert_eq!(Ok(IntRingElement::new(3)), expression.evaluate());
+        assert_eq!("0 ? 1 : 0 ? 2 : 3", expression.to_string());
+
+        let expression = parse_int_ring_expression("1 ? 0 ? 1 : 2 : 3").expect("ok");
+        assert_eq!(ExpressionComponent::new_conditional(
+            ExpressionComponent::new_int_element(1),
+            ExpressionComponent::new_conditional(
+                ExpressionComponent::new_int_element(0),
+                ExpressionComponent::new_int_element(1),
+                ExpressionComponent::new_int_element(2)),
+            ExpressionComponent::new_int_element(3),
+        ), expression);
+        assert_eq!(Ok(IntRingElement::new(2)), expression.evaluate());
+
+        let expression = parse_int_ring_expression("(1 ? 0 : 1) ? 2 : 3").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(3)), expression.evaluate());
+        assert_eq!(expression, parse_int_ring_expression(expression.to_string()).expect("ok"));
+    }
+
+    #[test]
+    fn conditional_errors() {
+        assert_eq!(Err(ParseExpressionError{message: "Missing colon for question mark".to_string(), position: 2, kind: Unspecified}),
+                   parse_int_ring_expression("1 ? 2"));
+        assert_eq!(Err(ParseExpressionError{message: "Missing question mark for colon".to_string(), position: 2, kind: Unspecified}),
+                   parse_int_ring_expression("1 : 2"));
+        assert_eq!(Err(ParseExpressionError{message: "Missing question mark for colon".to_string(), position: 7, kind: Unspecified}),
+                   parse_int_ring_expression("1 ? (2 : 3)"));
+        assert_eq!(Err(ParseExpressionError{message: "Unexpected end of input".to_string(), position: 7, kind: UnexpectedEndOfInput}),
+                   parse_int_ring_expression("1 ? 2 :"));
+        assert_eq!(Err(ParseExpressionError{message: "Missing expression after question mark".to_string(), position: 2, kind: Unspecified}),
+                   parse_int_ring_expression("1 ? : 3"));
+        assert!(parse_int_ring_expression("? 2 : 3").is_err());
+    }
+
+    #[test]
+    fn modulo_left_associative() {
+        let expression = parse_int_ring_expression("17 mod 5 mod 3").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_modulo(
+            ExpressionComponent::new_modulo(
+                ExpressionComponent::new_int_element(17),
+                ExpressionComponent::new_int_element(5)),
+            ExpressionComponent::new_int_element(3),
+        ), expression);
+        assert_eq!(Ok(IntRingElement::new(2)), expression.evaluate());
+    }
+
+    #[test]
+    fn modulo_same_precedence_as_multiplication() {
+        let expression = parse_int_ring_expression("2 * 8 mod 3").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_modulo(
+            ExpressionComponent::new_multiplication(
+                ExpressionComponent::new_int_element(2),
+                ExpressionComponent::new_int_element(8)),
+            ExpressionComponent::new_int_element(3),
+        ), expression);
+        assert_eq!(Ok(IntRingElement::new(1)), expression.evaluate());
+
+        let expression = parse_int_ring_expression("8 mod 3 * 2").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(4)), expression.evaluate());
+    }
+
+    #[test]
+    fn nested_parentheses() {
+        let expression = parse_int_ring_expression("((1) + (2))").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_parenteses(ExpressionComponent::new_addition(
+            ExpressionComponent::new_parenteses(ExpressionComponent::new_int_element(1)),
+            ExpressionComponent::new_parenteses(ExpressionComponent::new_int_element(2))
+        )), expression);
+    }
+
+    #[test]
+    fn top_level_parentheses() {
+        let expression = parse_int_ring_expression("(2 + 3 * 4)").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_parenteses(ExpressionComponent::new_addition(
+            ExpressionComponent::new_int_element(2),
+            ExpressionComponent::new_multiplication(
+                ExpressionComponent::new_int_element(3),
+                ExpressionComponent::new_int_element(4)))
+        ), expression);
+        assert_eq!(Ok(IntRingElement::new(14)), expression.evaluate());
+
+        let expression = parse_int_ring_expression("((2 + 3) * 4)").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_parenteses(ExpressionComponent::new_multiplication(
+            ExpressionComponent::new_parenteses(ExpressionComponent::new_addition(
+                ExpressionComponent::new_int_element(2),
+                ExpressionComponent::new_int_element(3))),
+            ExpressionComponent::new_int_element(4))
+        ), expression);
+        assert_eq!(Ok(IntRingElement::new(20)), expression.evaluate());
+    }
+
+    #[test]
+    fn top_level_parentheses_positions() {
+        let expression = parse_int_ring_expression("(2 + 3 * 4)").expect("ok");
+        if let ExpressionComponent::Parentheses { inner, .. } = &expression {
+            assert_eq!(Some(3), inner.position().position);
+        } else {
+            panic!("Expected parentheses: {:?}", expression);
+        }
+
+        assert_eq!(Err(EvaluateExpressionError{message: "Overflow during addition".to_string(), kind: Overflow, position: Some(21), span: Some(1..24)}),
+                   parse_int_ring_expression("(9223372036854775807 + 1)").expect("ok").evaluate());
+        assert_eq!(Err(EvaluateExpressionError{message: "Overflow during multiplication".to_string(), kind: Overflow, position: Some(27), span: Some(1..30)}),
+                   parse_int_ring_expression("((9223372036854775807 + 0) * 2)").expect("ok").evaluate());
+        assert_eq!(Err(ParseExpressionError{message: "Unexpected end of input".to_string(), position: 4, kind: UnexpectedEndOfInput}),
+                   parse_int_ring_expression("(2 + "));
+    }
+
+    #[test]
+    fn evaluation_error_spans() {
+        assert_eq!(Err(EvaluateExpressionError{message: "Overflow during multiplication".to_string(), kind: Overflow, position: Some(20), span: Some(0..23)}),
+                   parse_int_ring_expression("9223372036854775807 * 2").expect("ok").evaluate());
+        assert_eq!(Err(EvaluateExpressionError{message: "Overflow during multiplication".to_string(), kind: Overflow, position: Some(31), span: Some(4..34)}),
+                   parse_int_ring_expression("1 + (-9223372036854775807 - 1) * 2 - 3").expect("ok").evaluate());
+        assert_eq!(Err(EvaluateExpressionError{message: "Overflow during multiplication".to_string(), kind: Overflow, position: Some(20), span: Some(0..26)}),
+                   parse_int_ring_calculator_expression("9223372036854775807 * 200%").expect("ok").evaluate());
+        assert_eq!(Err(EvaluateExpressionError{message: "Overflow during abs".to_string(), kind: Overflow, position: Some(7), span: Some(7..36)}),
+                   parse_int_ring_expression("gcd(2, abs(-9223372036854775807 - 1))").expect("ok").evaluate());
+    }
+
+    #[test]
+    fn deep_parentheses() {
+        let n = 100_000;
+        let str = "(".repeat(n) + "1" + &")".repeat(n);
+
+        let expression = parse_int_ring_expression(&str).expect("ok");
+
+        assert_eq!(Ok(IntRingElement::new(1)), expression.evaluate());
+    }
+
+    #[test]
+    fn many_additions() {
+        let n = 100_000;
+        let str = "1".to_string() + &" + 1".repeat(n);
+
+        let expression = parse_int_ring_expression(&str).expect("ok");
+
+        // walk the left hand sides, the derived comparison would recurse as deep as the expression
+        let mut additions = 0;
+        let mut expr = &expression;
+        while let ExpressionComponent::Addition { left, .. } = expr {
+            additions += 1;
+            expr = left;
+        }
+        assert_eq!(n, additions);
+        assert_eq!(ExpressionComponent::new_int_element(1), *expr);
+    }
+
+    #[test]
+    fn very_deep_parentheses() {
+        let n = 10_000;
+        let str = "(".repeat(n) + "1" + &")".repeat(n);
+
+        let expression = parse_int_ring_expression(&str).expect("ok");
+
+        let mut parentheses = 0;
+        let mut expr = &expression;
+        while let ExpressionComponent::Parentheses { inner, .. } = expr {
+            parentheses += 1;
+            expr = inner;
+        }
+        assert_eq!(n, parentheses);
+        assert_eq!(ExpressionComponent::new_int_element(1), *expr);
+    }
+
+    #[test]
+    fn depth_limit() {
+        // parentheses, multiplication, parentheses, addition and the operands of the addition
+        let str = "((1 + 2) * 3)";
+
+        assert!(parse_int_ring_expression_with_limits(str, &ParseLimits { max_depth: 5 }).is_ok());
+        assert_eq!(Err(ParseExpressionError{message: "Expression nested deeper than 4".to_string(), position: 4, kind: DepthExceeded}),
+                   parse_int_ring_expression_with_limits(str, &ParseLimits { max_depth: 4 }));
+    }
+
+    #[test]
+    fn depth_limit_parentheses() {
+        let str = "(".repeat(10_000) + "1" + &")".repeat(10_000);
+
+        assert_eq!(Err(ParseExpressionError{message: "Expression nested deeper than 100".to_string(), position: 0, kind: DepthExceeded}),
+                   parse_int_ring_expression_with_limits(str, &ParseLimits { max_depth: 100 }));
+    }
+
+    #[test]
+    fn evaluate_error_position() {
+        let expression = parse_int_ring_expression("6 / 4").expect("ok");
+        assert_eq!(Some(2), expression.evaluate().unwrap_err().position);
+
+        let expression = parse_int_ring_expression("1 + 2 * (6 / 4) - 3").expect("ok");
+        assert_eq!(Some(11), expression.evaluate().unwrap_err().position);
+    }
+
+    #[test]
+    fn negation_error_position() {
+        assert_eq!(Err(EvaluateExpressionError{message: "Overflow during negation".to_string(), kind: Overflow, position: Some(4), span: Some(4..31)}),
+                   parse_int_ring_expression("1 + -(-9223372036854775807 - 1)").expect("ok").evaluate());
+        assert_eq!(Err(EvaluateExpressionError{message: "Overflow during negation".to_string(), kind: Overflow, position: Some(4), span: Some(4..35)}),
+                   parse_int_ring_expression("2 * -(-9223372036854775807 - 1) ^ 1").expect("ok").evaluate());
+    }
+
+    #[test]
+    fn percent() {
+        let expression = parse_int_ring_calculator_expression("200%").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_parenteses(ExpressionComponent::new_division(
+            ExpressionComponent::new_int_element(200),
+            ExpressionComponent::new_int_element(100))), expression);
+        assert_eq!(Ok(IntRingElement::new(2)), expression.evaluate());
+    }
+
+    #[test]
+    fn percent_precedence() {
+        let expression = parse_int_ring_calculator_expression("1 + 300% * 2 - (1 + 99)%").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(6)), expression.evaluate());
+
+        let expression = parse_int_ring_calculator_expression("-200% - 10000%%").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(-3)), expression.evaluate());
+    }
+
+    #[test]
+    fn percent_not_in_ring() {
+        let expression = parse_int_ring_calculator_expression("50%").expect("ok");
+
+        let err = expression.evaluate().unwrap_err();
+        assert!(matches!(err.kind, NotInRing { .. }));
+        assert_eq!(Some(2), err.position);
+    }
+
+    #[test]
+    fn percent_errors() {
+        assert_eq!(Err(ParseExpressionError{message: "Missing expression for percent".to_string(), position: 0, kind: Unspecified}),
+                   parse_int_ring_calculator_expression("%"));
+        assert_eq!(Err(ParseExpressionError{message: "Missing expression for percent".to_string(), position: 4, kind: Unspecified}),
+                   parse_int_ring_calculator_expression("2 + %"));
+        assert_eq!(Err(ParseExpressionError{message: "Ring element cannot be followed by another ring element in expression".to_string(), position: 2, kind: Unspecified}),
+                   parse_int_ring_calculator_expression("50% 2"));
+        assert_eq!(TokenParseError, parse_int_ring_expression("50%").unwrap_err().kind);
+    }
+
+    #[test]
+    fn decimal_fraction_not_in_int_ring() {
+        assert_eq!(Err(ParseExpressionError{message: "Invalid integer '6.5'".to_string(), position: 4, kind: InvalidLiteral}),
+                   parse_int_ring_expression("2 * 6.5"));
+        assert_eq!(Err(ParseExpressionError{message: "Invalid integer '-6.5'".to_string(), position: 1, kind: InvalidLiteral}),
+                   parse_int_ring_expression("-6.5 + 1"));
+    }
+
+    #[test]
+    fn decimal_fraction_not_in_rational_ring() {
+        assert_eq!(Err(ParseExpressionError{message: "Invalid rational number '6.5'".to_string(), position: 4, kind: InvalidLiteral}),
+                   parse_rational_ring_expression("2 * 6.5"));
+    }
+
+    #[test]
+    fn percent_power() {
+        let expression = parse_int_ring_calculator_expression("1 + 1000% ^ 2").expect("ok");
+
+        assert_eq!(Ok(IntRingElement::new(101)), expression.evaluate());
+    }
+
+    #[test]
+    fn power_right_associative() {
+        let expression = parse_int_ring_expression("2 ^ 3 ^ 2").expect("ok");
+
+        assert_eq!(ExpressionComponent::new_power(
+            ExpressionComponent::new_int_element(2),
+            ExpressionComponent::new_power(
+                ExpressionComponent::new_int_element(3),
+                ExpressionComponent::new_int_element(2))), expression);
+        assert_eq!(Ok(IntRingElement::new(512)), expression.evaluate());
+    }
+
+    #[test]
+    fn power_higher_precedence_than_mul() {
+        let expression = parse_int_ring_expression("2 * 3 ^ 2").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(18)), expression.evaluate());
+
+        let expression = parse_int_ring_expression("1 + 2 * 3 ^ 2 ^ 1 - 4").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(15)), expression.evaluate());
+    }
+
+    #[test]
+    fn unary_minus_precedence() {
+        let expression = parse_int_ring_expression("-5 * 2").expect("ok");
+        assert_eq!(ExpressionComponent::new_multiplication(
+            ExpressionComponent::new_int_element(-5),
+            ExpressionComponent::new_int_element(2)), expression);
+        assert_eq!(Ok(IntRingElement::new(-10)), expression.evaluate());
+
+        let expression = parse_int_ring_expression("-2 ^ 2").expect("ok");
+        assert_eq!(ExpressionComponent::new_unary_minus(ExpressionComponent::new_power(
+            ExpressionComponent::new_int_element(2),
+            ExpressionComponent::new_int_element(2))), expression);
+        assert_eq!(Ok(IntRingElement::new(-4)), expression.evaluate());
+
+        let expression = parse_int_ring_expression("(-2) ^ 2").expect("ok");
+        assert_eq!(ExpressionComponent::new_power(
+            ExpressionComponent::new_parenteses(ExpressionComponent::new_int_element(-2)),
+            ExpressionComponent::new_int_element(2)), expression);
+        assert_eq!(Ok(IntRingElement::new(4)), expression.evaluate());
+    }
+
+    #[test]
+    fn power_negative_exponent() {
+        let expression = parse_int_ring_expression("2 ^ -1").expect("ok");
+
+        let err = expression.evaluate().unwrap_err();
+        assert_eq!("Negative exponent during exponentiation", err.message);
+        assert_eq!(Some(2), err.position);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,499 @@
+use crate::expression::ExpressionComponent;
+use crate::expression::ExpressionComponent::{RingElement, Variable, FunctionCall, Addition, Subtraction, Multiplication, Division, Modulo, Power, LessThan, GreaterThan, Equal, Conditional, Parentheses, UnaryMinus};
+use crate::expression::ring::Ring;
+use std::fmt;
+use std::fmt::{Display, Formatter, Write};
+
+/// Symbol used for multiplication when printing expressions.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum MultiplicationSymbol {
+    /// The symbol given by [Ring::OPERATOR_SYMBOLS].
+    Ring,
+    /// `2 * 3`
+    Asterisk,
+    /// `2 × 3`
+    Times,
+    /// `2 3`, the operands are juxtaposed. The output cannot be parsed again.
+    Implicit,
+}
+
+/// Options for printing expressions in infix notation.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct PrintOptions {
+    /// Put spaces around binary operators.
+    pub spaces: bool,
+    /// Put parentheses around all operations that are operands of other operations.
+    pub always_parenthesize: bool,
+    pub multiplication_symbol: MultiplicationSymbol,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        PrintOptions {
+            spaces: true,
+            always_parenthesize: false,
+            multiplication_symbol: MultiplicationSymbol::Ring,
+        }
+    }
+}
+
+impl<R: Ring> ExpressionComponent<R> {
+    /// Print expression in infix notation as specified by `options`. Parentheses in the expression
+    /// are printed as is, so expressions constructed without [Parentheses] nodes may print
+    /// ambiguously unless [PrintOptions::always_parenthesize] is set. The negation of a literal
+    /// prints as e.g. `-(5)`, since `-5` parses as a negative literal.
+    pub fn to_infix(&self, options: &PrintOptions) -> String {
+        let mut str = String::new();
+        self.write_infix(&mut str, options, false).expect("writing to string");
+        str
+    }
+
+    fn write_infix(&self, f: &mut impl Write, options: &PrintOptions, operand: bool) -> fmt::Result {
+        let parenthesize = operand && options.always_parenthesize && self.is_operator();
+        if parenthesize {
+            f.write_char('(')?;
+        }
+        match self {
+            RingElement(element) => write!(f, "{}", element)?,
+            Variable(name) => f.write_str(name)?,
+            FunctionCall { name, args, .. } => {
+                f.write_str(name)?;
+                f.write_char('(')?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    arg.write_infix(f, options, false)?;
+                }
+                f.write_char(')')?;
+            }
+            Parentheses { inner, .. } => {
+                f.write_char('(')?;
+                inner.write_infix(f, options, false)?;
+                f.write_char(')')?;
+            }
+            UnaryMinus { inner, .. } => {
+                f.write_char('-')?;
+                // a unary minus binds looser than `^`, so a negated power needs no parentheses, while
+                // a negated literal does, since `-5` parses as a negative literal
+                if (inner.is_operator() && !matches!(**inner, Power { .. })) || matches!(**inner, RingElement(_)) {
+                    f.write_char('(')?;
+                    inner.write_infix(f, options, false)?;
+                    f.write_char(')')?;
+                } else {
+                    inner.write_infix(f, options, true)?;
+                }
+            }
+            Addition { left, right, .. } => {
+                Self::write_binary_operation(f, options, R::OPERATOR_SYMBOLS.addition, left, right, false)?
+            }
+            Subtraction { left, right, .. } => {
+                Self::write_binary_operation(f, options, R::OPERATOR_SYMBOLS.subtraction, left, right, false)?
+            }
+            Multiplication { left, right, .. } => {
+                let symbol = match options.multiplication_symbol {
+                    MultiplicationSymbol::Ring => R::OPERATOR_SYMBOLS.multiplication,
+                    MultiplicationSymbol::Asterisk => "*",
+                    MultiplicationSymbol::Times => "×",
+                    MultiplicationSymbol::Implicit => "",
+                };
+                Self::write_binary_operation(f, options, symbol, left, right, false)?
+            }
+            Division { left, right, .. } => {
+                Self::write_binary_operation(f, options, R::OPERATOR_SYMBOLS.division, left, right, false)?
+            }
+            Modulo { left, right, .. } => {
+                Self::write_binary_operation(f, options, R::OPERATOR_SYMBOLS.modulo, left, right, false)?
+            }
+            Power { left, right, .. } => {
+                Self::write_binary_operation(f, options, R::OPERATOR_SYMBOLS.power, left, right, left.starts_with_minus())?
+            }
+            LessThan { left, right, .. } => {
+                Self::write_binary_operation(f, options, R::OPERATOR_SYMBOLS.less_than, left, right, false)?
+            }
+            GreaterThan { left, right, .. } => {
+                Self::write_binary_operation(f, options, R::OPERATOR_SYMBOLS.greater_than, left, right, false)?
+            }
+            Equal { left, right, .. } => {
+                Self::write_binary_operation(f, options, R::OPERATOR_SYMBOLS.equal, left, right, false)?
+            }
+            Conditional { condition, then, otherwise, .. } => {
+                Self::write_binary_operation(f, options, "?", condition, then, false)?;
+                f.write_str(if options.spaces { " : " } else { ":" })?;
+                otherwise.write_infix(f, options, true)?;
+            }
+        }
+        if parenthesize {
+            f.write_char(')')?;
+        }
+        Ok(())
+    }
+
+    fn write_binary_operation(
+        f: &mut impl Write,
+        options: &PrintOptions,
+        symbol: &str,
+        left: &ExpressionComponent<R>,
+        right: &ExpressionComponent<R>,
+        parenthesize_left: bool) -> fmt::Result
+    {
+        if parenthesize_left {
+            f.write_char('(')?;
+            left.write_infix(f, options, false)?;
+            f.write_char(')')?;
+        } else {
+            left.write_infix(f, options, true)?;
+        }
+        if symbol.is_empty() {
+            f.write_char(' ')?;
+        } else if options.spaces {
+            write!(f, " {} ", symbol)?;
+        } else {
+            f.write_str(symbol)?;
+        }
+        right.write_infix(f, options, true)
+    }
+
+    /// Print expression in infix notation with the fewest parentheses needed given the precedence
+    /// and associativity of the operators. [Parentheses] nodes in the expression are ignored, so
+    /// `(2 + 3) * 4` prints as is while `2 + (3 * 4)` prints as `2 + 3 * 4`. The negation of a
+    /// literal prints as e.g. `-(5)`, since `-5` parses as a negative literal. Parsing the result
+    /// gives an expression equal to this one up to [Parentheses] nodes, provided ring elements
+    /// print as literals.
+    pub fn to_string_minimal(&self) -> String {
+        let mut str = String::new();
+        self.write_minimal(&mut str).expect("writing to string");
+        str
+    }
+
+    fn write_minimal(&self, f: &mut impl Write) -> fmt::Result {
+        let (symbol, left, right) = match self {
+            RingElement(element) => return write!(f, "{}", element),
+            Variable(name) => return f.write_str(name),
+            FunctionCall { name, args, .. } => {
+                f.write_str(name)?;
+                f.write_char('(')?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    arg.write_minimal(f)?;
+                }
+                return f.write_char(')');
+            }
+            Parentheses { inner, .. } => return inner.write_minimal(f),
+            UnaryMinus { inner, .. } => {
+                let inner = inner.without_parentheses();
+                f.write_char('-')?;
+                let parenthesize = (inner.is_operator() && !matches!(inner, Power { .. })) || matches!(inner, RingElement(_));
+                return Self::write_minimal_operand(f, inner, parenthesize);
+            }
+            Conditional { condition, then, otherwise, .. } => {
+                // conditionals nest to the right, so only a conditional as condition is parenthesized
+                let condition = condition.without_parentheses();
+                Self::write_minimal_operand(f, condition, matches!(condition, Conditional { .. }))?;
+                f.write_str(" ? ")?;
+                then.without_parentheses().write_minimal(f)?;
+                f.write_str(" : ")?;
+                return otherwise.without_parentheses().write_minimal(f);
+            }
+            Addition { left, right, .. } => (R::OPERATOR_SYMBOLS.addition, left, right),
+            Subtraction { left, right, .. } => (R::OPERATOR_SYMBOLS.subtraction, left, right),
+            Multiplication { left, right, .. } => (R::OPERATOR_SYMBOLS.multiplication, left, right),
+            Division { left, right, .. } => (R::OPERATOR_SYMBOLS.division, left, right),
+            Modulo { left, right, .. } => (R::OPERATOR_SYMBOLS.modulo, left, right),
+            Power { left, right, .. } => (R::OPERATOR_SYMBOLS.power, left, right),
+            LessThan { left, right, .. } => (R::OPERATOR_SYMBOLS.less_than, left, right),
+            GreaterThan { left, right, .. } => (R::OPERATOR_SYMBOLS.greater_than, left, right),
+            Equal { left, right, .. } => (R::OPERATOR_SYMBOLS.equal, left, right),
+        };
+        let left = left.without_parentheses();
+        let right = right.without_parentheses();
+        // operators of equal precedence associate to the left, except for right associative operators,
+        // and a unary minus binds looser than `^`, so a negative base of a power is parenthesized
+        let left_parenthesized = (left.is_operator() && (left.precedence() < self.precedence()
+            || (left.precedence() == self.precedence() && self.is_right_associative())))
+            || (matches!(self, Power { .. }) && left.starts_with_minus());
+        let right_parenthesized = right.is_operator() && (right.precedence() < self.precedence()
+            || (right.precedence() == self.precedence() && !self.is_right_associative()));
+        Self::write_minimal_operand(f, left, left_parenthesized)?;
+        write!(f, " {} ", symbol)?;
+        Self::write_minimal_operand(f, right, right_parenthesized)
+    }
+
+    fn write_minimal_operand(f: &mut impl Write, operand: &ExpressionComponent<R>, parenthesize: bool) -> fmt::Result {
+        if parenthesize {
+            f.write_char('(')?;
+            operand.write_minimal(f)?;
+            f.write_char(')')
+        } else {
+            operand.write_minimal(f)
+        }
+    }
+
+    /// Whether the expression prints with a leading minus sign, i.e. is a negation or a negative
+    /// literal.
+    fn starts_with_minus(&self) -> bool {
+        match self {
+            UnaryMinus { .. } => true,
+            RingElement(element) => element.to_string().starts_with('-'),
+            _ => false,
+        }
+    }
+
+    fn without_parentheses(&self) -> &ExpressionComponent<R> {
+        let mut expression = self;
+        while let Parentheses { inner, .. } = expression {
+            expression = inner;
+        }
+        expression
+    }
+}
+
+impl<R: Ring> Display for ExpressionComponent<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_infix(f, &PrintOptions::default(), false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::parser::parse_int_ring_expression;
+    use crate::expression::print::{PrintOptions, MultiplicationSymbol};
+    use crate::expression::ring::{Ring, RingResult, OperatorSymbols};
+    use crate::expression::ring::intring::{IntRing, IntRingElement};
+    use crate::expression::ExpressionComponent;
+    use crate::expression::ExpressionComponent::{RingElement, Variable, FunctionCall, Addition, Subtraction, Multiplication, Division, Modulo, Power, LessThan, GreaterThan, Equal, Conditional, Parentheses, UnaryMinus};
+
+    struct DotRing {
+    }
+
+    impl Ring for DotRing {
+        type RingElementType = IntRingElement;
+
+        const OPERATOR_SYMBOLS: OperatorSymbols = OperatorSymbols {
+            multiplication: "·",
+            ..OperatorSymbols::DEFAULT
+        };
+
+        fn zero() -> Self::RingElementType {
+            IntRing::zero()
+        }
+
+        fn one() -> Self::RingElementType {
+            IntRing::one()
+        }
+
+        fn parse_element(str: &str) -> RingResult<Self::RingElementType> {
+            IntRing::parse_element(str)
+        }
+
+        fn add(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+            IntRing::add(elm1, elm2)
+        }
+
+        fn sub(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+            IntRing::sub(elm1, elm2)
+        }
+
+        fn mul(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+            IntRing::mul(elm1, elm2)
+        }
+
+        fn div(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+            IntRing::div(elm1, elm2)
+        }
+
+        fn rem(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+            IntRing::rem(elm1, elm2)
+        }
+    }
+
+    #[test]
+    fn spaced() {
+        let expression = parse_int_ring_expression("2 + 3 * 4").expect("ok");
+
+        assert_eq!("2 + 3 * 4", expression.to_infix(&PrintOptions::default()));
+    }
+
+    #[test]
+    fn unspaced() {
+        let expression = parse_int_ring_expression("2 + 3 * 4").expect("ok");
+        let options = PrintOptions { spaces: false, ..PrintOptions::default() };
+
+        assert_eq!("2+3*4", expression.to_infix(&options));
+    }
+
+    #[test]
+    fn always_parenthesize() {
+        let expression = parse_int_ring_expression("2 + 3 * 4").expect("ok");
+        let options = PrintOptions { always_parenthesize: true, ..PrintOptions::default() };
+
+        assert_eq!("2 + (3 * 4)", expression.to_infix(&options));
+    }
+
+    #[test]
+    fn multiplication_symbol() {
+        let expression = parse_int_ring_expression("2 + 3 * (4 - 1)").expect("ok");
+
+        let options = PrintOptions { multiplication_symbol: MultiplicationSymbol::Times, ..PrintOptions::default() };
+        assert_eq!("2 + 3 × (4 - 1)", expression.to_infix(&options));
+
+        let options = PrintOptions { multiplication_symbol: MultiplicationSymbol::Implicit, ..PrintOptions::default() };
+        assert_eq!("2 + 3 (4 - 1)", expression.to_infix(&options));
+    }
+
+    #[test]
+    fn function_call() {
+        let expression = parse_int_ring_expression("gcd(12, 3 * 6) + abs(-5)").expect("ok");
+
+        assert_eq!("gcd(12, 3 * 6) + abs(-5)", expression.to_string());
+    }
+
+    #[test]
+    fn modulo() {
+        let expression = parse_int_ring_expression("2 + 7 mod 3").expect("ok");
+
+        assert_eq!("2 + 7 mod 3", expression.to_string());
+    }
+
+    #[test]
+    fn power() {
+        let expression = parse_int_ring_expression("2 * 3 ^ 2 ^ 2").expect("ok");
+
+        assert_eq!("2 * 3 ^ 2 ^ 2", expression.to_string());
+    }
+
+    #[test]
+    fn power_negative_base() {
+        assert_eq!("-2 ^ 2", parse_int_ring_expression("-2 ^ 2").expect("ok").to_string());
+        assert_eq!("(-2) ^ 2", parse_int_ring_expression("(-2) ^ 2").expect("ok").to_string());
+
+        let expression = ExpressionComponent::<IntRing>::new_power(
+            -ExpressionComponent::new_variable("x"),
+            ExpressionComponent::new_int_element(2));
+        assert_eq!("(-x) ^ 2", expression.to_string());
+        let expression = ExpressionComponent::<IntRing>::new_power(
+            ExpressionComponent::new_int_element(-2),
+            ExpressionComponent::new_int_element(2));
+        assert_eq!("(-2) ^ 2", expression.to_string());
+    }
+
+    #[test]
+    fn display() {
+        let expression = parse_int_ring_expression("(2 + 3) * 4 / 2").expect("ok");
+
+        assert_eq!("(2 + 3) * 4 / 2", expression.to_string());
+    }
+
+    #[test]
+    fn ring_operator_symbols() {
+        let expression = ExpressionComponent::<DotRing>::new_addition(
+            ExpressionComponent::new_ring_element(IntRingElement::new(2)),
+            ExpressionComponent::new_multiplication(
+                ExpressionComponent::new_ring_element(IntRingElement::new(3)),
+                ExpressionComponent::new_ring_element(IntRingElement::new(4))));
+
+        assert_eq!("2 + 3 · 4", expression.to_string());
+
+        let options = PrintOptions { multiplication_symbol: MultiplicationSymbol::Asterisk, ..PrintOptions::default() };
+        assert_eq!("2 + 3 * 4", expression.to_infix(&options));
+    }
+
+    #[test]
+    fn display_reparses() {
+        for str in ["(2 + 5) * 3", "-(3 + 4) mod 2", "2 - -3 * (1 / 1)", "-x ^ 2", "x > 1 ? -x : (1 ? 2 : 3)"] {
+            let expression = parse_int_ring_expression(str).expect("ok");
+            let printed = expression.to_string();
+
+            assert_eq!(expression, parse_int_ring_expression(&printed).expect("ok"), "{}", printed);
+        }
+        assert!(parse_int_ring_expression("(2 + 5) * 3").unwrap().to_string().contains("(2 + 5)"));
+    }
+
+    #[test]
+    fn display_negated_literal_reparses() {
+        let negated_literal = -ExpressionComponent::new_int_element(5);
+        let expressions = [
+            parse_int_ring_expression("- 5").expect("ok"),
+            parse_int_ring_expression("2 * - 5").expect("ok"),
+            parse_int_ring_expression("(- 5) ^ 2").expect("ok"),
+            parse_int_ring_expression("- -5").expect("ok"),
+            negated_literal.clone(),
+            ExpressionComponent::new_power(negated_literal.clone(), ExpressionComponent::new_int_element(2)),
+            ExpressionComponent::new_subtraction(ExpressionComponent::new_int_element(2), negated_literal),
+        ];
+        for expression in expressions {
+            let printed = expression.to_string();
+            let reparsed = parse_int_ring_expression(&printed).expect("ok");
+
+            assert_eq!(without_parentheses(&expression), without_parentheses(&reparsed), "{}", printed);
+            assert_eq!(expression.evaluate(), reparsed.evaluate(), "{}", printed);
+        }
+        assert_eq!("-(5)", parse_int_ring_expression("- 5").expect("ok").to_string());
+        assert_eq!("(-(5)) ^ 2", ExpressionComponent::new_power(-ExpressionComponent::new_int_element(5), ExpressionComponent::new_int_element(2)).to_string());
+    }
+
+    /// Expression without [Parentheses](ExpressionComponent::Parentheses) nodes, which are
+    /// ignored when printing with minimal parentheses.
+    fn without_parentheses(expression: &ExpressionComponent<IntRing>) -> ExpressionComponent<IntRing> {
+        match expression {
+            RingElement(_) | Variable(_) => expression.clone(),
+            FunctionCall { name, args, .. } => ExpressionComponent::new_function_call(name.clone(), args.iter().map(without_parentheses).collect()),
+            Parentheses { inner, .. } => without_parentheses(inner),
+            UnaryMinus { inner, .. } => ExpressionComponent::new_unary_minus(without_parentheses(inner)),
+            Addition { left, right, .. } => ExpressionComponent::new_addition(without_parentheses(left), without_parentheses(right)),
+            Subtraction { left, right, .. } => ExpressionComponent::new_subtraction(without_parentheses(left), without_parentheses(right)),
+            Multiplication { left, right, .. } => ExpressionComponent::new_multiplication(without_parentheses(left), without_parentheses(right)),
+            Division { left, right, .. } => ExpressionComponent::new_division(without_parentheses(left), without_parentheses(right)),
+            Modulo { left, right, .. } => ExpressionComponent::new_modulo(without_parentheses(left), without_parentheses(right)),
+            Power { left, right, .. } => ExpressionComponent::new_power(without_parentheses(left), without_parentheses(right)),
+            LessThan { left, right, .. } => ExpressionComponent::new_less_than(without_parentheses(left), without_parentheses(right)),
+            GreaterThan { left, right, .. } => ExpressionComponent::new_greater_than(without_parentheses(left), without_parentheses(right)),
+            Equal { left, right, .. } => ExpressionComponent::new_equal(without_parentheses(left), without_parentheses(right)),
+            Conditional { condition, then, otherwise, .. } => ExpressionComponent::new_conditional(
+                without_parentheses(condition), without_parentheses(then), without_parentheses(otherwise)),
+        }
+    }
+
+    #[test]
+    fn minimal_parentheses() {
+        let minimal = |str| parse_int_ring_expression(str).expect("ok").to_string_minimal();
+
+        assert_eq!("(2 + 3) * 4", minimal("(2 + 3) * 4"));
+        assert_eq!("2 + 3 * 4", minimal("2 + (3 * 4)"));
+        assert_eq!("2 - 3 - 4", minimal("(2 - 3) - 4"));
+        assert_eq!("2 - (3 - 4)", minimal("2 - (3 - 4)"));
+        assert_eq!("2 / (3 * 4) mod 5", minimal("((2 / (3 * 4)) mod 5)"));
+        assert_eq!("2 ^ 3 ^ 4", minimal("2 ^ (3 ^ 4)"));
+        assert_eq!("(2 ^ 3) ^ 4", minimal("(2 ^ 3) ^ 4"));
+        assert_eq!("-x ^ 2", minimal("-(x ^ 2)"));
+        assert_eq!("(-x) ^ 2", minimal("(-x) ^ 2"));
+        assert_eq!("(-2) ^ 2", minimal("(-2) ^ 2"));
+        assert_eq!("-(2 + x) * -y", minimal("(-((2 + x))) * (-y)"));
+        assert_eq!("-(5) - -5", minimal("-(5) - (-5)"));
+        assert_eq!("gcd(2 * 3, abs(x))", minimal("gcd((2 * 3), abs((x)))"));
+        assert_eq!("1 + 2 > 3 == 1", minimal("((1 + 2) > 3) == 1"));
+        assert_eq!("1 < (2 < 3)", minimal("1 < (2 < 3)"));
+        assert_eq!("x > 0 ? x + 1 : 0 ? 1 : 2", minimal("(x > 0) ? (x + 1) : (0 ? 1 : 2)"));
+        assert_eq!("(x ? 1 : 0) ? 2 : 3", minimal("(x ? 1 : 0) ? 2 : 3"));
+        assert_eq!("1 + (x ? 2 : 3)", minimal("1 + (x ? 2 : 3)"));
+    }
+
+    #[test]
+    fn minimal_parentheses_reparses() {
+        let expressions = [
+            "(2 + 3) * 4", "2 + (3 * 4)", "((1 - 2) - (3 - 4)) - 5", "2 ^ (3 ^ 4) ^ (5 * 6)", "(2 ^ 3) ^ -4",
+            "-(x ^ 2) mod (7 mod 3)", "-(5) * (-(-(x)))", "gcd((1 + 2) * 3, (4))", "((((7))))",
+            "(-2) ^ 2", "-2 ^ 2", "(-(x)) ^ -2 ^ 3", "(x ? 1 : 2) ? (3 ? 4 : 5) : 6 ? 7 : 8", "(x ? 1 : 2) * 3",
+        ];
+        for str in expressions {
+            let expression = parse_int_ring_expression(str).expect("ok");
+            let minimal = expression.to_string_minimal();
+            let reparsed = parse_int_ring_expression(&minimal).expect("ok");
+
+            assert_eq!(without_parentheses(&expression), without_parentheses(&reparsed), "{}", minimal);
+            assert_eq!(minimal, reparsed.to_string_minimal());
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,88 @@
+use crate::expression::ExpressionComponent;
+use crate::expression::ring::Ring;
+use rand::Rng;
+
+/// Generate a random well-formed expression with `size` nodes, for use in benchmarks and
+/// property tests. Ring elements in the leaves are generated by `leaf`. The expression is
+/// generated without recursion, since random trees of large size can be deep.
+pub fn random_expression<R, G, F>(rng: &mut G, size: usize, mut leaf: F) -> ExpressionComponent<R>
+    where R: Ring, G: Rng, F: FnMut(&mut G) -> R::RingElementType
+{
+    enum Step {
+        Generate(usize),
+        BuildParentheses,
+        BuildBinaryOperation,
+    }
+
+    let mut steps = vec![Step::Generate(size)];
+    let mut generated: Vec<ExpressionComponent<R>> = Vec::new();
+    while let Some(step) = steps.pop() {
+        match step {
+            Step::Generate(size) if size <= 1 => generated.push(ExpressionComponent::new_ring_element(leaf(rng))),
+            Step::Generate(2) => {
+                steps.push(Step::BuildParentheses);
+                steps.push(Step::Generate(1));
+            }
+            Step::Generate(size) => {
+                let left_size = rng.gen_range(1..size - 1);
+                steps.push(Step::BuildBinaryOperation);
+                steps.push(Step::Generate(size - 1 - left_size));
+                steps.push(Step::Generate(left_size));
+            }
+            Step::BuildParentheses => {
+                let inner = generated.pop().unwrap();
+                generated.push(ExpressionComponent::new_parenteses(inner));
+            }
+            Step::BuildBinaryOperation => {
+                let right = generated.pop().unwrap();
+                let left = generated.pop().unwrap();
+                let construct_expression = match rng.gen_range(0..5) {
+                    0 => ExpressionComponent::new_addition,
+                    1 => ExpressionComponent::new_subtraction,
+                    2 => ExpressionComponent::new_multiplication,
+                    3 => ExpressionComponent::new_division,
+                    _ => ExpressionComponent::new_modulo,
+                };
+                generated.push(construct_expression(left, right));
+            }
+        }
+    }
+
+    debug_assert_eq!(1, generated.len());
+    generated.pop().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::random::random_expression;
+    use crate::expression::ring::intring::{IntRing, IntRingElement};
+    use crate::expression::ExpressionComponent;
+    use crate::expression::ring::RingErrorKind::{Overflow, DivisionByZero, NotInRing};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn random_expression_size() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for size in [1, 2, 3, 10, 1000] {
+            let expression: ExpressionComponent<IntRing> =
+                random_expression(&mut rng, size, |rng| IntRingElement::new(rng.gen_range(-100..100)));
+
+            assert_eq!(size, expression.node_count());
+            if let Err(err) = expression.evaluate() {
+                assert!(matches!(err.kind, Overflow | DivisionByZero | NotInRing { .. }), "{:?}", err);
+            }
+        }
+    }
+
+    #[test]
+    fn random_expression_large() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let expression: ExpressionComponent<IntRing> =
+            random_expression(&mut rng, 200_000, |rng| IntRingElement::new(rng.gen_range(1..100)));
+
+        assert_eq!(200_000, expression.node_count());
+    }
+}
@@ -2,12 +2,42 @@ use std::fmt::{Formatter, Display};
 use core::fmt;
 use std::{result, error};
 use std::hash::Hash;
+use std::cmp::Ordering;
+use crate::expression::Operator;
 
+pub mod bigint;
+pub mod complexrational;
 pub mod intring;
+pub mod logic;
+pub mod modring;
+pub mod rational;
+pub mod uint;
+pub mod zeroring;
 
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub struct RingError {
-    pub message: String
+    pub message: String,
+    pub kind: RingErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum RingErrorKind {
+    Unspecified,
+    /// The result is outside the range of elements that can be represented.
+    Overflow,
+    DivisionByZero,
+    /// The result is not an element of the ring, e.g. `5 / 2` in the integers. May suggest a
+    /// ring in which the result exists, by the name used for `--ring` in the binaries. For
+    /// inexact division the quotient and remainder may be given.
+    NotInRing { suggested_ring: Option<&'static str>, remainder: Option<Box<DivisionRemainder>> },
+}
+
+/// Quotient and remainder of a division that is not exact, formatted as ring elements, such that
+/// `dividend = quotient * divisor + remainder`. E.g. `5 / 2` is `2` remainder `1`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct DivisionRemainder {
+    pub quotient: String,
+    pub remainder: String,
 }
 
 impl fmt::Display for RingError {
@@ -25,13 +55,128 @@ pub type RingResult<T> = result::Result<T, RingError>;
 pub trait RingElement : Display + PartialEq + Eq + Hash + Clone {
 }
 
+/// Ring whose elements are totally ordered, such that [Ring::compare] never fails and agrees
+/// with [Ord]. Comparisons evaluate to [Ring::one] if true and [Ring::zero] if false.
+pub trait OrderedRing: Ring where Self::RingElementType: Ord {
+}
+
+/// Symbols used for the operators when printing expressions in a ring.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct OperatorSymbols {
+    pub addition: &'static str,
+    pub subtraction: &'static str,
+    pub multiplication: &'static str,
+    pub division: &'static str,
+    pub modulo: &'static str,
+    pub power: &'static str,
+    pub less_than: &'static str,
+    pub greater_than: &'static str,
+    pub equal: &'static str,
+}
+
+impl OperatorSymbols {
+    pub const DEFAULT: OperatorSymbols = OperatorSymbols {
+        addition: "+",
+        subtraction: "-",
+        multiplication: "*",
+        division: "/",
+        modulo: "mod",
+        power: "^",
+        less_than: "<",
+        greater_than: ">",
+        equal: "==",
+    };
+
+    /// Symbol for `operator`.
+    pub fn symbol(&self, operator: Operator) -> &'static str {
+        match operator {
+            Operator::Addition => self.addition,
+            Operator::Subtraction => self.subtraction,
+            Operator::Multiplication => self.multiplication,
+            Operator::Division => self.division,
+            Operator::Modulo => self.modulo,
+            Operator::Power => self.power,
+            Operator::LessThan => self.less_than,
+            Operator::GreaterThan => self.greater_than,
+            Operator::Equal => self.equal,
+        }
+    }
+}
+
+/// Function on the elements of a ring that can be called in expressions, e.g. `gcd(12, 18)`, see
+/// [Ring::function]. `apply` is only called with `arity` arguments.
+pub struct RingFunction<R: Ring> {
+    pub arity: usize,
+    pub apply: fn(&[R::RingElementType]) -> RingResult<R::RingElementType>,
+}
+
+impl<R: Ring> RingFunction<R> {
+    /// The function `name` of the ring `R`. Fails if there is no such function or it does not
+    /// take `arguments` arguments.
+    pub fn resolve(name: &str, arguments: usize) -> RingResult<RingFunction<R>> {
+        let function = R::function(name).ok_or_else(|| RingError {
+            message: format!("Unknown function '{}'", name),
+            kind: RingErrorKind::Unspecified,
+        })?;
+        function.check_arity(name, arguments)?;
+        Ok(function)
+    }
+
+    /// Fail if the function, called `name`, does not take `arguments` arguments.
+    pub fn check_arity(&self, name: &str, arguments: usize) -> RingResult<()> {
+        if self.arity != arguments {
+            return Err(RingError {
+                message: format!("Function '{}' takes {} argument{}, not {}",
+                                 name, self.arity, if self.arity == 1 { "" } else { "s" }, arguments),
+                kind: RingErrorKind::Unspecified,
+            });
+        }
+        Ok(())
+    }
+}
+
 /// Represents ring or class of rings with division. Arithmetic operations in the ring are allowed to fail.
 pub trait Ring {
     type RingElementType : RingElement;
 
+    /// Symbols used for the operators when printing expressions in the ring.
+    const OPERATOR_SYMBOLS: OperatorSymbols = OperatorSymbols::DEFAULT;
+
+    /// The additive identity.
+    fn zero() -> Self::RingElementType;
+
+    /// The multiplicative identity.
+    fn one() -> Self::RingElementType;
+
+    /// Parse a literal ring element, e.g. `42` in the integers.
+    fn parse_element(str: &str) -> RingResult<Self::RingElementType>;
+
     fn add(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType>;
     fn sub(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType>;
     fn mul(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType>;
     fn div(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType>;
+    fn rem(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType>;
+
+    /// Additive inverse. Defaults to subtracting the element from zero.
+    fn neg(elm: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Self::sub(&Self::zero(), elm)
+    }
+
+    /// `base` raised to the power `exp`. Defaults to failing, for rings without a meaningful
+    /// notion of exponentiation by their own elements.
+    fn pow(_base: &Self::RingElementType, _exp: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Err(RingError { message: "Exponentiation not supported".to_string(), kind: RingErrorKind::Unspecified })
+    }
+
+    /// The function called `name` in expressions in the ring, if any. Defaults to no functions.
+    fn function(_name: &str) -> Option<RingFunction<Self>> where Self: Sized {
+        None
+    }
+
+    /// Order of `elm1` relative to `elm2`, for the comparisons `<` and `>`. Defaults to failing,
+    /// for rings whose elements are not ordered, see [OrderedRing].
+    fn compare(_elm1: &Self::RingElementType, _elm2: &Self::RingElementType) -> RingResult<Ordering> {
+        Err(RingError { message: "Comparison not supported".to_string(), kind: RingErrorKind::Unspecified })
+    }
 
 }
\ No newline at end of file
@@ -0,0 +1,451 @@
+use crate::expression::ring::{Ring, RingResult, RingElement, RingError, RingErrorKind, DivisionRemainder, OrderedRing};
+use crate::expression::parser::ElementTokenRing;
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+/// Base of the limbs of the magnitude of a [BigIntRingElement].
+const BASE: u64 = 1_000_000_000;
+
+/// Number of decimal digits in a limb.
+const BASE_DIGITS: usize = 9;
+
+/// Maximum number of limbs in the result of a multiplication, such that e.g. `10 ^ 10 ^ 10` fails
+/// instead of exhausting memory.
+const MAX_LIMBS: usize = 4096;
+
+/// Integer of arbitrary size, represented by its sign and the limbs of its magnitude in base 10^9,
+/// least significant first and without leading zero limbs. Zero has no limbs and is nonnegative.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct BigIntRingElement {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl Display for BigIntRingElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+        match self.magnitude.split_last() {
+            None => f.write_str("0")?,
+            Some((most_significant, rest)) => {
+                write!(f, "{}", most_significant)?;
+                for limb in rest.iter().rev() {
+                    write!(f, "{:09}", limb)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<i64> for BigIntRingElement {
+    fn from(value: i64) -> Self {
+        BigIntRingElement::new(value)
+    }
+}
+
+impl RingElement for BigIntRingElement {
+
+}
+
+impl PartialOrd for BigIntRingElement {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigIntRingElement {
+    /// Compares the signs, and then the magnitudes, which order negative elements in reverse.
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitudes(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl BigIntRingElement {
+    pub fn new(value: i64) -> BigIntRingElement {
+        let mut magnitude = Vec::new();
+        let mut remaining = value.unsigned_abs();
+        while remaining > 0 {
+            magnitude.push((remaining % BASE) as u32);
+            remaining /= BASE;
+        }
+        BigIntRingElement { negative: value < 0, magnitude }
+    }
+
+    /// Whether the element is zero.
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    fn from_parts(negative: bool, magnitude: Vec<u32>) -> BigIntRingElement {
+        let magnitude = trimmed(magnitude);
+        BigIntRingElement { negative: negative && !magnitude.is_empty(), magnitude }
+    }
+
+    fn negated(&self) -> BigIntRingElement {
+        BigIntRingElement::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+/// Integers of arbitrary size. Operations are exact and never overflow, except that products with
+/// more than about 36000 decimal digits fail. Division must be exact and modulo is euclidean,
+/// as in [IntRing](crate::expression::ring::intring::IntRing). Expressions with literals beyond
+/// the range of `i64` are parsed with
+/// [parse_big_int_ring_expression](crate::expression::parser::parse_big_int_ring_expression).
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct BigIntRing {
+}
+
+impl Ring for BigIntRing {
+    type RingElementType = BigIntRingElement;
+
+    fn zero() -> Self::RingElementType {
+        BigIntRingElement::new(0)
+    }
+
+    fn one() -> Self::RingElementType {
+        BigIntRingElement::new(1)
+    }
+
+    fn parse_element(str: &str) -> RingResult<Self::RingElementType> {
+        let (negative, digits) = match str.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, str),
+        };
+        if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+            return Err(RingError { message: format!("Invalid integer '{}'", str), kind: RingErrorKind::Unspecified });
+        }
+        let magnitude = digits.as_bytes().rchunks(BASE_DIGITS)
+            .map(|chunk| chunk.iter().fold(0, |limb, c| limb * 10 + (c - b'0') as u32))
+            .collect();
+        Ok(BigIntRingElement::from_parts(negative, magnitude))
+    }
+
+    fn add(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        if elm1.negative == elm2.negative {
+            return Ok(BigIntRingElement::from_parts(elm1.negative, add_magnitudes(&elm1.magnitude, &elm2.magnitude)));
+        }
+        // the sign of the sum is the sign of the operand with the larger magnitude
+        Ok(match compare_magnitudes(&elm1.magnitude, &elm2.magnitude) {
+            Ordering::Less => BigIntRingElement::from_parts(elm2.negative, sub_magnitudes(&elm2.magnitude, &elm1.magnitude)),
+            _ => BigIntRingElement::from_parts(elm1.negative, sub_magnitudes(&elm1.magnitude, &elm2.magnitude)),
+        })
+    }
+
+    fn sub(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        BigIntRing::add(elm1, &elm2.negated())
+    }
+
+    fn mul(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(BigIntRingElement::from_parts(elm1.negative != elm2.negative, mul_magnitudes(&elm1.magnitude, &elm2.magnitude)?))
+    }
+
+    fn div(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        if elm2.is_zero() {
+            return Err(RingError { message: "Division by zero".to_string(), kind: RingErrorKind::DivisionByZero });
+        }
+        let (quotient, remainder) = BigIntRing::div_rem_euclid(elm1, elm2);
+        if !remainder.is_zero() {
+            let remainder = DivisionRemainder {
+                quotient: quotient.to_string(),
+                remainder: remainder.to_string(),
+            };
+            return Err(RingError {
+                message: "Result not in ring".to_string(),
+                kind: RingErrorKind::NotInRing { suggested_ring: None, remainder: Some(Box::new(remainder)) },
+            });
+        }
+        Ok(quotient)
+    }
+
+    /// Euclidean remainder, which is never negative: `-1 mod 3 = 2`.
+    fn rem(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        if elm2.is_zero() {
+            return Err(RingError { message: "Modulo by zero".to_string(), kind: RingErrorKind::DivisionByZero });
+        }
+        Ok(BigIntRing::div_rem_euclid(elm1, elm2).1)
+    }
+
+    fn neg(elm: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(elm.negated())
+    }
+
+    /// Fails for negative exponents, since e.g. `2 ^ -1` is not an integer, and if the result is
+    /// too large.
+    fn pow(base: &Self::RingElementType, exp: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        if exp.negative {
+            return Err(RingError {
+                message: "Negative exponent".to_string(),
+                kind: RingErrorKind::NotInRing { suggested_ring: None, remainder: None },
+            });
+        }
+        let mut result = BigIntRing::one();
+        let mut square = base.clone();
+        let mut exp = exp.magnitude.clone();
+        while !exp.is_empty() {
+            if exp[0] % 2 == 1 {
+                result = BigIntRing::mul(&result, &square)?;
+            }
+            exp = div_magnitude_by_limb(&exp, 2).0;
+            if !exp.is_empty() {
+                square = BigIntRing::mul(&square, &square)?;
+            }
+        }
+        Ok(result)
+    }
+
+    fn compare(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Ordering> {
+        Ok(elm1.cmp(elm2))
+    }
+}
+
+impl OrderedRing for BigIntRing {
+}
+
+impl ElementTokenRing for BigIntRing {
+    fn element_from_token(value: i64) -> RingResult<Self::RingElementType> {
+        Ok(BigIntRingElement::new(value))
+    }
+}
+
+impl BigIntRing {
+    /// Euclidean quotient and remainder of `dividend` by the nonzero `divisor`.
+    fn div_rem_euclid(dividend: &BigIntRingElement, divisor: &BigIntRingElement) -> (BigIntRingElement, BigIntRingElement) {
+        let (quotient, remainder) = div_rem_magnitudes(&dividend.magnitude, &divisor.magnitude);
+        let quotient = BigIntRingElement::from_parts(dividend.negative != divisor.negative, quotient);
+        let remainder = BigIntRingElement::from_parts(dividend.negative, remainder);
+        if !remainder.negative {
+            return (quotient, remainder);
+        }
+        // truncated division rounds towards zero, move the negative remainder into 0..|divisor|
+        let abs_divisor = BigIntRingElement::from_parts(false, divisor.magnitude.clone());
+        let one = BigIntRing::one();
+        let quotient = if divisor.negative { BigIntRing::add(&quotient, &one) } else { BigIntRing::sub(&quotient, &one) };
+        (quotient.expect("addition cannot fail"), BigIntRing::add(&remainder, &abs_divisor).expect("addition cannot fail"))
+    }
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let limb = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push((limb % BASE) as u32);
+        carry = limb / BASE;
+    }
+    if carry > 0 {
+        sum.push(carry as u32);
+    }
+    sum
+}
+
+/// Difference of the magnitudes `a` and `b`, where `a` is not less than `b`.
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, limb) in a.iter().enumerate() {
+        let subtrahend = *b.get(i).unwrap_or(&0) as i64 + borrow;
+        let mut limb = *limb as i64 - subtrahend;
+        borrow = 0;
+        if limb < 0 {
+            limb += BASE as i64;
+            borrow = 1;
+        }
+        difference.push(limb as u32);
+    }
+    difference
+}
+
+fn mul_magnitudes(a: &[u32], b: &[u32]) -> RingResult<Vec<u32>> {
+    if a.is_empty() || b.is_empty() {
+        return Ok(Vec::new());
+    }
+    if a.len() + b.len() > MAX_LIMBS + 1 {
+        return Err(RingError { message: "Result too large".to_string(), kind: RingErrorKind::Overflow });
+    }
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, y) in b.iter().enumerate() {
+            // at most (10^9 - 1)^2 + 2 * (10^9 - 1), which fits in u64
+            let limb = product[i + j] as u64 + *x as u64 * *y as u64 + carry;
+            product[i + j] = (limb % BASE) as u32;
+            carry = limb / BASE;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    Ok(product)
+}
+
+fn mul_magnitude_by_limb(a: &[u32], limb: u32) -> Vec<u32> {
+    let mut product = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0;
+    for x in a {
+        let current = *x as u64 * limb as u64 + carry;
+        product.push((current % BASE) as u32);
+        carry = current / BASE;
+    }
+    product.push(carry as u32);
+    trimmed(product)
+}
+
+/// Quotient and remainder of the magnitude `a` divided by the nonzero `limb`.
+fn div_magnitude_by_limb(a: &[u32], limb: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for i in (0..a.len()).rev() {
+        let current = remainder * BASE + a[i] as u64;
+        quotient[i] = (current / limb as u64) as u32;
+        remainder = current % limb as u64;
+    }
+    (trimmed(quotient), remainder as u32)
+}
+
+/// Truncated quotient and remainder of the magnitudes `a` divided by the nonzero `b`, by long
+/// division with one limb of the quotient at a time.
+fn div_rem_magnitudes(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if let [limb] = b {
+        let (quotient, remainder) = div_magnitude_by_limb(a, *limb);
+        return (quotient, if remainder == 0 { Vec::new() } else { vec![remainder] });
+    }
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for i in (0..a.len()).rev() {
+        // shift the next limb of the dividend into the remainder
+        remainder.insert(0, a[i]);
+        remainder = trimmed(remainder);
+        // the largest limb q with q * b <= remainder, found by bisection
+        let (mut low, mut high) = (0, BASE as u32 - 1);
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if compare_magnitudes(&mul_magnitude_by_limb(b, mid), &remainder) == Ordering::Greater {
+                high = mid - 1;
+            } else {
+                low = mid;
+            }
+        }
+        if low > 0 {
+            remainder = trimmed(sub_magnitudes(&remainder, &mul_magnitude_by_limb(b, low)));
+        }
+        quotient[i] = low;
+    }
+    (trimmed(quotient), remainder)
+}
+
+fn trimmed(mut magnitude: Vec<u32>) -> Vec<u32> {
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+    magnitude
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::ring::bigint::{BigIntRingElement, BigIntRing};
+    use crate::expression::ring::{Ring, RingError, DivisionRemainder};
+    use crate::expression::ring::RingErrorKind::{Overflow, NotInRing, DivisionByZero, Unspecified};
+    use crate::expression::parser::{parse_big_int_ring_expression, parse_int_ring_expression};
+
+    fn big(str: &str) -> BigIntRingElement {
+        BigIntRing::parse_element(str).expect("ok")
+    }
+
+    #[test]
+    fn compare() {
+        assert!(big("-123456789012345678901234567890") < big("-7"));
+        assert!(big("-7") < big("0"));
+        assert!(big("0") < big("9223372036854775808"));
+        assert!(big("9223372036854775808") < big("123456789012345678901234567890"));
+        assert_eq!(Ok(big("1")), parse_big_int_ring_expression("3 > 2").expect("ok").evaluate());
+        assert_eq!(Ok(big("0")), parse_big_int_ring_expression("-100000000000000000000 > 2").expect("ok").evaluate());
+        assert_eq!(Ok(big("1")), parse_big_int_ring_expression("10 ^ 20 == 100000000000000000000").expect("ok").evaluate());
+    }
+
+    #[test]
+    fn parse_and_display() {
+        for str in ["0", "7", "-7", "1000000000", "-123456789012345678901234567890", "9223372036854775808"] {
+            assert_eq!(str, big(str).to_string());
+        }
+        assert_eq!("0", big("-000").to_string());
+        assert_eq!(BigIntRingElement::new(i64::MIN), big("-9223372036854775808"));
+        assert_eq!(Err(RingError{message: "Invalid integer '12a'".to_string(), kind: Unspecified}), BigIntRing::parse_element("12a"));
+        assert_eq!(Err(RingError{message: "Invalid integer '-'".to_string(), kind: Unspecified}), BigIntRing::parse_element("-"));
+    }
+
+    #[test]
+    fn add_and_sub() {
+        assert_eq!(Ok(big("18446744073709551616")), BigIntRing::add(&big("9223372036854775808"), &big("9223372036854775808")));
+        assert_eq!(Ok(big("-1")), BigIntRing::add(&big("999999999999999999"), &big("-1000000000000000000")));
+        assert_eq!(Ok(big("0")), BigIntRing::add(&big("-1000000000"), &big("1000000000")));
+        assert_eq!(Ok(big("-18446744073709551617")), BigIntRing::sub(&big("-9223372036854775809"), &big("9223372036854775808")));
+        assert_eq!(Ok(big("1")), BigIntRing::sub(&big("1000000000000000000"), &big("999999999999999999")));
+    }
+
+    #[test]
+    fn mul() {
+        assert_eq!(Ok(big("85070591730234615847396907784232501249")),
+                   BigIntRing::mul(&big("9223372036854775807"), &big("9223372036854775807")));
+        assert_eq!(Ok(big("-1000000000000000000000000000")), BigIntRing::mul(&big("-1000000000"), &big("1000000000000000000")));
+        assert_eq!(Ok(big("0")), BigIntRing::mul(&big("-5"), &big("0")));
+    }
+
+    #[test]
+    fn div_and_rem() {
+        let product = big("85070591730234615847396907784232501249");
+
+        assert_eq!(Ok(big("9223372036854775807")), BigIntRing::div(&product, &big("9223372036854775807")));
+        assert_eq!(Ok(big("-9223372036854775807")), BigIntRing::div(&product, &big("-9223372036854775807")));
+        assert_eq!(Ok(big("1")), BigIntRing::rem(&big("-1000000000000000000000"), &big("7")));
+        assert_eq!(Ok(big("1")), BigIntRing::rem(&big("-1000000000000000000000"), &big("-7")));
+        assert_eq!(Ok(big("999999999999999999")), BigIntRing::rem(&big("-1"), &big("1000000000000000000")));
+        assert_eq!(Err(RingError{message: "Result not in ring".to_string(), kind: NotInRing { suggested_ring: None, remainder: Some(Box::new(DivisionRemainder { quotient: "-142857142857142857143".to_string(), remainder: "1".to_string() })) }}),
+                   BigIntRing::div(&big("-1000000000000000000000"), &big("7")));
+        assert_eq!(Err(RingError{message: "Division by zero".to_string(), kind: DivisionByZero}), BigIntRing::div(&big("1"), &big("0")));
+        assert_eq!(Err(RingError{message: "Modulo by zero".to_string(), kind: DivisionByZero}), BigIntRing::rem(&big("1"), &big("0")));
+    }
+
+    #[test]
+    fn same_as_int_ring() {
+        for str in ["7 / -7 + 3 * -4 mod 5", "-17 mod 5 - 2 ^ 62", "(1 - 3000000000) * 3000000000 / 3", "-(-8 ^ 3) mod -3"] {
+            let expected = parse_int_ring_expression(str).expect("ok").evaluate().map(|value| value.value().into());
+
+            assert_eq!(expected, parse_big_int_ring_expression(str).expect("ok").evaluate(), "{}", str);
+        }
+    }
+
+    #[test]
+    fn evaluate_large_power() {
+        let expression = parse_big_int_ring_expression("100 ^ 100").expect("ok");
+
+        assert_eq!(Ok(big(&format!("1{}", "0".repeat(200)))), expression.evaluate());
+        assert_eq!(Ok(big("-2535301200456458802993406410752")), parse_big_int_ring_expression("(-2) ^ 101").expect("ok").evaluate());
+        assert_eq!(Overflow, parse_big_int_ring_expression("10 ^ 10 ^ 10").expect("ok").evaluate().unwrap_err().kind);
+    }
+
+    #[test]
+    fn evaluate_large_sum() {
+        let str = vec!["9223372036854775807"; 1000].join(" + ");
+
+        let expression = parse_big_int_ring_expression(&str).expect("ok");
+
+        assert_eq!(Ok(big("9223372036854775807000")), expression.evaluate());
+    }
+
+    #[test]
+    fn parse_expression_big_literals() {
+        let expression = parse_big_int_ring_expression("123456789012345678901234567890 - -123456789012345678901234567890").expect("ok");
+
+        assert_eq!(Ok(big("246913578024691357802469135780")), expression.evaluate());
+        assert_eq!(Ok(big("-1")), parse_big_int_ring_expression("-9223372036854775809 + 9223372036854775808").expect("ok").evaluate());
+    }
+}
@@ -0,0 +1,227 @@
+use crate::expression::ring::{Ring, RingResult, RingElement, RingError, RingErrorKind};
+use crate::expression::ring::rational::{RationalRing, RationalRingElement};
+use crate::expression::parser::ElementTokenRing;
+use std::fmt::{Display, Formatter};
+
+/// Complex number `re + im·i` with rational real and imaginary parts.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ComplexRationalRingElement {
+    re: RationalRingElement,
+    im: RationalRingElement,
+}
+
+impl Display for ComplexRationalRingElement {
+    /// Written as e.g. `1/2 - 3·i`, leaving out a zero real or imaginary part: `1/2`, `-i`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.im == RationalRing::zero() {
+            return write!(f, "{}", self.re);
+        }
+        let im = self.im.to_string();
+        let (sign, magnitude) = match im.strip_prefix('-') {
+            Some(magnitude) => ("-", magnitude),
+            None => ("+", im.as_str()),
+        };
+        if self.re != RationalRing::zero() {
+            write!(f, "{} {} ", self.re, sign)?;
+        } else if sign == "-" {
+            f.write_str("-")?;
+        }
+        if magnitude != "1" {
+            write!(f, "{}·", magnitude)?;
+        }
+        f.write_str("i")?;
+        Ok(())
+    }
+}
+
+impl From<ComplexRationalRingElement> for String {
+    fn from(element: ComplexRationalRingElement) -> Self {
+        element.to_string()
+    }
+}
+
+impl RingElement for ComplexRationalRingElement {
+
+}
+
+impl ComplexRationalRingElement {
+    pub fn new(re: RationalRingElement, im: RationalRingElement) -> ComplexRationalRingElement {
+        ComplexRationalRingElement {
+            re,
+            im,
+        }
+    }
+
+    /// The rational number `re`.
+    pub fn from_rational(re: RationalRingElement) -> ComplexRationalRingElement {
+        ComplexRationalRingElement::new(re, RationalRing::zero())
+    }
+
+    /// The imaginary unit.
+    pub fn i() -> ComplexRationalRingElement {
+        ComplexRationalRingElement::new(RationalRing::zero(), RationalRing::one())
+    }
+
+    pub fn re(&self) -> &RationalRingElement {
+        &self.re
+    }
+
+    pub fn im(&self) -> &RationalRingElement {
+        &self.im
+    }
+}
+
+/// Field of complex numbers with rational real and imaginary parts, computed exactly with
+/// [RationalRing] arithmetic. Division only fails when dividing by zero, or if a part overflows.
+/// Literals are rational, the imaginary unit has no literal and may be bound as a variable, e.g.
+/// `i` with [EvalContext](crate::expression::EvalContext). Complex numbers are not ordered, so
+/// modulo and comparisons are rejected.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ComplexRationalRing {
+}
+
+impl Ring for ComplexRationalRing {
+    type RingElementType = ComplexRationalRingElement;
+
+    fn zero() -> Self::RingElementType {
+        ComplexRationalRingElement::from_rational(RationalRing::zero())
+    }
+
+    fn one() -> Self::RingElementType {
+        ComplexRationalRingElement::from_rational(RationalRing::one())
+    }
+
+    /// Parse a rational number as for [RationalRing], e.g. `2/4`.
+    fn parse_element(str: &str) -> RingResult<Self::RingElementType> {
+        Ok(ComplexRationalRingElement::from_rational(RationalRing::parse_element(str)?))
+    }
+
+    fn add(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(ComplexRationalRingElement::new(RationalRing::add(&elm1.re, &elm2.re)?, RationalRing::add(&elm1.im, &elm2.im)?))
+    }
+
+    fn sub(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(ComplexRationalRingElement::new(RationalRing::sub(&elm1.re, &elm2.re)?, RationalRing::sub(&elm1.im, &elm2.im)?))
+    }
+
+    /// `(a + b·i) * (c + d·i) = (ac - bd) + (ad + bc)·i`
+    fn mul(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        let re = RationalRing::sub(&RationalRing::mul(&elm1.re, &elm2.re)?, &RationalRing::mul(&elm1.im, &elm2.im)?)?;
+        let im = RationalRing::add(&RationalRing::mul(&elm1.re, &elm2.im)?, &RationalRing::mul(&elm1.im, &elm2.re)?)?;
+        Ok(ComplexRationalRingElement::new(re, im))
+    }
+
+    fn neg(elm: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(ComplexRationalRingElement::new(RationalRing::neg(&elm.re)?, RationalRing::neg(&elm.im)?))
+    }
+
+    /// Multiplies by the conjugate of the divisor: `(a + b·i) / (c + d·i) = ((ac + bd) + (bc - ad)·i) / (c² + d²)`,
+    /// where `c² + d²` is zero only for the divisor zero.
+    fn div(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        let norm = RationalRing::add(&RationalRing::mul(&elm2.re, &elm2.re)?, &RationalRing::mul(&elm2.im, &elm2.im)?)?;
+        let re = RationalRing::add(&RationalRing::mul(&elm1.re, &elm2.re)?, &RationalRing::mul(&elm1.im, &elm2.im)?)?;
+        let im = RationalRing::sub(&RationalRing::mul(&elm1.im, &elm2.re)?, &RationalRing::mul(&elm1.re, &elm2.im)?)?;
+        Ok(ComplexRationalRingElement::new(RationalRing::div(&re, &norm)?, RationalRing::div(&im, &norm)?))
+    }
+
+    fn rem(_elm1: &Self::RingElementType, _elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Err(RingError { message: "Modulo not supported".to_string(), kind: RingErrorKind::Unspecified })
+    }
+
+    /// Fails unless the exponent is an integer. Computed by squaring, and negative exponents give
+    /// powers of the reciprocal: `i ^ -1 = -i`.
+    fn pow(base: &Self::RingElementType, exp: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        if exp.im != RationalRing::zero() || exp.re.denominator() != 1 {
+            return Err(RingError {
+                message: "Exponent not an integer".to_string(),
+                kind: RingErrorKind::NotInRing { suggested_ring: None, remainder: None },
+            });
+        }
+        let mut magnitude = exp.re.numerator().unsigned_abs();
+        let (mut square, mut result) = (base.clone(), Self::one());
+        while magnitude > 0 {
+            if magnitude & 1 == 1 {
+                result = Self::mul(&result, &square)?;
+            }
+            magnitude >>= 1;
+            if magnitude > 0 {
+                square = Self::mul(&square, &square)?;
+            }
+        }
+        if exp.re.numerator() < 0 {
+            Self::div(&Self::one(), &result)
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+impl ElementTokenRing for ComplexRationalRing {
+    fn element_from_token(value: i64) -> RingResult<Self::RingElementType> {
+        Ok(ComplexRationalRingElement::from_rational(RationalRingElement::from_int(value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::ring::complexrational::{ComplexRationalRingElement, ComplexRationalRing};
+    use crate::expression::ring::rational::RationalRingElement;
+    use crate::expression::ring::{Ring, RingError};
+    use crate::expression::ring::RingErrorKind::{DivisionByZero, Unspecified};
+    use crate::expression::parser::parse_expression;
+    use crate::expression::{ExpressionComponent, EvalContext, EvaluateExpressionResult};
+
+    fn complex(re: (i64, i64), im: (i64, i64)) -> ComplexRationalRingElement {
+        ComplexRationalRingElement::new(RationalRingElement::new(re.0, re.1).expect("ok"), RationalRingElement::new(im.0, im.1).expect("ok"))
+    }
+
+    fn evaluate(str: &str) -> EvaluateExpressionResult<ComplexRationalRingElement> {
+        let expression: ExpressionComponent<ComplexRationalRing> = parse_expression(str).expect("ok");
+        expression.evaluate_in(&EvalContext::new().with_variable("i", ComplexRationalRingElement::i()))
+    }
+
+    #[test]
+    fn mul_conjugates() {
+        assert_eq!(Ok(complex((5, 4), (0, 1))), evaluate("(1/2 + i) * (1/2 - i)"));
+        assert_eq!(Ok(complex((-1, 1), (0, 1))), evaluate("i * i"));
+    }
+
+    #[test]
+    fn div() {
+        assert_eq!(Ok(complex((1, 10), (7, 10))), evaluate("(1 + 2 * i) / (3 - i)"));
+        assert_eq!(Ok(complex((0, 1), (-1, 3))), evaluate("1 / (3 * i)"));
+        assert_eq!(Ok(complex((7, 2), (0, 1))), evaluate("7 / 2"));
+    }
+
+    #[test]
+    fn div_zero() {
+        let res = ComplexRationalRing::div(&ComplexRationalRingElement::i(), &ComplexRationalRing::zero());
+
+        assert_eq!(Err(RingError{message: "Division by zero".to_string(), kind: DivisionByZero}), res);
+    }
+
+    #[test]
+    fn pow() {
+        assert_eq!(Ok(complex((-1, 1), (0, 1))), evaluate("i ^ 2"));
+        assert_eq!(Ok(complex((0, 1), (-1, 1))), evaluate("i ^ -1"));
+        assert_eq!(Ok(complex((0, 1), (-1, 2))), evaluate("(1 + i) ^ -2"));
+        assert_eq!(Ok(ComplexRationalRing::one()), evaluate("i ^ 4000000000000"));
+        assert!(evaluate("i ^ (1 / 2)").is_err());
+        assert!(evaluate("2 ^ i").is_err());
+    }
+
+    #[test]
+    fn rem_not_supported() {
+        assert_eq!(Err(RingError{message: "Modulo not supported".to_string(), kind: Unspecified}),
+                   ComplexRationalRing::rem(&ComplexRationalRing::one(), &ComplexRationalRing::one()));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("1/2 - 3·i", complex((1, 2), (-3, 1)).to_string());
+        assert_eq!("2 + i", complex((2, 1), (1, 1)).to_string());
+        assert_eq!("-i", complex((0, 1), (-1, 1)).to_string());
+        assert_eq!("1/3·i", complex((0, 1), (1, 3)).to_string());
+        assert_eq!("-5/4", complex((-5, 4), (0, 1)).to_string());
+    }
+}
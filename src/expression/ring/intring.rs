@@ -1,81 +1,792 @@
-use crate::expression::ring::{Ring, RingResult, RingElement, RingError};
-use std::fmt::{Display, Formatter};
-use crate::expression::ExpressionComponent;
+use crate::expression::ring::{Ring, RingResult, RingElement, RingError, RingErrorKind, DivisionRemainder, RingFunction, OrderedRing};
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter, Debug};
+use std::num::{IntErrorKind, ParseIntError};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use crate::expression::{ExpressionComponent, EvaluateExpressionError, Operator, SourcePosition};
+use crate::expression::parser::ElementTokenRing;
 
-#[derive(Debug, PartialEq, Eq, Clone, Hash)]
-pub struct IntRingElement {
-    value: i64
+/// Primitive signed integer type with checked arithmetic, used as the elements of [GenericIntRing].
+pub trait CheckedInt: Copy + Debug + Display + Eq + Ord + Hash + FromStr<Err=ParseIntError> + TryFrom<i64> + Into<i128> {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn checked_div_euclid(self, rhs: Self) -> Option<Self>;
+    fn checked_rem_euclid(self, rhs: Self) -> Option<Self>;
 }
 
-impl Display for IntRingElement {
+macro_rules! checked_int_impl {
+    ($($t:ty)*) => ($(
+        impl CheckedInt for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> { <$t>::checked_add(self, rhs) }
+            fn checked_sub(self, rhs: Self) -> Option<Self> { <$t>::checked_sub(self, rhs) }
+            fn checked_mul(self, rhs: Self) -> Option<Self> { <$t>::checked_mul(self, rhs) }
+            fn checked_neg(self) -> Option<Self> { <$t>::checked_neg(self) }
+            fn checked_div(self, rhs: Self) -> Option<Self> { <$t>::checked_div(self, rhs) }
+            fn checked_div_euclid(self, rhs: Self) -> Option<Self> { <$t>::checked_div_euclid(self, rhs) }
+            fn checked_rem_euclid(self, rhs: Self) -> Option<Self> { <$t>::checked_rem_euclid(self, rhs) }
+        }
+
+        impl From<GenericIntRingElement<$t>> for $t {
+            fn from(element: GenericIntRingElement<$t>) -> Self {
+                element.value
+            }
+        }
+    )*)
+}
+
+checked_int_impl! { i32 i64 i128 }
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash)]
+pub struct GenericIntRingElement<T: CheckedInt> {
+    value: T
+}
+
+/// Element of [IntRing].
+pub type IntRingElement = GenericIntRingElement<i64>;
+
+impl<T: CheckedInt> Display for GenericIntRingElement<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.value)?;
         Ok(())
     }
 }
 
-impl RingElement for IntRingElement {
+impl<T: CheckedInt> From<GenericIntRingElement<T>> for String {
+    fn from(element: GenericIntRingElement<T>) -> Self {
+        element.to_string()
+    }
+}
+
+impl<T: CheckedInt> RingElement for GenericIntRingElement<T> {
 
 }
 
-impl IntRingElement {
-    pub fn new(value: i64) -> IntRingElement {
-        IntRingElement {
+impl<T: CheckedInt> GenericIntRingElement<T> {
+    pub fn new(value: T) -> GenericIntRingElement<T> {
+        GenericIntRingElement {
             value
         }
     }
+
+    pub fn value(&self) -> T {
+        self.value
+    }
 }
 
+/// Ring of integers in the range of the primitive integer type `T`, e.g. `i32`. Operations fail
+/// on overflow. Division is partial, it fails unless the result is an integer, and it is not
+/// associative: `(8 / 4) / 2 = 1` while `8 / (4 / 2) = 4`.
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
-pub struct IntRing {
+pub struct GenericIntRing<T: CheckedInt> {
+    _int: PhantomData<T>,
 }
 
-impl Ring for IntRing {
-    type RingElementType = IntRingElement;
+/// Ring of integers in the range of `i64`, see [GenericIntRing].
+pub type IntRing = GenericIntRing<i64>;
+
+impl<T: CheckedInt> Ring for GenericIntRing<T> {
+    type RingElementType = GenericIntRingElement<T>;
+
+    fn zero() -> Self::RingElementType {
+        GenericIntRingElement::new(T::ZERO)
+    }
+
+    fn one() -> Self::RingElementType {
+        GenericIntRingElement::new(T::ONE)
+    }
+
+    /// Parse decimal integer with optional sign.
+    fn parse_element(str: &str) -> RingResult<Self::RingElementType> {
+        str.parse().map(GenericIntRingElement::new).map_err(|err: ParseIntError| match err.kind() {
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+                RingError { message: "Decimal number too big".to_string(), kind: RingErrorKind::Overflow }
+            }
+            _ => RingError { message: format!("Invalid integer '{}'", str), kind: RingErrorKind::Unspecified },
+        })
+    }
 
     fn add(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
-        IntRing::ring_result(elm1.value.checked_add(elm2.value))
+        GenericIntRing::ring_result(elm1.value.checked_add(elm2.value))
     }
 
     fn sub(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
-        IntRing::ring_result(elm1.value.checked_sub(elm2.value))
+        GenericIntRing::ring_result(elm1.value.checked_sub(elm2.value))
     }
 
     fn mul(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
-        IntRing::ring_result(elm1.value.checked_mul(elm2.value))
+        GenericIntRing::ring_result(elm1.value.checked_mul(elm2.value))
+    }
+
+    fn neg(elm: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        GenericIntRing::ring_result(elm.value.checked_neg())
     }
 
+    /// Fails unless the division is exact. The error then carries the euclidean quotient and
+    /// remainder, consistent with [rem](GenericIntRing::rem): `-5 / 2` is `-3` remainder `1`.
     fn div(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
-        let rem = elm1.value.checked_rem(elm2.value);
-        if let Some(d ) = rem {
-            if d != 0 {
-                return Err(RingError { message: "Result not in ring".to_string() });
+        GenericIntRing::check_divisor(elm2)?;
+        let rem = elm1.value.checked_rem_euclid(elm2.value);
+        if let Some(d) = rem {
+            if d != T::ZERO {
+                // the euclidean quotient cannot overflow when the remainder is nonzero
+                let remainder = DivisionRemainder {
+                    quotient: elm1.value.checked_div_euclid(elm2.value).unwrap().to_string(),
+                    remainder: d.to_string(),
+                };
+                return Err(RingError {
+                    message: "Result not in ring".to_string(),
+                    kind: RingErrorKind::NotInRing { suggested_ring: Some("rational"), remainder: Some(Box::new(remainder)) },
+                });
+            }
+        }
+        GenericIntRing::ring_result(elm1.value.checked_div(elm2.value))
+    }
+
+    /// Euclidean remainder, which is never negative: `-1 mod 3 = 2`. Fails for modulo by zero,
+    /// but not for the minimum value modulo `-1`, which is `0` even though the quotient overflows.
+    fn rem(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        if elm2.value == T::ZERO {
+            return Err(RingError { message: "Modulo by zero".to_string(), kind: RingErrorKind::DivisionByZero });
+        }
+        // the remainder only fails for the minimum value modulo -1, which is zero
+        Ok(GenericIntRingElement::new(elm1.value.checked_rem_euclid(elm2.value).unwrap_or(T::ZERO)))
+    }
+
+    /// Fails for negative exponents, since e.g. `2 ^ -1` is not an integer. Computed by squaring,
+    /// and on overflow the error names the multiplication that overflowed, e.g. `3 ^ 40` fails
+    /// in `6561 * 1853020188851841`.
+    fn pow(base: &Self::RingElementType, exp: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        if exp.value < T::ZERO {
+            return Err(RingError {
+                message: "Negative exponent".to_string(),
+                kind: RingErrorKind::NotInRing { suggested_ring: Some("rational"), remainder: None },
+            });
+        }
+        let mut exp = exp.value.into();
+        let (mut square, mut result) = (base.clone(), Self::one());
+        loop {
+            if exp & 1 == 1 {
+                result = GenericIntRing::pow_mul(&result, &square)?;
             }
+            exp >>= 1;
+            if exp == 0 {
+                return Ok(result);
+            }
+            square = GenericIntRing::pow_mul(&square, &square)?;
+        }
+    }
+
+    /// `abs(x)`, the absolute value, and `gcd(x, y)`, the nonnegative greatest common divisor.
+    fn function(name: &str) -> Option<RingFunction<Self>> {
+        match name {
+            "abs" => Some(RingFunction { arity: 1, apply: |args| GenericIntRing::abs(&args[0]) }),
+            "gcd" => Some(RingFunction { arity: 2, apply: |args| GenericIntRing::gcd(&args[0], &args[1]) }),
+            _ => None,
         }
-        IntRing::ring_result(elm1.value.checked_div(elm2.value))
+    }
+
+    fn compare(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Ordering> {
+        Ok(elm1.cmp(elm2))
     }
 }
 
-impl IntRing {
-    fn ring_result(res: Option<i64>) -> Result<IntRingElement, RingError> {
+impl<T: CheckedInt> OrderedRing for GenericIntRing<T> {
+}
+
+impl<T: CheckedInt> ElementTokenRing for GenericIntRing<T> {
+    /// Literals outside the range of `T` fail with an overflow.
+    fn element_from_token(value: i64) -> RingResult<Self::RingElementType> {
+        T::try_from(value).map(GenericIntRingElement::new).map_err(|_| {
+            RingError { message: "Decimal number too big".to_string(), kind: RingErrorKind::Overflow }
+        })
+    }
+}
+
+impl<T: CheckedInt> GenericIntRing<T> {
+    fn ring_result(res: Option<T>) -> Result<GenericIntRingElement<T>, RingError> {
         match res {
-            Some(val) => Ok(IntRingElement::new(val)),
-            None => Err(RingError { message: "Overflow".to_string() }),
+            Some(val) => Ok(GenericIntRingElement::new(val)),
+            None => Err(RingError { message: "Overflow".to_string(), kind: RingErrorKind::Overflow }),
         }
     }
+
+    fn abs(elm: &GenericIntRingElement<T>) -> RingResult<GenericIntRingElement<T>> {
+        if elm.value < T::ZERO {
+            GenericIntRing::ring_result(elm.value.checked_neg())
+        } else {
+            Ok(elm.clone())
+        }
+    }
+
+    /// Fails only if the result is out of range, e.g. `gcd(i64::MIN, 0)`.
+    fn gcd(elm1: &GenericIntRingElement<T>, elm2: &GenericIntRingElement<T>) -> RingResult<GenericIntRingElement<T>> {
+        let (mut a, mut b) = (elm1.value, elm2.value);
+        while b != T::ZERO {
+            // the remainder only fails for the minimum value modulo -1, which is zero
+            (a, b) = (b, a.checked_rem_euclid(b).unwrap_or(T::ZERO));
+        }
+        GenericIntRing::abs(&GenericIntRingElement::new(a))
+    }
+
+    /// Multiplication in [pow](GenericIntRing::pow), failing with the operands in the message.
+    fn pow_mul(elm1: &GenericIntRingElement<T>, elm2: &GenericIntRingElement<T>) -> RingResult<GenericIntRingElement<T>> {
+        GenericIntRing::mul(elm1, elm2).map_err(|err| RingError {
+            message: format!("{} in multiplication {} * {}", err.message, elm1, elm2),
+            kind: err.kind,
+        })
+    }
+
+    fn check_divisor(divisor: &GenericIntRingElement<T>) -> RingResult<()> {
+        if divisor.value == T::ZERO {
+            Err(RingError { message: "Division by zero".to_string(), kind: RingErrorKind::DivisionByZero })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Ring of integers modulo `2^64`, represented in the range of `i64`. Addition, subtraction,
+/// multiplication, negation and exponentiation wrap around in two's complement, e.g.
+/// `i64::MAX + 1 = i64::MIN`, instead of failing like in [IntRing]. Division and modulo are as in
+/// [IntRing], except that `i64::MIN / -1` wraps to `i64::MIN`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct WrappingIntRing;
+
+impl Ring for WrappingIntRing {
+    type RingElementType = IntRingElement;
+
+    fn zero() -> Self::RingElementType {
+        IntRing::zero()
+    }
+
+    fn one() -> Self::RingElementType {
+        IntRing::one()
+    }
+
+    /// Parse decimal integer with optional sign. Literals outside the range of `i64` fail rather
+    /// than wrap.
+    fn parse_element(str: &str) -> RingResult<Self::RingElementType> {
+        IntRing::parse_element(str)
+    }
+
+    fn add(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(IntRingElement::new(elm1.value.wrapping_add(elm2.value)))
+    }
+
+    fn sub(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(IntRingElement::new(elm1.value.wrapping_sub(elm2.value)))
+    }
+
+    fn mul(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(IntRingElement::new(elm1.value.wrapping_mul(elm2.value)))
+    }
+
+    fn neg(elm: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(IntRingElement::new(elm.value.wrapping_neg()))
+    }
+
+    fn div(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        match IntRing::div(elm1, elm2) {
+            Err(err) if err.kind == RingErrorKind::Overflow => Ok(IntRingElement::new(elm1.value.wrapping_div(elm2.value))),
+            result => result,
+        }
+    }
+
+    fn rem(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        IntRing::rem(elm1, elm2)
+    }
+
+    /// Fails for negative exponents, like [IntRing].
+    fn pow(base: &Self::RingElementType, exp: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        if exp.value < 0 {
+            return IntRing::pow(base, exp);
+        }
+        // exponentiation by squaring, since wrapping_pow only takes exponents up to u32::MAX
+        let (mut base, mut exp, mut result) = (base.value, exp.value, 1i64);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.wrapping_mul(base);
+            }
+            base = base.wrapping_mul(base);
+            exp >>= 1;
+        }
+        Ok(IntRingElement::new(result))
+    }
+
+    /// `abs(x)` and `gcd(x, y)` as in [IntRing], wrapping to `i64::MIN` where the result is `2^63`.
+    fn function(name: &str) -> Option<RingFunction<Self>> {
+        match name {
+            "abs" => Some(RingFunction { arity: 1, apply: |args| Ok(IntRingElement::new(args[0].value.wrapping_abs())) }),
+            "gcd" => Some(RingFunction { arity: 2, apply: |args| {
+                // the gcd only fails if it is 2^63
+                Ok(IntRing::gcd(&args[0], &args[1]).unwrap_or(IntRingElement::new(i64::MIN)))
+            }}),
+            _ => None,
+        }
+    }
+
+    fn compare(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Ordering> {
+        IntRing::compare(elm1, elm2)
+    }
+}
+
+impl OrderedRing for WrappingIntRing {
+}
+
+impl ElementTokenRing for WrappingIntRing {
+    fn element_from_token(value: i64) -> RingResult<Self::RingElementType> {
+        Ok(IntRingElement::new(value))
+    }
 }
 
 impl ExpressionComponent<IntRing> {
     pub fn new_int_element(value: i64) -> ExpressionComponent<IntRing> {
         ExpressionComponent::new_ring_element(IntRingElement::new(value))
     }
+
+    /// Evaluate expression without failing. Operations that would fail in the ring are instead
+    /// evaluated with saturating arithmetic and the error is recorded as a warning. Division by
+    /// zero saturates according to the sign of the dividend and division results not in the ring
+    /// are truncated, as are powers with negative exponents, except that `0 ^ -n` saturates to
+    /// `i64::MAX`. Modulo by zero evaluates to the dividend unchanged, `x mod 0 = x`. Variables
+    /// are unbound and, like function calls that fail, evaluate to zero. As when evaluating, only the
+    /// taken branch of a conditional is evaluated. Returns the best effort value and the warnings
+    /// in evaluation order, with the source positions of the failing operations as when evaluating.
+    pub fn evaluate_saturating_with_warnings(&self) -> (IntRingElement, Vec<EvaluateExpressionError>) {
+        let mut warnings = Vec::new();
+        let value = self.evaluate_saturating(&mut warnings);
+        (value, warnings)
+    }
+
+    fn evaluate_saturating(&self, warnings: &mut Vec<EvaluateExpressionError>) -> IntRingElement {
+        match self {
+            ExpressionComponent::RingElement(element) => element.clone(),
+            ExpressionComponent::Variable(name) => {
+                warnings.push(ExpressionComponent::<IntRing>::unbound_variable(name));
+                IntRingElement::new(0)
+            }
+            ExpressionComponent::FunctionCall { name, args, position } => {
+                let values: Vec<_> = args.iter().map(|arg| arg.evaluate_saturating(warnings)).collect();
+                ExpressionComponent::<IntRing>::apply_function(name, *position, &values).unwrap_or_else(|err| {
+                    warnings.push(err);
+                    IntRingElement::new(0)
+                })
+            }
+            ExpressionComponent::Parentheses { inner, .. } => inner.evaluate_saturating(warnings),
+            ExpressionComponent::UnaryMinus { inner, position } => {
+                let value = inner.evaluate_saturating(warnings);
+                ExpressionComponent::<IntRing>::negate(&value, *position).unwrap_or_else(|err| {
+                    warnings.push(err);
+                    IntRingElement::new(value.value.saturating_neg())
+                })
+            }
+            ExpressionComponent::Conditional { condition, then, otherwise, .. } => {
+                let condition_value = condition.evaluate_saturating(warnings);
+                let branch = if ExpressionComponent::<IntRing>::holds(&condition_value) { then } else { otherwise };
+                branch.evaluate_saturating(warnings)
+            }
+            ExpressionComponent::Addition { left, right, position } => {
+                Self::evaluate_saturating_binary_operation(Operator::Addition, i64::saturating_add, left, right, *position, warnings)
+            }
+            ExpressionComponent::Subtraction { left, right, position } => {
+                Self::evaluate_saturating_binary_operation(Operator::Subtraction, i64::saturating_sub, left, right, *position, warnings)
+            }
+            ExpressionComponent::Multiplication { left, right, position } => {
+                Self::evaluate_saturating_binary_operation(Operator::Multiplication, i64::saturating_mul, left, right, *position, warnings)
+            }
+            ExpressionComponent::Division { left, right, position } => {
+                Self::evaluate_saturating_binary_operation(Operator::Division, IntRing::saturating_div, left, right, *position, warnings)
+            }
+            ExpressionComponent::Modulo { left, right, position } => {
+                Self::evaluate_saturating_binary_operation(Operator::Modulo, IntRing::saturating_rem, left, right, *position, warnings)
+            }
+            ExpressionComponent::Power { left, right, position } => {
+                Self::evaluate_saturating_binary_operation(Operator::Power, IntRing::saturating_pow, left, right, *position, warnings)
+            }
+            ExpressionComponent::LessThan { left, right, position } => {
+                Self::evaluate_saturating_binary_operation(Operator::LessThan, |a, b| (a < b) as i64, left, right, *position, warnings)
+            }
+            ExpressionComponent::GreaterThan { left, right, position } => {
+                Self::evaluate_saturating_binary_operation(Operator::GreaterThan, |a, b| (a > b) as i64, left, right, *position, warnings)
+            }
+            ExpressionComponent::Equal { left, right, position } => {
+                Self::evaluate_saturating_binary_operation(Operator::Equal, |a, b| (a == b) as i64, left, right, *position, warnings)
+            }
+        }
+    }
+
+    fn evaluate_saturating_binary_operation(
+        operator: Operator,
+        saturating_operation: fn(i64, i64) -> i64,
+        left: &ExpressionComponent<IntRing>,
+        right: &ExpressionComponent<IntRing>,
+        position: SourcePosition,
+        warnings: &mut Vec<EvaluateExpressionError>) -> IntRingElement
+    {
+        let left_value = left.evaluate_saturating(warnings);
+        let right_value = right.evaluate_saturating(warnings);
+        match operator.apply::<IntRing>(&left_value, &right_value) {
+            Ok(value) => value,
+            Err(err) => {
+                warnings.push(EvaluateExpressionError::from_operation_error(operator.name(), position, err));
+                IntRingElement::new(saturating_operation(left_value.value, right_value.value))
+            }
+        }
+    }
+
+    /// Find errors that are guaranteed to occur when evaluating the expression, without
+    /// evaluating it. Currently finds division and modulo by a literal zero and literal negative
+    /// exponents. Errors in the branches of conditionals are not guaranteed, since a branch may not
+    /// be taken. Returns `None` if no guaranteed error is found, which does not imply that
+    /// evaluation succeeds. The errors have the messages of the failing ring operations.
+    pub fn static_error_check(&self) -> Option<EvaluateExpressionError> {
+        match self {
+            ExpressionComponent::RingElement(_) | ExpressionComponent::Variable(_) => None,
+            ExpressionComponent::FunctionCall { args, .. } => args.iter().find_map(|arg| arg.static_error_check()),
+            ExpressionComponent::Parentheses { inner, .. } | ExpressionComponent::UnaryMinus { inner, .. } => inner.static_error_check(),
+            ExpressionComponent::Conditional { condition, .. } => condition.static_error_check(),
+            ExpressionComponent::Division { left, right, .. } if right.is_literal_zero() => {
+                left.static_error_check().or_else(|| Some(EvaluateExpressionError {
+                    message: "Division by zero".to_string(),
+                    kind: RingErrorKind::DivisionByZero,
+                    position: self.position().position,
+                    span: self.position().span_range(),
+                }))
+            }
+            ExpressionComponent::Modulo { left, right, .. } if right.is_literal_zero() => {
+                left.static_error_check().or_else(|| Some(EvaluateExpressionError {
+                    message: "Modulo by zero".to_string(),
+                    kind: RingErrorKind::DivisionByZero,
+                    position: self.position().position,
+                    span: self.position().span_range(),
+                }))
+            }
+            ExpressionComponent::Power { left, right, .. } if right.literal_value().is_some_and(|exp| exp < 0) => {
+                left.static_error_check().or_else(|| Some(EvaluateExpressionError {
+                    message: "Negative exponent".to_string(),
+                    kind: RingErrorKind::NotInRing { suggested_ring: Some("rational"), remainder: None },
+                    position: self.position().position,
+                    span: self.position().span_range(),
+                }))
+            }
+            ExpressionComponent::Addition { left, right, .. }
+            | ExpressionComponent::Subtraction { left, right, .. }
+            | ExpressionComponent::Multiplication { left, right, .. }
+            | ExpressionComponent::Division { left, right, .. }
+            | ExpressionComponent::Modulo { left, right, .. }
+            | ExpressionComponent::Power { left, right, .. }
+            | ExpressionComponent::LessThan { left, right, .. }
+            | ExpressionComponent::GreaterThan { left, right, .. }
+            | ExpressionComponent::Equal { left, right, .. } => {
+                left.static_error_check().or_else(|| right.static_error_check())
+            }
+        }
+    }
+
+    /// Suggest an equivalent expression that evaluates when evaluating this expression overflows
+    /// only because of the order of operands. The operands of chains of additions, and of chains
+    /// of multiplications, are reordered: terms are alternated by sign to keep the partial sums
+    /// small, and zero factors are moved first. Subtraction and the other operations are not
+    /// reordered, and a chain in parentheses is reordered on its own, as a single operand of the
+    /// enclosing chain. The operations of a reordered chain keep the source positions of the
+    /// operators of the chain, from left to right, but not their spans, since the operands are
+    /// moved. Returns `None` if evaluation does not overflow, or if reordering does not avoid the
+    /// overflow.
+    pub fn suggest_reordering(&self) -> Option<ExpressionComponent<IntRing>> {
+        match self.evaluate() {
+            Err(EvaluateExpressionError { kind: RingErrorKind::Overflow, .. }) => {
+                let reordered = self.reorder_operands();
+                reordered.evaluate().is_ok().then_some(reordered)
+            }
+            _ => None,
+        }
+    }
+
+    fn reorder_operands(&self) -> ExpressionComponent<IntRing> {
+        let (operator, left, right) = match self {
+            ExpressionComponent::RingElement(element) => return ExpressionComponent::RingElement(element.clone()),
+            ExpressionComponent::Variable(name) => return ExpressionComponent::Variable(name.clone()),
+            ExpressionComponent::FunctionCall { name, args, position } => {
+                return ExpressionComponent::new_function_call(name.clone(), args.iter().map(ExpressionComponent::reorder_operands).collect())
+                    .with_position(*position);
+            }
+            ExpressionComponent::Parentheses { inner, position } => return ExpressionComponent::new_parenteses(inner.reorder_operands()).with_position(*position),
+            ExpressionComponent::UnaryMinus { inner, position } => return ExpressionComponent::new_unary_minus(inner.reorder_operands()).with_position(*position),
+            ExpressionComponent::Conditional { condition, then, otherwise, position } => {
+                return ExpressionComponent::new_conditional(condition.reorder_operands(), then.reorder_operands(), otherwise.reorder_operands())
+                    .with_position(*position);
+            }
+            ExpressionComponent::Addition { .. } | ExpressionComponent::Multiplication { .. } => {
+                let operator = if let ExpressionComponent::Addition { .. } = self { Operator::Addition } else { Operator::Multiplication };
+                let mut operands = Vec::new();
+                let mut positions = Vec::new();
+                self.collect_chain_operands(operator, &mut operands, &mut positions);
+                let operands = operands.into_iter().map(ExpressionComponent::reorder_operands).collect();
+                let operands = match operator {
+                    Operator::Addition => IntRing::order_terms(operands),
+                    _ => IntRing::order_factors(operands),
+                };
+                // the reordered operations no longer span the source of the original operations
+                let mut positions = positions.into_iter().map(|position| SourcePosition { span: None, ..position });
+                return operands.into_iter()
+                    .reduce(|left, right| ExpressionComponent::new_binary_operation(operator, left, right)
+                        .with_position(positions.next().expect("operator between each pair of operands")))
+                    .expect("chain has operands");
+            }
+            ExpressionComponent::Subtraction { left, right, .. } => (Operator::Subtraction, left, right),
+            ExpressionComponent::Division { left, right, .. } => (Operator::Division, left, right),
+            ExpressionComponent::Modulo { left, right, .. } => (Operator::Modulo, left, right),
+            ExpressionComponent::Power { left, right, .. } => (Operator::Power, left, right),
+            ExpressionComponent::LessThan { left, right, .. } => (Operator::LessThan, left, right),
+            ExpressionComponent::GreaterThan { left, right, .. } => (Operator::GreaterThan, left, right),
+            ExpressionComponent::Equal { left, right, .. } => (Operator::Equal, left, right),
+        };
+        ExpressionComponent::new_binary_operation(operator, left.reorder_operands(), right.reorder_operands())
+            .with_position(self.position())
+    }
+
+    /// Operands of the maximal chain of `operator` operations outside parentheses, and the
+    /// positions of the operators of the chain, from left to right.
+    fn collect_chain_operands<'a>(&'a self, operator: Operator, operands: &mut Vec<&'a ExpressionComponent<IntRing>>, positions: &mut Vec<SourcePosition>) {
+        match (operator, self) {
+            (Operator::Addition, ExpressionComponent::Addition { left, right, position })
+            | (Operator::Multiplication, ExpressionComponent::Multiplication { left, right, position }) => {
+                left.collect_chain_operands(operator, operands, positions);
+                positions.push(*position);
+                right.collect_chain_operands(operator, operands, positions);
+            }
+            _ => operands.push(self),
+        }
+    }
+
+    fn is_literal_zero(&self) -> bool {
+        match self {
+            ExpressionComponent::RingElement(element) => element.value == 0,
+            ExpressionComponent::Parentheses { inner, .. } | ExpressionComponent::UnaryMinus { inner, .. } => inner.is_literal_zero(),
+            _ => false,
+        }
+    }
+
+    /// Value of a literal, possibly negated or in parentheses.
+    fn literal_value(&self) -> Option<i64> {
+        match self {
+            ExpressionComponent::RingElement(element) => Some(element.value),
+            ExpressionComponent::Parentheses { inner, .. } => inner.literal_value(),
+            ExpressionComponent::UnaryMinus { inner, .. } => inner.literal_value().and_then(i64::checked_neg),
+            _ => None,
+        }
+    }
+}
+
+impl From<i64> for ExpressionComponent<IntRing> {
+    fn from(value: i64) -> Self {
+        ExpressionComponent::new_int_element(value)
+    }
+}
+
+impl IntRing {
+    /// Order terms such that the partial sums stay between the smallest and largest of the
+    /// terms and the total: a negative term is added next when the partial sum is nonnegative,
+    /// otherwise a nonnegative term. Terms that fail to evaluate are kept in order.
+    fn order_terms(terms: Vec<ExpressionComponent<IntRing>>) -> Vec<ExpressionComponent<IntRing>> {
+        let values: Option<Vec<i64>> = terms.iter().map(|term| term.evaluate().ok().map(|value| value.value)).collect();
+        let Some(values) = values else {
+            return terms;
+        };
+        let (mut negative, mut nonnegative): (Vec<_>, Vec<_>) = values.into_iter().zip(terms).partition(|(value, _)| *value < 0);
+        // pop from the back while keeping the original relative order
+        negative.reverse();
+        nonnegative.reverse();
+        let mut sum = 0i128;
+        let mut ordered = Vec::new();
+        while let Some((value, term)) = if sum >= 0 && !negative.is_empty() || nonnegative.is_empty() {
+            negative.pop()
+        } else {
+            nonnegative.pop()
+        } {
+            sum += value as i128;
+            ordered.push(term);
+        }
+        ordered
+    }
+
+    /// Order factors such that literal zero factors come first, which makes all partial
+    /// products zero.
+    fn order_factors(factors: Vec<ExpressionComponent<IntRing>>) -> Vec<ExpressionComponent<IntRing>> {
+        let (mut ordered, other): (Vec<_>, Vec<_>) = factors.into_iter().partition(ExpressionComponent::is_literal_zero);
+        ordered.extend(other);
+        ordered
+    }
+
+    fn saturating_div(dividend: i64, divisor: i64) -> i64 {
+        if divisor == 0 {
+            match dividend {
+                0 => 0,
+                d if d > 0 => i64::MAX,
+                _ => i64::MIN,
+            }
+        } else {
+            dividend.saturating_div(divisor)
+        }
+    }
+
+    fn saturating_rem(dividend: i64, divisor: i64) -> i64 {
+        // the remainder only overflows for i64::MIN mod -1, where the exact result is 0
+        dividend.checked_rem_euclid(divisor).unwrap_or(if divisor == 0 { dividend } else { 0 })
+    }
+
+    fn saturating_pow(base: i64, exp: i64) -> i64 {
+        if exp < 0 {
+            // truncation of 1 / base ^ -exp
+            match base {
+                0 => i64::MAX,
+                1 => 1,
+                -1 => if exp % 2 == 0 { 1 } else { -1 },
+                _ => 0,
+            }
+        } else {
+            // clamp huge exponents to one of the same parity, which gives the sign of the result
+            base.saturating_pow(u32::try_from(exp).unwrap_or(if exp % 2 == 0 { u32::MAX - 1 } else { u32::MAX }))
+        }
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
-    use crate::expression::ring::intring::{IntRingElement, IntRing};
-    use crate::expression::ring::{Ring, RingError};
+    use crate::expression::ring::intring::{IntRingElement, IntRing, GenericIntRing, GenericIntRingElement, WrappingIntRing};
+    use crate::expression::ring::{Ring, RingError, DivisionRemainder};
+    use crate::expression::ring::RingErrorKind::{Overflow, NotInRing, Unspecified, DivisionByZero};
+    use crate::expression::parser::{parse_int_ring_expression, parse_expression, ParseExpressionError};
+    use crate::expression::parser::ParseExpressionErrorKind::InvalidLiteral;
+    use crate::expression::{ExpressionComponent, EvaluateExpressionError};
+
+    #[test]
+    fn wrapping_add_overflow() {
+        let max = IntRingElement::new(i64::MAX);
+        let one = IntRingElement::new(1);
+
+        assert_eq!(Ok(IntRingElement::new(i64::MIN)), WrappingIntRing::add(&max, &one));
+        assert_eq!(Err(RingError{message: "Overflow".to_string(), kind: Overflow}), IntRing::add(&max, &one));
+    }
+
+    #[test]
+    fn wrapping_operations() {
+        let elm = |value| IntRingElement::new(value);
+
+        assert_eq!(Ok(elm(i64::MAX)), WrappingIntRing::sub(&elm(i64::MIN), &elm(1)));
+        assert_eq!(Ok(elm(-2)), WrappingIntRing::mul(&elm(i64::MAX), &elm(2)));
+        assert_eq!(Ok(elm(i64::MIN)), WrappingIntRing::neg(&elm(i64::MIN)));
+        assert_eq!(Ok(elm(i64::MIN)), WrappingIntRing::div(&elm(i64::MIN), &elm(-1)));
+        assert_eq!(Ok(elm(0)), WrappingIntRing::pow(&elm(2), &elm(64)));
+        assert_eq!(Ok(elm(3i64.wrapping_pow(40))), WrappingIntRing::pow(&elm(3), &elm(40)));
+        assert_eq!(Ok(elm(-1)), WrappingIntRing::pow(&elm(-1), &elm(i64::MAX)));
+    }
+
+    #[test]
+    fn wrapping_errors() {
+        let elm = |value| IntRingElement::new(value);
+
+        assert_eq!(Err(RingError{message: "Division by zero".to_string(), kind: DivisionByZero}), WrappingIntRing::div(&elm(1), &elm(0)));
+        assert!(matches!(WrappingIntRing::div(&elm(5), &elm(2)), Err(RingError{kind: NotInRing {..}, ..})));
+        assert!(matches!(WrappingIntRing::pow(&elm(2), &elm(-1)), Err(RingError{kind: NotInRing {..}, ..})));
+    }
+
+    #[test]
+    fn evaluate_wrapping() {
+        let expression = parse_expression::<WrappingIntRing>("9223372036854775807 + 1").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(i64::MIN)), expression.evaluate());
+
+        let expression = parse_int_ring_expression("9223372036854775807 + 1").expect("ok");
+        assert_eq!(Err(EvaluateExpressionError{message: "Overflow during addition".to_string(), kind: Overflow, position: Some(20), span: Some(0..23)}), expression.evaluate());
+    }
+
+    #[test]
+    fn wrapping_compare() {
+        let expression = parse_expression::<WrappingIntRing>("9223372036854775807 + 1 < 0").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(1)), expression.evaluate());
+
+        let expression = parse_expression::<WrappingIntRing>("3 > 2").expect("ok");
+        assert_eq!(Ok(IntRingElement::new(1)), expression.evaluate());
+    }
+
+    #[test]
+    fn suggest_reordering_sum() {
+        let expression = parse_int_ring_expression("9223372036854775807 + 1 + -2").expect("ok");
+        assert!(expression.evaluate().is_err());
+
+        let reordered = expression.suggest_reordering().expect("reordering");
+
+        assert_eq!("-2 + 9223372036854775807 + 1", reordered.to_string());
+        assert_eq!(Ok(IntRingElement::new(i64::MAX - 1)), reordered.evaluate());
+    }
+
+    #[test]
+    fn suggest_reordering_product() {
+        let expression = parse_int_ring_expression("3037000500 * 3037000500 * (2 - 2) * 0").expect("ok");
+
+        let reordered = expression.suggest_reordering().expect("reordering");
+
+        assert_eq!(Ok(IntRingElement::new(0)), reordered.evaluate());
+    }
+
+    #[test]
+    fn suggest_reordering_respects_parentheses() {
+        let expression = parse_int_ring_expression("1 + (9223372036854775807 + 1) + -2").expect("ok");
+
+        assert_eq!(None, expression.suggest_reordering());
+
+        let expression = parse_int_ring_expression("(9223372036854775807 + 1 + -2) + 1").expect("ok");
+        let reordered = expression.suggest_reordering().expect("reordering");
+
+        assert_eq!("(-2 + 9223372036854775807 + 1) + 1", reordered.to_string());
+    }
+
+    #[test]
+    fn suggest_reordering_keeps_positions() {
+        let expression = parse_int_ring_expression("9223372036854775807 + 1 + -2").expect("ok");
+
+        let reordered = expression.suggest_reordering().expect("reordering");
+
+        assert_eq!(Some(24), reordered.position().position);
+        let ExpressionComponent::Addition { left, .. } = &reordered else {
+            panic!("not an addition: {}", reordered);
+        };
+        assert_eq!(Some(20), left.position().position);
+    }
+
+    #[test]
+    fn suggest_reordering_none() {
+        let evaluates = parse_int_ring_expression("1 + 2").expect("ok");
+        let overflows = parse_int_ring_expression("9223372036854775807 + 1 - 2").expect("ok");
+        let division_by_zero = parse_int_ring_expression("1 / 0 + 1").expect("ok");
+
+        assert_eq!(None, evaluates.suggest_reordering());
+        assert_eq!(None, overflows.suggest_reordering());
+        assert_eq!(None, division_by_zero.suggest_reordering());
+    }
+
+    #[test]
+    fn zero_and_one() {
+        let elm = IntRingElement::new(-7);
+
+        assert_eq!(IntRingElement::new(0), IntRing::zero());
+        assert_eq!(IntRingElement::new(1), IntRing::one());
+        assert_eq!(Ok(elm.clone()), IntRing::add(&IntRing::zero(), &elm));
+        assert_eq!(Ok(elm.clone()), IntRing::mul(&IntRing::one(), &elm));
+        assert_eq!(Ok(IntRing::zero()), IntRing::mul(&IntRing::zero(), &elm));
+    }
 
     #[test]
     fn add() {
@@ -94,7 +805,7 @@ mod tests {
 
         let res = IntRing::add(&elm1, &elm2);
 
-        assert_eq!(Err(RingError{message: "Overflow".to_string()}), res);
+        assert_eq!(Err(RingError{message: "Overflow".to_string(), kind: Overflow}), res);
     }
 
     #[test]
@@ -114,7 +825,7 @@ mod tests {
 
         let res = IntRing::sub(&elm1, &elm2);
 
-        assert_eq!(Err(RingError{message: "Overflow".to_string()}), res);
+        assert_eq!(Err(RingError{message: "Overflow".to_string(), kind: Overflow}), res);
     }
 
     #[test]
@@ -144,7 +855,7 @@ mod tests {
 
         let res = IntRing::mul(&elm1, &elm2);
 
-        assert_eq!(Err(RingError{message: "Overflow".to_string()}), res);
+        assert_eq!(Err(RingError{message: "Overflow".to_string(), kind: Overflow}), res);
     }
 
     #[test]
@@ -184,7 +895,17 @@ mod tests {
 
         let res = IntRing::div(&elm1, &elm2);
 
-        assert_eq!(Err(RingError{message: "Overflow".to_string()}), res);
+        assert_eq!(Err(RingError{message: "Division by zero".to_string(), kind: DivisionByZero}), res);
+    }
+
+    #[test]
+    fn div_overflow() {
+        let elm1 = IntRingElement::new(i64::MIN);
+        let elm2 = IntRingElement::new(-1);
+
+        let res = IntRing::div(&elm1, &elm2);
+
+        assert_eq!(Err(RingError{message: "Overflow".to_string(), kind: Overflow}), res);
     }
 
     #[test]
@@ -194,7 +915,23 @@ mod tests {
 
         let res = IntRing::div(&elm1, &elm2);
 
-        assert_eq!(Err(RingError{message: "Overflow".to_string()}), res);
+        assert_eq!(Err(RingError{message: "Division by zero".to_string(), kind: DivisionByZero}), res);
+    }
+
+    #[test]
+    fn div_zero_numerator() {
+        let res = IntRing::div(&IntRingElement::new(0), &IntRingElement::new(5));
+
+        assert_eq!(Ok(IntRingElement::new(0)), res);
+    }
+
+    #[test]
+    fn evaluate_division_zero_operands() {
+        let division_by_zero = |position| Err(EvaluateExpressionError{message: "Division by zero during division".to_string(), kind: DivisionByZero, position: Some(position), span: Some(0..5)});
+
+        assert_eq!(Ok(IntRingElement::new(0)), parse_int_ring_expression("0 / 5").expect("ok").evaluate());
+        assert_eq!(division_by_zero(2), parse_int_ring_expression("0 / 0").expect("ok").evaluate());
+        assert_eq!(division_by_zero(2), parse_int_ring_expression("5 / 0").expect("ok").evaluate());
     }
 
     #[test]
@@ -204,7 +941,7 @@ mod tests {
 
         let res = IntRing::div(&elm1, &elm2);
 
-        assert_eq!(Err(RingError{message: "Result not in ring".to_string()}), res);
+        assert_eq!(Err(RingError{message: "Result not in ring".to_string(), kind: NotInRing { suggested_ring: Some("rational"), remainder: Some(Box::new(DivisionRemainder { quotient: "2".to_string(), remainder: "1".to_string() })) }}), res);
     }
 
     #[test]
@@ -214,7 +951,7 @@ mod tests {
 
         let res = IntRing::div(&elm1, &elm2);
 
-        assert_eq!(Err(RingError{message: "Result not in ring".to_string()}), res);
+        assert_eq!(Err(RingError{message: "Result not in ring".to_string(), kind: NotInRing { suggested_ring: Some("rational"), remainder: Some(Box::new(DivisionRemainder { quotient: "-3".to_string(), remainder: "1".to_string() })) }}), res);
     }
 
     #[test]
@@ -224,6 +961,339 @@ mod tests {
 
         let res = IntRing::div(&elm1, &elm2);
 
-        assert_eq!(Err(RingError{message: "Result not in ring".to_string()}), res);
+        assert_eq!(Err(RingError{message: "Result not in ring".to_string(), kind: NotInRing { suggested_ring: Some("rational"), remainder: Some(Box::new(DivisionRemainder { quotient: "-2".to_string(), remainder: "1".to_string() })) }}), res);
+    }
+
+    #[test]
+    fn rem() {
+        let res = IntRing::rem(&IntRingElement::new(7), &IntRingElement::new(3));
+        assert_eq!(Ok(IntRingElement::new(1)), res);
+
+        let res = IntRing::rem(&IntRingElement::new(-1), &IntRingElement::new(3));
+        assert_eq!(Ok(IntRingElement::new(2)), res);
+
+        let res = IntRing::rem(&IntRingElement::new(7), &IntRingElement::new(-3));
+        assert_eq!(Ok(IntRingElement::new(1)), res);
+    }
+
+    #[test]
+    fn rem_by_zero() {
+        let res = IntRing::rem(&IntRingElement::new(7), &IntRingElement::new(0));
+
+        assert_eq!(Err(RingError{message: "Modulo by zero".to_string(), kind: DivisionByZero}), res);
+    }
+
+    #[test]
+    fn rem_min_by_minus_one() {
+        let res = IntRing::rem(&IntRingElement::new(i64::MIN), &IntRingElement::new(-1));
+        assert_eq!(Ok(IntRingElement::new(0)), res);
+
+        let res = GenericIntRing::<i32>::rem(&GenericIntRingElement::new(i32::MIN), &GenericIntRingElement::new(-1));
+        assert_eq!(Ok(GenericIntRingElement::new(0)), res);
+    }
+
+    #[test]
+    fn evaluate_modulo_by_zero() {
+        assert_eq!(Err(EvaluateExpressionError{message: "Modulo by zero during modulo".to_string(), kind: DivisionByZero, position: Some(2), span: Some(0..7)}),
+                   parse_int_ring_expression("5 mod 0").expect("ok").evaluate());
+        assert_eq!(Ok(IntRingElement::new(0)),
+                   parse_int_ring_expression("(-9223372036854775807 - 1) mod -1").expect("ok").evaluate());
+    }
+
+    #[test]
+    fn parse_element() {
+        assert_eq!(Ok(IntRingElement::new(42)), IntRing::parse_element("42"));
+        assert_eq!(Ok(IntRingElement::new(-42)), IntRing::parse_element("-42"));
+    }
+
+    #[test]
+    fn parse_element_invalid() {
+        assert_eq!(Err(RingError{message: "Invalid integer '4x2'".to_string(), kind: Unspecified}), IntRing::parse_element("4x2"));
+        assert_eq!(Err(RingError{message: "Decimal number too big".to_string(), kind: Overflow}), IntRing::parse_element("9223372036854775808"));
+    }
+
+    #[test]
+    fn neg() {
+        let elm = IntRingElement::new(5);
+
+        let res = IntRing::neg(&elm);
+
+        assert_eq!(Ok(IntRingElement::new(-5)), res);
+    }
+
+    #[test]
+    fn neg_overflow() {
+        let elm = IntRingElement::new(i64::MIN);
+
+        let res = IntRing::neg(&elm);
+
+        assert_eq!(Err(RingError{message: "Overflow".to_string(), kind: Overflow}), res);
+    }
+
+    #[test]
+    fn pow() {
+        assert_eq!(Ok(IntRingElement::new(-8)), IntRing::pow(&IntRingElement::new(-2), &IntRingElement::new(3)));
+        assert_eq!(Ok(IntRingElement::new(1)), IntRing::pow(&IntRingElement::new(0), &IntRingElement::new(0)));
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(Ok(IntRingElement::new(5)), parse_int_ring_expression("abs(-5)").expect("ok").evaluate());
+        assert_eq!(Ok(IntRingElement::new(6)), parse_int_ring_expression("gcd(12, 18)").expect("ok").evaluate());
+        assert_eq!(Ok(IntRingElement::new(6)), parse_int_ring_expression("gcd(-12, 0 - 18)").expect("ok").evaluate());
+        assert_eq!(Ok(IntRingElement::new(1)), parse_int_ring_expression("gcd(-9223372036854775807 - 1, -1)").expect("ok").evaluate());
+        assert_eq!(Ok(IntRingElement::new(0)), parse_int_ring_expression("gcd(0, 0)").expect("ok").evaluate());
+    }
+
+    #[test]
+    fn function_overflow() {
+        let expression = parse_int_ring_expression("1 + abs(-9223372036854775807 - 1)").expect("ok");
+
+        assert_eq!(Err(EvaluateExpressionError{message: "Overflow during abs".to_string(), kind: Overflow, position: Some(4), span: Some(4..33)}), expression.evaluate());
+    }
+
+    #[test]
+    fn function_wrong_arity() {
+        let expression = ExpressionComponent::<IntRing>::new_function_call("abs", vec![
+            ExpressionComponent::new_int_element(1),
+            ExpressionComponent::new_int_element(2)]);
+
+        assert_eq!(Err(EvaluateExpressionError{message: "Function 'abs' takes 1 argument, not 2".to_string(), kind: Unspecified, position: None, span: None}), expression.evaluate());
+    }
+
+    #[test]
+    fn pow_overflow() {
+        let res = IntRing::pow(&IntRingElement::new(2), &IntRingElement::new(63));
+
+        assert_eq!(Err(RingError{message: "Overflow in multiplication 2147483648 * 4294967296".to_string(), kind: Overflow}), res);
+    }
+
+    #[test]
+    fn pow_by_squaring() {
+        let expression = parse_int_ring_expression("2 ^ 10").expect("ok");
+
+        assert_eq!(Ok(IntRingElement::new(1024)), expression.evaluate());
+        assert_eq!(Ok(IntRingElement::new(i64::MIN)), IntRing::pow(&IntRingElement::new(-2), &IntRingElement::new(63)));
+    }
+
+    #[test]
+    fn pow_huge_exponent() {
+        let exp = IntRingElement::new(5_000_000_000);
+
+        assert_eq!(Ok(IntRingElement::new(1)), IntRing::pow(&IntRingElement::new(1), &exp));
+        assert_eq!(Ok(IntRingElement::new(0)), IntRing::pow(&IntRingElement::new(0), &exp));
+        assert_eq!(Ok(IntRingElement::new(1)), IntRing::pow(&IntRingElement::new(-1), &exp));
+        assert_eq!(Ok(IntRingElement::new(-1)), IntRing::pow(&IntRingElement::new(-1), &IntRingElement::new(5_000_000_001)));
+        assert_eq!(Ok(IntRingElement::new(1)), parse_int_ring_expression("1 ^ 5000000000").expect("ok").evaluate());
+    }
+
+    #[test]
+    fn evaluate_pow_overflow() {
+        let expression = parse_int_ring_expression("3 ^ 40").expect("ok");
+
+        assert_eq!(Err(EvaluateExpressionError{message: "Overflow in multiplication 6561 * 1853020188851841 during exponentiation".to_string(), kind: Overflow, position: Some(2), span: Some(0..6)}), expression.evaluate());
+    }
+
+    #[test]
+    fn pow_negative_exponent() {
+        let res = IntRing::pow(&IntRingElement::new(2), &IntRingElement::new(-1));
+
+        assert_eq!(Err(RingError{message: "Negative exponent".to_string(), kind: NotInRing { suggested_ring: Some("rational"), remainder: None }}), res);
+    }
+
+    #[test]
+    fn evaluate_saturating_power() {
+        let expression = parse_int_ring_expression("3 ^ 50 - 2 ^ -1").expect("ok");
+
+        let (value, warnings) = expression.evaluate_saturating_with_warnings();
+
+        assert_eq!(IntRingElement::new(i64::MAX), value);
+        assert_eq!(2, warnings.len());
+    }
+
+    #[test]
+    fn evaluate_saturating_with_warnings() {
+        let expression = parse_int_ring_expression("9223372036854775807 + 7 - 0").expect("ok");
+
+        let (value, warnings) = expression.evaluate_saturating_with_warnings();
+
+        assert_eq!(IntRingElement::new(i64::MAX), value);
+        assert_eq!(vec![EvaluateExpressionError{message: "Overflow during addition".to_string(), kind: Overflow, position: Some(20), span: Some(0..23)}], warnings);
+    }
+
+    #[test]
+    fn evaluate_saturating_conditional() {
+        let expression = parse_int_ring_expression("1 > 0 ? 5 : 1 / 0").expect("ok");
+
+        assert_eq!((IntRingElement::new(5), vec![]), expression.evaluate_saturating_with_warnings());
+
+        let (value, warnings) = parse_int_ring_expression("1 / 0 ? 1 : 2").expect("ok").evaluate_saturating_with_warnings();
+        assert_eq!(IntRingElement::new(1), value);
+        assert_eq!(1, warnings.len());
+    }
+
+    #[test]
+    fn evaluate_saturating_with_warnings_positions() {
+        let expression = parse_int_ring_expression("1 + 5 / 0 * -(-9223372036854775807 - 2)").expect("ok");
+
+        let (value, warnings) = expression.evaluate_saturating_with_warnings();
+
+        assert_eq!(IntRingElement::new(i64::MAX), value);
+        assert_eq!(vec![
+            EvaluateExpressionError{message: "Division by zero during division".to_string(), kind: DivisionByZero, position: Some(6), span: Some(4..9)},
+            EvaluateExpressionError{message: "Overflow during subtraction".to_string(), kind: Overflow, position: Some(35), span: Some(14..38)},
+            EvaluateExpressionError{message: "Overflow during negation".to_string(), kind: Overflow, position: Some(12), span: Some(12..39)},
+            EvaluateExpressionError{message: "Overflow during multiplication".to_string(), kind: Overflow, position: Some(10), span: Some(4..39)},
+            EvaluateExpressionError{message: "Overflow during addition".to_string(), kind: Overflow, position: Some(2), span: Some(0..39)},
+        ], warnings);
+    }
+
+    #[test]
+    fn evaluate_saturating_power_huge_exponent() {
+        assert_eq!(IntRingElement::new(i64::MAX), parse_int_ring_expression("(-2) ^ 4294967296").expect("ok").evaluate_saturating_with_warnings().0);
+        assert_eq!(IntRingElement::new(i64::MIN), parse_int_ring_expression("(-2) ^ 4294967297").expect("ok").evaluate_saturating_with_warnings().0);
+    }
+
+    #[test]
+    fn evaluate_saturating_modulo_by_zero() {
+        let expression = parse_int_ring_expression("-7 mod 0").expect("ok");
+
+        let (value, warnings) = expression.evaluate_saturating_with_warnings();
+
+        assert_eq!(IntRingElement::new(-7), value);
+        assert_eq!(vec![EvaluateExpressionError{message: "Modulo by zero during modulo".to_string(), kind: DivisionByZero, position: Some(3), span: Some(0..8)}], warnings);
+    }
+
+    #[test]
+    fn evaluate_saturating_zero_to_negative_power() {
+        let expression = parse_int_ring_expression("0 ^ -3").expect("ok");
+
+        let (value, warnings) = expression.evaluate_saturating_with_warnings();
+
+        assert_eq!(IntRingElement::new(i64::MAX), value);
+        assert_eq!(1, warnings.len());
+        assert_eq!(Some(2), warnings[0].position);
+    }
+
+    #[test]
+    fn evaluate_saturating_with_warnings_no_overflow() {
+        let expression = ExpressionComponent::new_division(
+            ExpressionComponent::new_int_element(6),
+            ExpressionComponent::new_int_element(-2));
+
+        let (value, warnings) = expression.evaluate_saturating_with_warnings();
+
+        assert_eq!(IntRingElement::new(-3), value);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn evaluate_saturating_with_warnings_division_by_zero() {
+        let expression = ExpressionComponent::new_division(
+            ExpressionComponent::new_int_element(-6),
+            ExpressionComponent::new_int_element(0));
+
+        let (value, warnings) = expression.evaluate_saturating_with_warnings();
+
+        assert_eq!(IntRingElement::new(i64::MIN), value);
+        assert_eq!(1, warnings.len());
+    }
+
+    #[test]
+    fn not_in_ring_suggests_rational() {
+        let expression = ExpressionComponent::new_division(
+            ExpressionComponent::new_int_element(5),
+            ExpressionComponent::new_int_element(2));
+
+        let err = expression.evaluate().unwrap_err();
+
+        assert!(matches!(err.kind, NotInRing { suggested_ring: Some("rational"), .. }));
+    }
+
+    #[test]
+    fn not_in_ring_quotient_and_remainder() {
+        let expression = parse_int_ring_expression("1 + 7 / 3").expect("ok");
+
+        let err = expression.evaluate().unwrap_err();
+
+        let remainder = DivisionRemainder { quotient: "2".to_string(), remainder: "1".to_string() };
+        assert_eq!(NotInRing { suggested_ring: Some("rational"), remainder: Some(Box::new(remainder)) }, err.kind);
+        assert_eq!(Some(6), err.position);
+    }
+
+    #[test]
+    fn static_error_check_division_by_zero() {
+        let expression = parse_int_ring_expression("2 + 5 * (3 / 0)").expect("ok");
+
+        assert_eq!(Some(EvaluateExpressionError { message: "Division by zero".to_string(), kind: DivisionByZero, position: Some(11), span: Some(9..14) }),
+                   expression.static_error_check());
+    }
+
+    #[test]
+    fn static_error_check_modulo_by_zero() {
+        let expression = parse_int_ring_expression("7 mod 0").expect("ok");
+
+        assert_eq!(Some(EvaluateExpressionError { message: "Modulo by zero".to_string(), kind: DivisionByZero, position: Some(2), span: Some(0..7) }),
+                   expression.static_error_check());
+        assert_eq!(Err(EvaluateExpressionError { message: "Modulo by zero during modulo".to_string(), kind: DivisionByZero, position: Some(2), span: Some(0..7) }),
+                   expression.evaluate());
+    }
+
+    #[test]
+    fn static_error_check_negative_exponent() {
+        let expression = parse_int_ring_expression("1 + 2 ^ -1").expect("ok");
+
+        assert_eq!(Some(EvaluateExpressionError { message: "Negative exponent".to_string(), kind: NotInRing { suggested_ring: Some("rational"), remainder: None }, position: Some(6), span: Some(4..10) }),
+                   expression.static_error_check());
+        assert!(parse_int_ring_expression("2 ^ -(1)").expect("ok").static_error_check().is_some());
+        assert_eq!(None, parse_int_ring_expression("2 ^ --1").expect("ok").static_error_check());
+    }
+
+    #[test]
+    fn static_error_check_no_guaranteed_error() {
+        let expression = parse_int_ring_expression("5 / x").expect("ok");
+
+        assert_eq!(None, expression.static_error_check());
+    }
+
+    #[test]
+    fn static_error_check_conditional() {
+        assert_eq!(None, parse_int_ring_expression("x ? 1 / 0 : 2").expect("ok").static_error_check());
+        assert_eq!(Some(EvaluateExpressionError { message: "Division by zero".to_string(), kind: DivisionByZero, position: Some(3), span: Some(1..6) }),
+                   parse_int_ring_expression("(1 / 0) ? 1 : 2").expect("ok").static_error_check());
+    }
+
+    #[test]
+    fn i32_overflow() {
+        let elm1 = GenericIntRingElement::new(i32::MAX);
+        let elm2 = GenericIntRingElement::new(1);
+
+        let res = GenericIntRing::add(&elm1, &elm2);
+
+        assert_eq!(Err(RingError{message: "Overflow".to_string(), kind: Overflow}), res);
+    }
+
+    #[test]
+    fn i32_evaluate_overflow() {
+        let str = "2147483647 + 1";
+
+        assert_eq!(Ok(IntRingElement::new(2147483648)), parse_int_ring_expression(str).expect("ok").evaluate());
+        let expression: ExpressionComponent<GenericIntRing<i32>> = parse_expression(str).expect("ok");
+        assert_eq!(Overflow, expression.evaluate().unwrap_err().kind);
+    }
+
+    #[test]
+    fn i32_literal_too_big() {
+        assert_eq!(Err(ParseExpressionError{message: "Decimal number too big".to_string(), position: 4, kind: InvalidLiteral}),
+                   parse_expression::<GenericIntRing<i32>>("1 + 3000000000"));
+        assert_eq!(Err(RingError{message: "Decimal number too big".to_string(), kind: Overflow}),
+                   GenericIntRing::<i32>::parse_element("3000000000"));
+    }
+
+    #[test]
+    fn i128_no_overflow() {
+        let expression: ExpressionComponent<GenericIntRing<i128>> = parse_expression("9223372036854775807 * 2").expect("ok");
+
+        assert_eq!(Ok(GenericIntRingElement::new(i64::MAX as i128 * 2)), expression.evaluate());
     }
-}
\ No newline at end of file
+}
\ No newline at end of file
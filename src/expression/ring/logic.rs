@@ -0,0 +1,179 @@
+use crate::expression::ring::{Ring, RingResult, RingElement, RingError, RingErrorKind};
+use crate::expression::parser::ElementTokenRing;
+use std::fmt::{Display, Formatter};
+
+/// Truth value in [LogicRing].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct LogicRingElement {
+    value: bool
+}
+
+impl Display for LogicRingElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value)?;
+        Ok(())
+    }
+}
+
+impl From<LogicRingElement> for bool {
+    fn from(element: LogicRingElement) -> Self {
+        element.value
+    }
+}
+
+impl RingElement for LogicRingElement {
+
+}
+
+impl LogicRingElement {
+    pub fn new(value: bool) -> LogicRingElement {
+        LogicRingElement {
+            value
+        }
+    }
+
+    pub fn value(&self) -> bool {
+        self.value
+    }
+}
+
+/// The two element Boolean algebra adapted to the [Ring] interface: `+` is OR, `*` is AND and unary
+/// minus is the complement NOT, with `false` as zero and `true` as one. Subtraction is the
+/// difference `a AND NOT b`. This is not a ring, since OR has no inverses, and it is distinct from
+/// GF(2) where addition is XOR. Division and modulo are rejected. Literals are `0` and `1`, or
+/// `false` and `true` when parsed with [Ring::parse_element].
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct LogicRing {
+}
+
+impl Ring for LogicRing {
+    type RingElementType = LogicRingElement;
+
+    fn zero() -> Self::RingElementType {
+        LogicRingElement::new(false)
+    }
+
+    fn one() -> Self::RingElementType {
+        LogicRingElement::new(true)
+    }
+
+    fn parse_element(str: &str) -> RingResult<Self::RingElementType> {
+        match str {
+            "false" | "0" => Ok(LogicRingElement::new(false)),
+            "true" | "1" => Ok(LogicRingElement::new(true)),
+            _ => Err(RingError { message: format!("Invalid truth value '{}'", str), kind: RingErrorKind::Unspecified }),
+        }
+    }
+
+    fn add(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(LogicRingElement::new(elm1.value || elm2.value))
+    }
+
+    fn sub(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(LogicRingElement::new(elm1.value && !elm2.value))
+    }
+
+    fn mul(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(LogicRingElement::new(elm1.value && elm2.value))
+    }
+
+    fn div(_elm1: &Self::RingElementType, _elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Err(RingError { message: "Division not supported".to_string(), kind: RingErrorKind::Unspecified })
+    }
+
+    fn rem(_elm1: &Self::RingElementType, _elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Err(RingError { message: "Modulo not supported".to_string(), kind: RingErrorKind::Unspecified })
+    }
+
+    fn neg(elm: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(LogicRingElement::new(!elm.value))
+    }
+}
+
+impl ElementTokenRing for LogicRing {
+    fn element_from_token(value: i64) -> RingResult<Self::RingElementType> {
+        match value {
+            0 => Ok(LogicRingElement::new(false)),
+            1 => Ok(LogicRingElement::new(true)),
+            _ => Err(RingError {
+                message: format!("Number {} is not a truth value", value),
+                kind: RingErrorKind::NotInRing { suggested_ring: None, remainder: None },
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::ring::logic::{LogicRingElement, LogicRing};
+    use crate::expression::ring::{Ring, RingError};
+    use crate::expression::ring::RingErrorKind::Unspecified;
+    use crate::expression::parser::{parse_expression, ParseExpressionError};
+    use crate::expression::parser::ParseExpressionErrorKind::InvalidLiteral;
+    use crate::expression::{ExpressionComponent, EvaluateExpressionError};
+
+    const TRUE: LogicRingElement = LogicRingElement { value: true };
+    const FALSE: LogicRingElement = LogicRingElement { value: false };
+
+    #[test]
+    fn add_is_or() {
+        assert_eq!(Ok(TRUE), LogicRing::add(&TRUE, &FALSE));
+        assert_eq!(Ok(TRUE), LogicRing::add(&TRUE, &TRUE));
+        assert_eq!(Ok(FALSE), LogicRing::add(&FALSE, &FALSE));
+    }
+
+    #[test]
+    fn mul_is_and() {
+        assert_eq!(Ok(FALSE), LogicRing::mul(&TRUE, &FALSE));
+        assert_eq!(Ok(TRUE), LogicRing::mul(&TRUE, &TRUE));
+        assert_eq!(Ok(FALSE), LogicRing::mul(&FALSE, &FALSE));
+    }
+
+    #[test]
+    fn neg_is_complement() {
+        assert_eq!(Ok(FALSE), LogicRing::neg(&TRUE));
+        assert_eq!(Ok(TRUE), LogicRing::neg(&FALSE));
+    }
+
+    #[test]
+    fn sub_is_difference() {
+        assert_eq!(Ok(TRUE), LogicRing::sub(&TRUE, &FALSE));
+        assert_eq!(Ok(FALSE), LogicRing::sub(&TRUE, &TRUE));
+        assert_eq!(Ok(FALSE), LogicRing::sub(&FALSE, &TRUE));
+    }
+
+    #[test]
+    fn div_rejected() {
+        assert_eq!(Err(RingError{message: "Division not supported".to_string(), kind: Unspecified}), LogicRing::div(&TRUE, &TRUE));
+        assert_eq!(Err(RingError{message: "Modulo not supported".to_string(), kind: Unspecified}), LogicRing::rem(&TRUE, &TRUE));
+    }
+
+    #[test]
+    fn parse_element() {
+        assert_eq!(Ok(TRUE), LogicRing::parse_element("true"));
+        assert_eq!(Ok(FALSE), LogicRing::parse_element("0"));
+        assert_eq!(Err(RingError{message: "Invalid truth value '2'".to_string(), kind: Unspecified}), LogicRing::parse_element("2"));
+    }
+
+    #[test]
+    fn parse_expression_in_ring() {
+        let expression: ExpressionComponent<LogicRing> = parse_expression("1 * -(0 + 1) + 0 * 1").expect("ok");
+
+        assert_eq!(Ok(FALSE), expression.evaluate());
+
+        let expression: ExpressionComponent<LogicRing> = parse_expression("1 / 1").expect("ok");
+        assert_eq!(Err(EvaluateExpressionError{message: "Division not supported during division".to_string(), kind: Unspecified, position: Some(2), span: Some(0..5)}), expression.evaluate());
+    }
+
+    #[test]
+    fn parse_expression_not_truth_value() {
+        assert_eq!(Err(ParseExpressionError{message: "Number 2 is not a truth value".to_string(), position: 4, kind: InvalidLiteral}),
+                   parse_expression::<LogicRing>("1 + 2"));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("true", TRUE.to_string());
+        assert_eq!("false", FALSE.to_string());
+    }
+}
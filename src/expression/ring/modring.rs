@@ -0,0 +1,247 @@
+use crate::expression::ring::{Ring, RingResult, RingElement, RingError, RingErrorKind};
+use crate::expression::ring::intring::IntRing;
+use crate::expression::ExpressionComponent;
+use crate::expression::parser::ElementTokenRing;
+use std::fmt::{Display, Formatter};
+use std::num::IntErrorKind;
+
+/// Residue class in [ModRing], represented by its canonical residue in `0..N`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ModRingElement<const N: u64> {
+    residue: u64
+}
+
+impl<const N: u64> Display for ModRingElement<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.residue)?;
+        Ok(())
+    }
+}
+
+impl<const N: u64> From<ModRingElement<N>> for u64 {
+    fn from(element: ModRingElement<N>) -> Self {
+        element.residue
+    }
+}
+
+impl<const N: u64> RingElement for ModRingElement<N> {
+
+}
+
+impl<const N: u64> ModRingElement<N> {
+    const NONZERO_MODULUS: () = assert!(N > 0, "Modulus must be positive");
+
+    /// Residue class of `value`, reduced modulo `N`.
+    pub fn new(value: u64) -> ModRingElement<N> {
+        ModRingElement::from_i128(value as i128)
+    }
+
+    /// Residue class of the integer `value`, which may be negative: `-1` is `N - 1`.
+    pub fn from_int(value: i64) -> ModRingElement<N> {
+        ModRingElement::from_i128(value as i128)
+    }
+
+    fn from_i128(value: i128) -> ModRingElement<N> {
+        // fails to compile for a zero modulus
+        let () = Self::NONZERO_MODULUS;
+        ModRingElement {
+            residue: value.rem_euclid(N as i128) as u64
+        }
+    }
+
+    pub fn residue(&self) -> u64 {
+        self.residue
+    }
+}
+
+/// Ring of integers modulo `N`, Z/NZ. Operations never overflow. Division is multiplication by
+/// the modular inverse of the divisor, and fails unless the divisor is coprime to `N`. E.g. in
+/// Z/5Z, `1 / 2 = 3` since `2 * 3 = 1`, while in Z/4Z, `1 / 2` fails.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ModRing<const N: u64> {
+}
+
+impl<const N: u64> Ring for ModRing<N> {
+    type RingElementType = ModRingElement<N>;
+
+    fn zero() -> Self::RingElementType {
+        ModRingElement::new(0)
+    }
+
+    /// The residue class of `1`, which is `0` for `N = 1`.
+    fn one() -> Self::RingElementType {
+        ModRingElement::new(1)
+    }
+
+    /// Parse decimal integer with optional sign and reduce it modulo `N`.
+    fn parse_element(str: &str) -> RingResult<Self::RingElementType> {
+        str.parse().map(ModRingElement::from_i128).map_err(|err| match err.kind() {
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+                RingError { message: "Decimal number too big".to_string(), kind: RingErrorKind::Overflow }
+            }
+            _ => RingError { message: format!("Invalid integer '{}'", str), kind: RingErrorKind::Unspecified },
+        })
+    }
+
+    fn add(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(ModRingElement::from_i128(elm1.residue as i128 + elm2.residue as i128))
+    }
+
+    fn sub(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(ModRingElement::from_i128(elm1.residue as i128 - elm2.residue as i128))
+    }
+
+    fn mul(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        // the product of two residues fits in u128 but not necessarily in i128
+        let residue = (elm1.residue as u128 * elm2.residue as u128) % N as u128;
+        Ok(ModRingElement { residue: residue as u64 })
+    }
+
+    fn neg(elm: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(ModRingElement::from_i128(-(elm.residue as i128)))
+    }
+
+    fn div(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        ModRing::mul(elm1, &ModRing::inverse(elm2)?)
+    }
+
+    /// Remainder of the canonical residues. The residue classes themselves have no meaningful
+    /// remainder, since every class contains multiples of any element coprime to `N`.
+    fn rem(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        ModRing::check_divisor(elm2)?;
+        Ok(ModRingElement::new(elm1.residue % elm2.residue))
+    }
+}
+
+impl<const N: u64> ElementTokenRing for ModRing<N> {
+    fn element_from_token(value: i64) -> RingResult<Self::RingElementType> {
+        Ok(ModRingElement::from_int(value))
+    }
+}
+
+impl<const N: u64> ModRing<N> {
+    /// Multiplicative inverse, found by the extended euclidean algorithm. Fails unless `elm`
+    /// is coprime to `N`.
+    pub fn inverse(elm: &ModRingElement<N>) -> RingResult<ModRingElement<N>> {
+        ModRing::check_divisor(elm)?;
+        let (mut r0, mut r1) = (N as i128, elm.residue as i128);
+        let (mut t0, mut t1) = (0i128, 1i128);
+        while r1 != 0 {
+            let quotient = r0 / r1;
+            (r0, r1) = (r1, r0 - quotient * r1);
+            (t0, t1) = (t1, t0 - quotient * t1);
+        }
+        if r0 == 1 {
+            Ok(ModRingElement::from_i128(t0))
+        } else {
+            Err(RingError {
+                message: format!("{} not invertible modulo {}", elm, N),
+                kind: RingErrorKind::NotInRing { suggested_ring: None, remainder: None },
+            })
+        }
+    }
+
+    fn check_divisor(divisor: &ModRingElement<N>) -> RingResult<()> {
+        if divisor.residue == 0 {
+            Err(RingError { message: "Division by zero".to_string(), kind: RingErrorKind::DivisionByZero })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<const N: u64> ExpressionComponent<ModRing<N>> {
+    /// Convert an [IntRing] expression to the same expression modulo `N`, by reducing each
+    /// ring element. Source positions are kept.
+    pub fn from_int_ring_expression(expression: &ExpressionComponent<IntRing>) -> ExpressionComponent<ModRing<N>> {
+        expression.map_ring_elements(&|element| ModRingElement::from_int(element.value()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::ring::modring::{ModRingElement, ModRing};
+    use crate::expression::ring::{Ring, RingError};
+    use crate::expression::ring::RingErrorKind::{NotInRing, DivisionByZero};
+    use crate::expression::parser::parse_mod_ring_expression;
+    use crate::expression::EvaluateExpressionError;
+
+    type Mod5 = ModRing<5>;
+
+    #[test]
+    fn new_reduces() {
+        assert_eq!(3, ModRingElement::<5>::new(13).residue());
+        assert_eq!(4, ModRingElement::<5>::from_int(-1).residue());
+    }
+
+    #[test]
+    fn operations() {
+        let elm1 = ModRingElement::<5>::new(3);
+        let elm2 = ModRingElement::<5>::new(4);
+
+        assert_eq!(Ok(ModRingElement::new(2)), Mod5::add(&elm1, &elm2));
+        assert_eq!(Ok(ModRingElement::new(4)), Mod5::sub(&elm1, &elm2));
+        assert_eq!(Ok(ModRingElement::new(2)), Mod5::mul(&elm1, &elm2));
+        assert_eq!(Ok(ModRingElement::new(2)), Mod5::neg(&elm1));
+        assert_eq!(Ok(ModRingElement::new(2)), Mod5::div(&elm1, &elm2));
+    }
+
+    #[test]
+    fn mul_large_modulus() {
+        let elm = ModRingElement::<{ u64::MAX }>::new(u64::MAX - 1);
+
+        assert_eq!(Ok(ModRingElement::new(1)), ModRing::mul(&elm, &elm));
+    }
+
+    #[test]
+    fn inverse() {
+        assert_eq!(Ok(ModRingElement::new(3)), Mod5::inverse(&ModRingElement::new(2)));
+        assert_eq!(Ok(ModRingElement::new(7)), ModRing::<12>::inverse(&ModRingElement::new(7)));
+    }
+
+    #[test]
+    fn div_not_invertible() {
+        let res = ModRing::<4>::div(&ModRingElement::new(1), &ModRingElement::new(2));
+
+        assert_eq!(Err(RingError{message: "2 not invertible modulo 4".to_string(), kind: NotInRing { suggested_ring: None, remainder: None }}), res);
+    }
+
+    #[test]
+    fn div_zero() {
+        let res = Mod5::div(&ModRingElement::new(1), &ModRingElement::new(5));
+
+        assert_eq!(Err(RingError{message: "Division by zero".to_string(), kind: DivisionByZero}), res);
+    }
+
+    #[test]
+    fn parse_element() {
+        assert_eq!(Ok(ModRingElement::new(2)), Mod5::parse_element("42"));
+        assert_eq!(Ok(ModRingElement::new(3)), Mod5::parse_element("-42"));
+    }
+
+    #[test]
+    fn evaluate_expression() {
+        let expression = parse_mod_ring_expression::<5>("(2 + 3) * 4 + 3 * 4").expect("ok");
+
+        assert_eq!(Ok(ModRingElement::new(2)), expression.evaluate());
+    }
+
+    #[test]
+    fn evaluate_negative_and_division() {
+        let expression = parse_mod_ring_expression::<7>("-3 - 6 / 4").expect("ok");
+
+        assert_eq!(Ok(ModRingElement::new(6)), expression.evaluate());
+    }
+
+    #[test]
+    fn evaluate_not_invertible() {
+        let expression = parse_mod_ring_expression::<6>("5 + 1 / 3").expect("ok");
+
+        assert_eq!(Err(EvaluateExpressionError {
+            message: "3 not invertible modulo 6 during division".to_string(),
+            kind: NotInRing { suggested_ring: None, remainder: None },
+            position: Some(6),
+            span: Some(4..9),
+        }), expression.evaluate());
+    }
+}
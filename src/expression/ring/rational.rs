@@ -0,0 +1,355 @@
+use crate::expression::ring::{Ring, RingResult, RingElement, RingError, RingErrorKind, OrderedRing};
+use crate::expression::ring::intring::IntRing;
+use crate::expression::ExpressionComponent;
+use crate::expression::parser::ElementTokenRing;
+use std::fmt::{Display, Formatter};
+use std::cmp::Ordering;
+
+/// Rational number in lowest terms. The denominator is positive.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct RationalRingElement {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Display for RationalRingElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)?;
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<RationalRingElement> for String {
+    fn from(element: RationalRingElement) -> Self {
+        element.to_string()
+    }
+}
+
+impl RingElement for RationalRingElement {
+
+}
+
+impl PartialOrd for RationalRingElement {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RationalRingElement {
+    /// Compares the cross products, which do not overflow in `i128`, since the denominators are
+    /// positive.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.numerator as i128 * other.denominator as i128).cmp(&(other.numerator as i128 * self.denominator as i128))
+    }
+}
+
+impl RationalRingElement {
+    /// The rational number `numerator / denominator` reduced to lowest terms. Fails if the
+    /// denominator is zero, or if the reduced fraction cannot be represented, which is only the
+    /// case for `i64::MIN` with a negative denominator.
+    pub fn new(numerator: i64, denominator: i64) -> RingResult<RationalRingElement> {
+        RationalRingElement::reduce(numerator as i128, denominator as i128)
+    }
+
+    /// The integer `value`.
+    pub fn from_int(value: i64) -> RationalRingElement {
+        RationalRingElement {
+            numerator: value,
+            denominator: 1,
+        }
+    }
+
+    pub fn numerator(&self) -> i64 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i64 {
+        self.denominator
+    }
+
+    fn reduce(numerator: i128, denominator: i128) -> RingResult<RationalRingElement> {
+        if denominator == 0 {
+            return Err(RingError { message: "Division by zero".to_string(), kind: RingErrorKind::DivisionByZero });
+        }
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+        match (i64::try_from(numerator / divisor), i64::try_from(denominator / divisor)) {
+            (Ok(numerator), Ok(denominator)) => Ok(RationalRingElement { numerator, denominator }),
+            _ => Err(RingError { message: "Overflow".to_string(), kind: RingErrorKind::Overflow }),
+        }
+    }
+}
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.abs()
+}
+
+/// Field of rational numbers with numerator and denominator in the range of `i64`. Operations
+/// are computed exactly and fail if the result in lowest terms overflows.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct RationalRing {
+}
+
+impl Ring for RationalRing {
+    type RingElementType = RationalRingElement;
+
+    fn zero() -> Self::RingElementType {
+        RationalRingElement::from_int(0)
+    }
+
+    fn one() -> Self::RingElementType {
+        RationalRingElement::from_int(1)
+    }
+
+    /// Parse an integer or a fraction of integers, e.g. `-3` or `2/4`.
+    fn parse_element(str: &str) -> RingResult<Self::RingElementType> {
+        let invalid = |err: RingError| match err.kind {
+            RingErrorKind::Overflow => err,
+            _ => RingError { message: format!("Invalid rational number '{}'", str), kind: RingErrorKind::Unspecified },
+        };
+        match str.split_once('/') {
+            Some((numerator, denominator)) => {
+                let numerator = IntRing::parse_element(numerator).map_err(invalid)?.value();
+                let denominator = IntRing::parse_element(denominator).map_err(invalid)?.value();
+                RationalRingElement::new(numerator, denominator)
+            }
+            None => Ok(RationalRingElement::from_int(IntRing::parse_element(str).map_err(invalid)?.value())),
+        }
+    }
+
+    fn add(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        let (n1, d1, n2, d2) = RationalRing::widen(elm1, elm2);
+        RationalRingElement::reduce(n1 * d2 + n2 * d1, d1 * d2)
+    }
+
+    fn sub(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        let (n1, d1, n2, d2) = RationalRing::widen(elm1, elm2);
+        RationalRingElement::reduce(n1 * d2 - n2 * d1, d1 * d2)
+    }
+
+    fn mul(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        let (n1, d1, n2, d2) = RationalRing::widen(elm1, elm2);
+        RationalRingElement::reduce(n1 * n2, d1 * d2)
+    }
+
+    fn neg(elm: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        RationalRingElement::reduce(-(elm.numerator as i128), elm.denominator as i128)
+    }
+
+    fn div(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        let (n1, d1, n2, d2) = RationalRing::widen(elm1, elm2);
+        RationalRingElement::reduce(n1 * d2, d1 * n2)
+    }
+
+    /// Euclidean remainder, which is never negative: `x mod y = x - |y| * floor(x / |y|)`, e.g.
+    /// `7/2 mod 1 = 1/2` and `-1/2 mod 1 = 1/2`.
+    fn rem(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        let (n1, d1, n2, d2) = RationalRing::widen(elm1, elm2);
+        if n2 == 0 {
+            return Err(RingError { message: "Division by zero".to_string(), kind: RingErrorKind::DivisionByZero });
+        }
+        // the remainder of the numerators over the common denominator
+        RationalRingElement::reduce((n1 * d2).rem_euclid(n2 * d1), d1 * d2)
+    }
+
+    /// Fails unless the exponent is an integer, e.g. `4 ^ 1/2` fails. Negative exponents give
+    /// powers of the reciprocal: `2 ^ -2 = 1/4`.
+    fn pow(base: &Self::RingElementType, exp: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        if exp.denominator != 1 {
+            return Err(RingError {
+                message: "Exponent not an integer".to_string(),
+                kind: RingErrorKind::NotInRing { suggested_ring: None, remainder: None },
+            });
+        }
+        let magnitude = exp.numerator.unsigned_abs();
+        // numerator and denominator are coprime, hence so are their powers
+        let numerator = RationalRing::checked_pow(base.numerator, magnitude)?;
+        let denominator = RationalRing::checked_pow(base.denominator, magnitude)?;
+        if exp.numerator < 0 {
+            RationalRingElement::new(denominator, numerator)
+        } else {
+            RationalRingElement::new(numerator, denominator)
+        }
+    }
+
+    fn compare(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Ordering> {
+        Ok(elm1.cmp(elm2))
+    }
+}
+
+impl OrderedRing for RationalRing {
+}
+
+impl ElementTokenRing for RationalRing {
+    fn element_from_token(value: i64) -> RingResult<Self::RingElementType> {
+        Ok(RationalRingElement::from_int(value))
+    }
+}
+
+impl RationalRing {
+    /// Numerators and denominators widened such that sums of products cannot overflow.
+    fn widen(elm1: &RationalRingElement, elm2: &RationalRingElement) -> (i128, i128, i128, i128) {
+        (elm1.numerator as i128, elm1.denominator as i128, elm2.numerator as i128, elm2.denominator as i128)
+    }
+
+    /// `value` raised to the power `exp`. Powers of `0`, `1` and `-1` are exact for any exponent,
+    /// other powers overflow if `exp` does not fit in `u32`.
+    fn checked_pow(value: i64, exp: u64) -> RingResult<i64> {
+        match value {
+            0 => Ok(if exp == 0 { 1 } else { 0 }),
+            1 => Ok(1),
+            -1 => Ok(if exp.is_multiple_of(2) { 1 } else { -1 }),
+            _ => u32::try_from(exp).ok()
+                .and_then(|exp| value.checked_pow(exp))
+                .ok_or_else(|| RingError { message: "Overflow".to_string(), kind: RingErrorKind::Overflow }),
+        }
+    }
+}
+
+impl ExpressionComponent<RationalRing> {
+    /// Convert an [IntRing] expression to the same expression in the rationals.
+    pub fn from_int_ring_expression(expression: &ExpressionComponent<IntRing>) -> ExpressionComponent<RationalRing> {
+        expression.map_ring_elements(&|element| RationalRingElement::from_int(element.value()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::ring::rational::{RationalRingElement, RationalRing};
+    use crate::expression::ring::{Ring, RingError};
+    use crate::expression::ring::RingErrorKind::{Overflow, DivisionByZero, Unspecified};
+    use crate::expression::parser::{parse_rational_ring_expression, parse_int_ring_calculator_expression, parse_expression_with_options, ParseOptions};
+    use crate::expression::ExpressionComponent;
+
+    fn rational(numerator: i64, denominator: i64) -> RationalRingElement {
+        RationalRingElement::new(numerator, denominator).expect("ok")
+    }
+
+    #[test]
+    fn compare() {
+        assert!(rational(1, 3) < rational(1, 2));
+        assert!(rational(-1, 2) < rational(-1, 3));
+        assert!(rational(i64::MAX, 2) > rational(i64::MIN, 3));
+        assert_eq!(Ok(rational(1, 1)), parse_rational_ring_expression("2 / 3 > 3 / 5").expect("ok").evaluate());
+        assert_eq!(Ok(rational(0, 1)), parse_rational_ring_expression("2 / 4 == 1 / 3").expect("ok").evaluate());
+    }
+
+    #[test]
+    fn new_reduces() {
+        let elm = rational(6, -4);
+
+        assert_eq!(-3, elm.numerator());
+        assert_eq!(2, elm.denominator());
+    }
+
+    #[test]
+    fn new_zero_denominator() {
+        assert_eq!(Err(RingError{message: "Division by zero".to_string(), kind: DivisionByZero}), RationalRingElement::new(1, 0));
+    }
+
+    #[test]
+    fn operations() {
+        let elm1 = rational(1, 2);
+        let elm2 = rational(-2, 3);
+
+        assert_eq!(Ok(rational(-1, 6)), RationalRing::add(&elm1, &elm2));
+        assert_eq!(Ok(rational(7, 6)), RationalRing::sub(&elm1, &elm2));
+        assert_eq!(Ok(rational(-1, 3)), RationalRing::mul(&elm1, &elm2));
+        assert_eq!(Ok(rational(-3, 4)), RationalRing::div(&elm1, &elm2));
+        assert_eq!(Ok(rational(-1, 2)), RationalRing::neg(&elm1));
+    }
+
+    #[test]
+    fn rem() {
+        assert_eq!(Ok(rational(1, 2)), RationalRing::rem(&rational(7, 2), &rational(1, 1)));
+        assert_eq!(Ok(rational(1, 2)), RationalRing::rem(&rational(-1, 2), &rational(1, 1)));
+        assert_eq!(Ok(rational(1, 6)), RationalRing::rem(&rational(1, 2), &rational(-1, 3)));
+    }
+
+    #[test]
+    fn pow() {
+        assert_eq!(Ok(rational(-8, 27)), RationalRing::pow(&rational(-2, 3), &rational(3, 1)));
+        assert_eq!(Ok(rational(9, 4)), RationalRing::pow(&rational(-2, 3), &rational(-2, 1)));
+        assert!(RationalRing::pow(&rational(4, 1), &rational(1, 2)).is_err());
+        assert_eq!(Err(RingError{message: "Division by zero".to_string(), kind: DivisionByZero}),
+                   RationalRing::pow(&RationalRing::zero(), &rational(-1, 1)));
+    }
+
+    #[test]
+    fn pow_huge_exponent() {
+        assert_eq!(Ok(rational(1, 1)), RationalRing::pow(&rational(1, 1), &rational(5000000000, 1)));
+        assert_eq!(Ok(rational(1, 1)), RationalRing::pow(&rational(-1, 1), &rational(5000000000, 1)));
+        assert_eq!(Ok(rational(-1, 1)), RationalRing::pow(&rational(-1, 1), &rational(-5000000001, 1)));
+        assert_eq!(Ok(rational(0, 1)), RationalRing::pow(&rational(0, 1), &rational(5000000000, 1)));
+        assert_eq!(Err(RingError{message: "Division by zero".to_string(), kind: DivisionByZero}),
+                   RationalRing::pow(&rational(0, 1), &rational(-5000000000, 1)));
+        assert_eq!(Err(RingError{message: "Overflow".to_string(), kind: Overflow}),
+                   RationalRing::pow(&rational(1, 2), &rational(5000000000, 1)));
+    }
+
+    #[test]
+    fn div_zero() {
+        let res = RationalRing::div(&rational(1, 2), &RationalRing::zero());
+
+        assert_eq!(Err(RingError{message: "Division by zero".to_string(), kind: DivisionByZero}), res);
+    }
+
+    #[test]
+    fn mul_overflow() {
+        let res = RationalRing::mul(&rational(i64::MAX, 1), &rational(2, 1));
+
+        assert_eq!(Err(RingError{message: "Overflow".to_string(), kind: Overflow}), res);
+    }
+
+    #[test]
+    fn add_reduces_before_overflow() {
+        let elm = rational(1, i64::MAX);
+
+        assert_eq!(Ok(rational(2, i64::MAX)), RationalRing::add(&elm, &elm));
+    }
+
+    #[test]
+    fn parse_element() {
+        assert_eq!(Ok(rational(-3, 1)), RationalRing::parse_element("-3"));
+        assert_eq!(Ok(rational(1, 2)), RationalRing::parse_element("2/4"));
+        assert!(RationalRing::parse_element("1/0").is_err());
+        assert_eq!(Err(RingError{message: "Invalid rational number '6.5'".to_string(), kind: Unspecified}), RationalRing::parse_element("6.5"));
+        assert_eq!(Err(RingError{message: "Invalid rational number '1/x'".to_string(), kind: Unspecified}), RationalRing::parse_element("1/x"));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("-1/2", rational(1, -2).to_string());
+        assert_eq!("3", rational(6, 2).to_string());
+    }
+
+    #[test]
+    fn evaluate_expression() {
+        let expression = parse_rational_ring_expression("1 / 3 + 1 / 6").expect("ok");
+
+        assert_eq!(Ok(rational(1, 2)), expression.evaluate());
+    }
+
+    #[test]
+    fn evaluate_percent() {
+        let calculator_mode = ParseOptions { calculator_mode: true, ..ParseOptions::default() };
+        let expression = parse_expression_with_options::<RationalRing>("50%", &calculator_mode).expect("ok");
+
+        assert_eq!(Ok(rational(1, 2)), expression.evaluate());
+        assert_eq!(Ok(rational(1, 1)), parse_expression_with_options::<RationalRing>("50% == 1/2", &calculator_mode).expect("ok").evaluate());
+        assert!(parse_rational_ring_expression("50%").is_err());
+    }
+
+    #[test]
+    fn evaluate_converted_percent() {
+        let expression = parse_int_ring_calculator_expression("50%").expect("ok");
+
+        assert_eq!(Ok(rational(1, 2)), ExpressionComponent::<RationalRing>::from_int_ring_expression(&expression).evaluate());
+    }
+}
@@ -0,0 +1,247 @@
+use crate::expression::ring::{Ring, RingResult, RingElement, RingError, RingErrorKind, DivisionRemainder};
+use std::num::IntErrorKind;
+use std::fmt::{Display, Formatter};
+use crate::expression::parser::ElementTokenRing;
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct UintRingElement {
+    value: u64
+}
+
+impl Display for UintRingElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value)?;
+        Ok(())
+    }
+}
+
+impl RingElement for UintRingElement {
+
+}
+
+impl UintRingElement {
+    pub fn new(value: u64) -> UintRingElement {
+        UintRingElement {
+            value
+        }
+    }
+}
+
+/// Nonnegative integers. Not strictly a ring since subtraction fails if the result would be
+/// negative, instead of wrapping.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct UintRing {
+}
+
+impl Ring for UintRing {
+    type RingElementType = UintRingElement;
+
+    fn zero() -> Self::RingElementType {
+        UintRingElement::new(0)
+    }
+
+    fn one() -> Self::RingElementType {
+        UintRingElement::new(1)
+    }
+
+    fn parse_element(str: &str) -> RingResult<Self::RingElementType> {
+        str.parse().map(UintRingElement::new).map_err(|err| match err.kind() {
+            IntErrorKind::PosOverflow => RingError { message: "Overflow".to_string(), kind: RingErrorKind::Overflow },
+            _ => RingError { message: format!("Invalid nonnegative integer '{}'", str), kind: RingErrorKind::Unspecified },
+        })
+    }
+
+    fn add(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        UintRing::ring_result(elm1.value.checked_add(elm2.value), "Overflow")
+    }
+
+    fn sub(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        UintRing::ring_result(elm1.value.checked_sub(elm2.value), "Underflow")
+    }
+
+    fn mul(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        UintRing::ring_result(elm1.value.checked_mul(elm2.value), "Overflow")
+    }
+
+    fn div(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        UintRing::check_divisor(elm2, "Division by zero")?;
+        let d = elm1.value % elm2.value;
+        if d != 0 {
+            let remainder = DivisionRemainder {
+                quotient: (elm1.value / elm2.value).to_string(),
+                remainder: d.to_string(),
+            };
+            return Err(RingError {
+                message: "Result not in ring".to_string(),
+                kind: RingErrorKind::NotInRing { suggested_ring: None, remainder: Some(Box::new(remainder)) },
+            });
+        }
+        Ok(UintRingElement::new(elm1.value / elm2.value))
+    }
+
+    fn rem(elm1: &Self::RingElementType, elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        UintRing::check_divisor(elm2, "Modulo by zero")?;
+        Ok(UintRingElement::new(elm1.value % elm2.value))
+    }
+
+    /// Powers of `0` and `1` are exact for any exponent. Other powers overflow if the exponent
+    /// does not fit in `u32`.
+    fn pow(base: &Self::RingElementType, exp: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        match base.value {
+            0 | 1 if exp.value != 0 => Ok(base.clone()),
+            _ => UintRing::ring_result(u32::try_from(exp.value).ok().and_then(|exp| base.value.checked_pow(exp)), "Overflow"),
+        }
+    }
+}
+
+impl ElementTokenRing for UintRing {
+    fn element_from_token(value: i64) -> RingResult<Self::RingElementType> {
+        u64::try_from(value).map(UintRingElement::new).map_err(|_| RingError {
+            message: format!("Negative number {} not in ring", value),
+            kind: RingErrorKind::NotInRing { suggested_ring: None, remainder: None },
+        })
+    }
+}
+
+impl UintRing {
+    fn ring_result(res: Option<u64>, error_message: &str) -> Result<UintRingElement, RingError> {
+        match res {
+            Some(val) => Ok(UintRingElement::new(val)),
+            None => Err(RingError { message: error_message.to_string(), kind: RingErrorKind::Overflow }),
+        }
+    }
+
+    fn check_divisor(divisor: &UintRingElement, error_message: &str) -> RingResult<()> {
+        if divisor.value == 0 {
+            Err(RingError { message: error_message.to_string(), kind: RingErrorKind::DivisionByZero })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::ring::uint::{UintRingElement, UintRing};
+    use crate::expression::ring::{Ring, RingError, DivisionRemainder};
+    use crate::expression::ring::RingErrorKind::{Overflow, NotInRing, DivisionByZero};
+    use crate::expression::parser::{parse_expression, ParseExpressionError};
+    use crate::expression::parser::ParseExpressionErrorKind::InvalidLiteral;
+    use crate::expression::{ExpressionComponent, EvaluateExpressionError};
+
+    #[test]
+    fn add() {
+        let elm1 = UintRingElement::new(5);
+        let elm2 = UintRingElement::new(3);
+
+        let res = UintRing::add(&elm1, &elm2);
+
+        assert_eq!(Ok(UintRingElement::new(8)), res);
+    }
+
+    #[test]
+    fn add_overflow() {
+        let elm1 = UintRingElement::new(u64::MAX);
+        let elm2 = UintRingElement::new(1);
+
+        let res = UintRing::add(&elm1, &elm2);
+
+        assert_eq!(Err(RingError{message: "Overflow".to_string(), kind: Overflow}), res);
+    }
+
+    #[test]
+    fn sub() {
+        let elm1 = UintRingElement::new(5);
+        let elm2 = UintRingElement::new(3);
+
+        let res = UintRing::sub(&elm1, &elm2);
+
+        assert_eq!(Ok(UintRingElement::new(2)), res);
+    }
+
+    #[test]
+    fn sub_underflow() {
+        let elm1 = UintRingElement::new(3);
+        let elm2 = UintRingElement::new(5);
+
+        let res = UintRing::sub(&elm1, &elm2);
+
+        assert_eq!(Err(RingError{message: "Underflow".to_string(), kind: Overflow}), res);
+    }
+
+    #[test]
+    fn mul_overflow() {
+        let elm1 = UintRingElement::new(u64::MAX);
+        let elm2 = UintRingElement::new(2);
+
+        let res = UintRing::mul(&elm1, &elm2);
+
+        assert_eq!(Err(RingError{message: "Overflow".to_string(), kind: Overflow}), res);
+    }
+
+    #[test]
+    fn div() {
+        let elm1 = UintRingElement::new(6);
+        let elm2 = UintRingElement::new(2);
+
+        let res = UintRing::div(&elm1, &elm2);
+
+        assert_eq!(Ok(UintRingElement::new(3)), res);
+    }
+
+    #[test]
+    fn div_not_int() {
+        let elm1 = UintRingElement::new(5);
+        let elm2 = UintRingElement::new(2);
+
+        let res = UintRing::div(&elm1, &elm2);
+
+        assert_eq!(Err(RingError{message: "Result not in ring".to_string(), kind: NotInRing { suggested_ring: None, remainder: Some(Box::new(DivisionRemainder { quotient: "2".to_string(), remainder: "1".to_string() })) }}), res);
+    }
+
+    #[test]
+    fn div_zero() {
+        let res = UintRing::div(&UintRingElement::new(5), &UintRingElement::new(0));
+
+        assert_eq!(Err(RingError{message: "Division by zero".to_string(), kind: DivisionByZero}), res);
+    }
+
+    #[test]
+    fn rem_zero() {
+        let res = UintRing::rem(&UintRingElement::new(5), &UintRingElement::new(0));
+
+        assert_eq!(Err(RingError{message: "Modulo by zero".to_string(), kind: DivisionByZero}), res);
+    }
+
+    #[test]
+    fn pow() {
+        assert_eq!(Ok(UintRingElement::new(81)), UintRing::pow(&UintRingElement::new(3), &UintRingElement::new(4)));
+        assert_eq!(Err(RingError{message: "Overflow".to_string(), kind: Overflow}),
+                   UintRing::pow(&UintRingElement::new(2), &UintRingElement::new(64)));
+    }
+
+    #[test]
+    fn pow_huge_exponent() {
+        assert_eq!(Ok(UintRingElement::new(1)), UintRing::pow(&UintRingElement::new(1), &UintRingElement::new(5000000000)));
+        assert_eq!(Ok(UintRingElement::new(0)), UintRing::pow(&UintRingElement::new(0), &UintRingElement::new(5000000000)));
+        assert_eq!(Ok(UintRingElement::new(1)), UintRing::pow(&UintRingElement::new(0), &UintRingElement::new(0)));
+        assert_eq!(Err(RingError{message: "Overflow".to_string(), kind: Overflow}),
+                   UintRing::pow(&UintRingElement::new(2), &UintRingElement::new(5000000000)));
+    }
+
+    #[test]
+    fn parse_expression_in_ring() {
+        let expression: ExpressionComponent<UintRing> = parse_expression("2 * (7 - 3) mod 5").expect("ok");
+
+        assert_eq!(Ok(UintRingElement::new(3)), expression.evaluate());
+    }
+
+    #[test]
+    fn parse_expression_negative_literal() {
+        assert_eq!(Err(ParseExpressionError{message: "Negative number -3 not in ring".to_string(), position: 6, kind: InvalidLiteral}),
+                   parse_expression::<UintRing>("2 * (-3)"));
+
+        let expression: ExpressionComponent<UintRing> = parse_expression("2 - 3").expect("ok");
+        assert_eq!(Err(EvaluateExpressionError{message: "Underflow during subtraction".to_string(), kind: Overflow, position: Some(2), span: Some(0..5)}), expression.evaluate());
+    }
+}
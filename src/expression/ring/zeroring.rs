@@ -0,0 +1,135 @@
+use crate::expression::ring::{Ring, RingResult, RingElement, RingError, RingErrorKind};
+use std::fmt::{Display, Formatter};
+
+/// The single element `0` of the [ZeroRing].
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ZeroRingElement {
+}
+
+impl Display for ZeroRingElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("0")
+    }
+}
+
+impl RingElement for ZeroRingElement {
+
+}
+
+impl ZeroRingElement {
+    pub fn new() -> ZeroRingElement {
+        ZeroRingElement {}
+    }
+}
+
+impl Default for ZeroRingElement {
+    fn default() -> Self {
+        ZeroRingElement::new()
+    }
+}
+
+/// The trivial ring where the only element is `0`, hence `0 = 1`. Every operation returns `0`,
+/// including division since `0 = 0 * 0`. The ring is degenerate and intended for testing that
+/// generic algorithms do not depend on assumptions like `1 != 0`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ZeroRing {
+}
+
+impl Ring for ZeroRing {
+    type RingElementType = ZeroRingElement;
+
+    fn zero() -> Self::RingElementType {
+        ZeroRingElement::new()
+    }
+
+    fn one() -> Self::RingElementType {
+        ZeroRingElement::new()
+    }
+
+    fn parse_element(str: &str) -> RingResult<Self::RingElementType> {
+        if str == "0" {
+            Ok(ZeroRingElement::new())
+        } else {
+            Err(RingError { message: format!("Invalid element '{}'", str), kind: RingErrorKind::Unspecified })
+        }
+    }
+
+    fn add(_elm1: &Self::RingElementType, _elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(ZeroRingElement::new())
+    }
+
+    fn sub(_elm1: &Self::RingElementType, _elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(ZeroRingElement::new())
+    }
+
+    fn mul(_elm1: &Self::RingElementType, _elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(ZeroRingElement::new())
+    }
+
+    fn div(_elm1: &Self::RingElementType, _elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(ZeroRingElement::new())
+    }
+
+    fn rem(_elm1: &Self::RingElementType, _elm2: &Self::RingElementType) -> RingResult<Self::RingElementType> {
+        Ok(ZeroRingElement::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::ring::zeroring::{ZeroRingElement, ZeroRing};
+    use crate::expression::ring::Ring;
+    use crate::expression::ExpressionComponent;
+
+    #[test]
+    fn operations() {
+        let zero = ZeroRingElement::new();
+
+        assert_eq!(Ok(zero.clone()), ZeroRing::add(&zero, &zero));
+        assert_eq!(Ok(zero.clone()), ZeroRing::sub(&zero, &zero));
+        assert_eq!(Ok(zero.clone()), ZeroRing::mul(&zero, &zero));
+        assert_eq!(Ok(zero.clone()), ZeroRing::div(&zero, &zero));
+    }
+
+    #[test]
+    fn evaluate_expression() {
+        let zero = || ExpressionComponent::<ZeroRing>::new_ring_element(ZeroRingElement::new());
+        let expression = ExpressionComponent::new_division(
+            ExpressionComponent::new_parenteses(
+                ExpressionComponent::new_addition(zero(), zero())),
+            ExpressionComponent::new_multiplication(
+                zero(),
+                ExpressionComponent::new_subtraction(zero(), zero())));
+
+        assert_eq!(Ok(ZeroRingElement::new()), expression.evaluate());
+    }
+
+    #[test]
+    fn simplify_where_one_is_zero() {
+        let zero = || ExpressionComponent::<ZeroRing>::new_ring_element(ZeroRingElement::new());
+        let variable = |name: &str| ExpressionComponent::<ZeroRing>::new_variable(name.to_string());
+        // the literal 0 is also the literal 1, and x * 0 simplifies to 0 rather than to x
+        let expression = ExpressionComponent::new_addition(
+            ExpressionComponent::new_parenteses(
+                ExpressionComponent::new_multiplication(variable("x"), zero())),
+            ExpressionComponent::new_multiplication(
+                zero(),
+                ExpressionComponent::new_subtraction(variable("y"), zero())));
+
+        let simplified_identities = expression.simplify_identities();
+        let simplified = expression.simplify();
+
+        assert_eq!(zero(), simplified_identities);
+        assert_eq!(zero(), simplified);
+        assert_eq!(simplified_identities, simplified_identities.simplify_identities());
+        assert_eq!(simplified, simplified.simplify());
+        assert_eq!(Ok(ZeroRingElement::new()), simplified.evaluate());
+        assert_eq!(zero(), ExpressionComponent::new_multiplication(variable("x"), zero()).simplify());
+        assert_eq!(zero(), ExpressionComponent::new_multiplication(zero(), variable("x")).simplify());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("0", ZeroRingElement::new().to_string());
+    }
+}
@@ -0,0 +1,286 @@
+use crate::expression::{ExpressionComponent, Operator};
+use crate::expression::ExpressionComponent::{RingElement, Variable, FunctionCall, Addition, Subtraction, Multiplication, Division, Modulo, Power, LessThan, GreaterThan, Equal, Conditional, Parentheses, UnaryMinus};
+use crate::expression::ring::Ring;
+
+/// Hooks called by [ExpressionComponent::visit] for the nodes of an expression. `depth` is the
+/// depth of the node, which is 1 for the whole expression. All hooks default to doing nothing,
+/// such that visitors only implement the hooks for the nodes they are interested in.
+pub trait ExpressionVisitor<R: Ring> {
+    /// Called for every node, before the hook for the variant of the node.
+    fn visit_node(&mut self, _expression: &ExpressionComponent<R>, _depth: usize) {}
+
+    fn visit_ring_element(&mut self, _element: &R::RingElementType, _depth: usize) {}
+
+    fn visit_variable(&mut self, _name: &str, _depth: usize) {}
+
+    /// Called before the arguments of the function call are visited.
+    fn visit_function_call(&mut self, _name: &str, _arguments: usize, _depth: usize) {}
+
+    fn visit_parentheses(&mut self, _depth: usize) {}
+
+    fn visit_unary_minus(&mut self, _depth: usize) {}
+
+    /// Called before the condition and the branches of the conditional are visited.
+    fn visit_conditional(&mut self, _depth: usize) {}
+
+    /// Called for the binary operations [Addition], [Subtraction], [Multiplication], [Division],
+    /// [Modulo], [Power], [LessThan], [GreaterThan] and [Equal], before their operands are visited.
+    fn visit_binary_operation(&mut self, _operator: Operator, _depth: usize) {}
+}
+
+struct NodeCount {
+    count: usize,
+}
+
+impl<R: Ring> ExpressionVisitor<R> for NodeCount {
+    fn visit_node(&mut self, _expression: &ExpressionComponent<R>, _depth: usize) {
+        self.count += 1;
+    }
+}
+
+struct MaxDepth {
+    depth: usize,
+}
+
+impl<R: Ring> ExpressionVisitor<R> for MaxDepth {
+    fn visit_node(&mut self, _expression: &ExpressionComponent<R>, depth: usize) {
+        self.depth = self.depth.max(depth);
+    }
+}
+
+struct OperatorPositions {
+    position: Option<usize>,
+    operators: Vec<(Operator, usize)>,
+}
+
+impl<R: Ring> ExpressionVisitor<R> for OperatorPositions {
+    fn visit_node(&mut self, expression: &ExpressionComponent<R>, _depth: usize) {
+        self.position = expression.position().position;
+    }
+
+    fn visit_binary_operation(&mut self, operator: Operator, _depth: usize) {
+        if let Some(position) = self.position {
+            self.operators.push((operator, position));
+        }
+    }
+}
+
+/// Iterator over the subexpressions of an expression in pre-order, see [ExpressionComponent::iter].
+pub struct Iter<'a, R: Ring> {
+    subexpressions: Vec<&'a ExpressionComponent<R>>,
+}
+
+impl<'a, R: Ring> Iterator for Iter<'a, R> {
+    type Item = &'a ExpressionComponent<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let expr = self.subexpressions.pop()?;
+        match expr {
+            RingElement(_) | Variable(_) => (),
+            FunctionCall { args, .. } => self.subexpressions.extend(args.iter().rev()),
+            Parentheses { inner, .. } | UnaryMinus { inner, .. } => self.subexpressions.push(inner),
+            Conditional { condition, then, otherwise, .. } => {
+                self.subexpressions.push(otherwise);
+                self.subexpressions.push(then);
+                self.subexpressions.push(condition);
+            }
+            Addition { left, right, .. }
+            | Subtraction { left, right, .. }
+            | Multiplication { left, right, .. }
+            | Division { left, right, .. }
+            | Modulo { left, right, .. }
+            | Power { left, right, .. }
+            | LessThan { left, right, .. }
+            | GreaterThan { left, right, .. }
+            | Equal { left, right, .. } => {
+                self.subexpressions.push(right);
+                self.subexpressions.push(left);
+            }
+        }
+        Some(expr)
+    }
+}
+
+impl<R: Ring> ExpressionComponent<R> {
+    /// Iterate over the expression and all its subexpressions in pre-order, in the same order as
+    /// [visit](Self::visit), without recursion. The first item is the expression itself.
+    pub fn iter(&self) -> Iter<'_, R> {
+        Iter { subexpressions: vec![self] }
+    }
+
+    /// Visit the nodes of the expression in pre-order, without recursion: each node is visited
+    /// before its operands, and operands and function arguments are visited from left to right.
+    pub fn visit<V: ExpressionVisitor<R>>(&self, visitor: &mut V) {
+        let mut subexpressions = vec![(self, 1)];
+        while let Some((expr, depth)) = subexpressions.pop() {
+            visitor.visit_node(expr, depth);
+            let (operator, left, right) = match expr {
+                RingElement(element) => {
+                    visitor.visit_ring_element(element, depth);
+                    continue;
+                }
+                Variable(name) => {
+                    visitor.visit_variable(name, depth);
+                    continue;
+                }
+                FunctionCall { name, args, .. } => {
+                    visitor.visit_function_call(name, args.len(), depth);
+                    subexpressions.extend(args.iter().rev().map(|arg| (arg, depth + 1)));
+                    continue;
+                }
+                Parentheses { inner, .. } => {
+                    visitor.visit_parentheses(depth);
+                    subexpressions.push((inner, depth + 1));
+                    continue;
+                }
+                UnaryMinus { inner, .. } => {
+                    visitor.visit_unary_minus(depth);
+                    subexpressions.push((inner, depth + 1));
+                    continue;
+                }
+                Conditional { condition, then, otherwise, .. } => {
+                    visitor.visit_conditional(depth);
+                    subexpressions.push((otherwise, depth + 1));
+                    subexpressions.push((then, depth + 1));
+                    subexpressions.push((condition, depth + 1));
+                    continue;
+                }
+                Addition { left, right, .. } => (Operator::Addition, left, right),
+                Subtraction { left, right, .. } => (Operator::Subtraction, left, right),
+                Multiplication { left, right, .. } => (Operator::Multiplication, left, right),
+                Division { left, right, .. } => (Operator::Division, left, right),
+                Modulo { left, right, .. } => (Operator::Modulo, left, right),
+                Power { left, right, .. } => (Operator::Power, left, right),
+                LessThan { left, right, .. } => (Operator::LessThan, left, right),
+                GreaterThan { left, right, .. } => (Operator::GreaterThan, left, right),
+                Equal { left, right, .. } => (Operator::Equal, left, right),
+            };
+            visitor.visit_binary_operation(operator, depth);
+            subexpressions.push((right, depth + 1));
+            subexpressions.push((left, depth + 1));
+        }
+    }
+
+    /// Number of nodes in the expression, including parentheses.
+    pub fn node_count(&self) -> usize {
+        let mut visitor = NodeCount { count: 0 };
+        self.visit(&mut visitor);
+        visitor.count
+    }
+
+    /// Number of levels of nesting in the expression, without recursion. A single ring element or
+    /// variable has depth 1, and parentheses count as a level.
+    pub fn depth(&self) -> usize {
+        let mut visitor = MaxDepth { depth: 0 };
+        self.visit(&mut visitor);
+        visitor.depth
+    }
+
+    /// Binary operators of the expression with their source positions, ordered by position.
+    /// Operators without a source position, e.g. in constructed expressions, are left out.
+    pub fn operator_positions(&self) -> Vec<(Operator, usize)> {
+        let mut visitor = OperatorPositions { position: None, operators: Vec::new() };
+        self.visit(&mut visitor);
+        visitor.operators.sort_by_key(|&(_, position)| position);
+        visitor.operators
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::parser::parse_int_ring_expression;
+    use crate::expression::ring::intring::{IntRing, IntRingElement};
+    use crate::expression::visit::ExpressionVisitor;
+    use crate::expression::Operator;
+    use crate::expression::ExpressionComponent::Division;
+
+    struct Literals {
+        values: Vec<i64>,
+    }
+
+    impl ExpressionVisitor<IntRing> for Literals {
+        fn visit_ring_element(&mut self, element: &IntRingElement, _depth: usize) {
+            self.values.push(element.value());
+        }
+    }
+
+    struct Trace {
+        nodes: Vec<(String, usize)>,
+    }
+
+    impl ExpressionVisitor<IntRing> for Trace {
+        fn visit_ring_element(&mut self, element: &IntRingElement, depth: usize) {
+            self.nodes.push((element.to_string(), depth));
+        }
+
+        fn visit_variable(&mut self, name: &str, depth: usize) {
+            self.nodes.push((name.to_string(), depth));
+        }
+
+        fn visit_function_call(&mut self, name: &str, arguments: usize, depth: usize) {
+            self.nodes.push((format!("{}/{}", name, arguments), depth));
+        }
+
+        fn visit_parentheses(&mut self, depth: usize) {
+            self.nodes.push(("()".to_string(), depth));
+        }
+
+        fn visit_unary_minus(&mut self, depth: usize) {
+            self.nodes.push(("neg".to_string(), depth));
+        }
+
+        fn visit_binary_operation(&mut self, operator: Operator, depth: usize) {
+            self.nodes.push((operator.name().to_string(), depth));
+        }
+    }
+
+    #[test]
+    fn node_count_and_depth() {
+        let expression = parse_int_ring_expression("2 + 3 * 4").expect("ok");
+
+        assert_eq!(5, expression.node_count());
+        assert_eq!(3, expression.depth());
+    }
+
+    #[test]
+    fn collect_literals() {
+        let expression = parse_int_ring_expression("(2 + x) * -3 - gcd(4, 5 ^ 6)").expect("ok");
+        let mut visitor = Literals { values: Vec::new() };
+
+        expression.visit(&mut visitor);
+
+        assert_eq!(vec![2, -3, 4, 5, 6], visitor.values);
+    }
+
+    #[test]
+    fn iter() {
+        let expression = parse_int_ring_expression("(1 + x) / 2 - gcd(3 / 4, -5)").expect("ok");
+
+        let printed: Vec<_> = expression.iter().map(|expr| expr.to_string()).collect();
+
+        assert_eq!(vec!["(1 + x) / 2 - gcd(3 / 4, -5)", "(1 + x) / 2", "(1 + x)", "1 + x", "1", "x", "2", "gcd(3 / 4, -5)", "3 / 4", "3", "4", "-5"], printed);
+        assert_eq!(expression.node_count(), expression.iter().count());
+        assert_eq!(2, expression.iter().filter(|expr| matches!(expr, Division { .. })).count());
+        assert!(std::ptr::eq(&expression, expression.iter().next().expect("some")));
+    }
+
+    #[test]
+    fn operator_positions() {
+        let expression = parse_int_ring_expression("2 + 5 * 1").expect("ok");
+
+        assert_eq!(vec![(Operator::Addition, 2), (Operator::Multiplication, 6)], expression.operator_positions());
+        assert_eq!(vec![(Operator::Multiplication, 1), (Operator::Addition, 3), (Operator::Power, 6)],
+                   parse_int_ring_expression("2*3+(4^5)").expect("ok").operator_positions());
+    }
+
+    #[test]
+    fn pre_order() {
+        let expression = parse_int_ring_expression("-(x) * abs(2 - 1)").expect("ok");
+        let mut visitor = Trace { nodes: Vec::new() };
+
+        expression.visit(&mut visitor);
+
+        let nodes: Vec<_> = visitor.nodes.iter().map(|(node, depth)| (node.as_str(), *depth)).collect();
+        assert_eq!(vec![("multiplication", 1), ("neg", 2), ("()", 3), ("x", 4), ("abs/1", 2), ("subtraction", 3), ("2", 4), ("1", 4)], nodes);
+    }
+}
@@ -1,2 +1,5 @@
 pub mod token;
-pub mod expression;
\ No newline at end of file
+pub mod expression;
+pub mod error;
+pub mod evaluate;
+pub mod cli;
\ No newline at end of file
@@ -1,10 +1,15 @@
 
 use std::iter::{Peekable, Enumerate};
+use std::ops::Range;
 use std::str::Chars;
 use core::result;
-use std::error;
+use std::{error, str};
 use std::fmt::{Display, Formatter, Debug};
 use std::hash::Hash;
+use std::io::BufRead;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::cell::{Cell, RefCell};
 
 pub mod intring;
 
@@ -37,53 +42,345 @@ pub trait TokenParser {
         &self,
         char_iterator: &mut Peekable<I>) -> TokenResult<Self::TokenType>;
 
+    /// Char starting a comment that extends to the end of the line and is skipped like
+    /// whitespace. Defaults to no comments.
+    fn comment_start(&self) -> Option<char> {
+        None
+    }
 }
 
+/// Token with the char offsets in the input where it starts, `position`, and ends, `end`. The end
+/// is exclusive, so the token covers [span](TokenWithPos::span).
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub struct TokenWithPos<T: Token> {
     pub token: T,
-    pub position: usize
+    pub position: usize,
+    pub end: usize,
+}
+
+impl<T: Token> TokenWithPos<T> {
+    /// Range of char offsets covered by the token.
+    pub fn span(&self) -> Range<usize> {
+        self.position..self.end
+    }
 }
 
 /// A token iterator based on a string input and a [TokenParser]
 pub struct TokenIterator<T: Token, I: Iterator<Item=(usize, char)>, G: TokenParser<TokenType=T>> {
-    char_iterator: Peekable<I>,
+    char_iterator: Peekable<CharsWithEnd<I>>,
+    input_end: Rc<Cell<usize>>,
     token_generator: G
 }
 
 impl<T: Token, G: TokenParser<TokenType=T>> TokenIterator<T, Enumerate<Chars<'_>>, G> {
     pub fn new(str: &impl AsRef<str>, token_generator: G) -> TokenIterator<T, Enumerate<Chars<'_>>, G> {
+        let (char_iterator, input_end) = CharsWithEnd::new(str.as_ref().chars().enumerate());
         TokenIterator {
-            char_iterator: str.as_ref().chars().enumerate().peekable(),
+            char_iterator: char_iterator.peekable(),
+            input_end,
             token_generator
         }
     }
 }
 
+impl<T: Token, I: Iterator<Item=(usize, char)>, G: TokenParser<TokenType=T>> TokenIterator<T, I, G> {
+    /// Token stream with lookahead over the tokens, see [TokenStream].
+    pub fn peekable_tokens(self) -> TokenStream<Self> {
+        TokenStream::new(self)
+    }
+}
+
 impl<T: Token, I: Iterator<Item=(usize, char)>, G: TokenParser<TokenType=T>> Iterator
 for TokenIterator<T, I, G> {
     type Item = TokenResult<TokenWithPos<T>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while self.char_iterator.next_if(|c| c.1.is_whitespace()).is_some() {}
+        next_token(&mut self.char_iterator, &self.input_end, &self.token_generator)
+    }
+}
+
+/// Stream of tokens, or token errors, that can be peeked and rewound, such that a parser can look
+/// ahead while consuming the tokens lazily. Tokens given back with [TokenStream::push_back] are
+/// returned again, last pushed first, before the remaining tokens.
+pub struct TokenStream<I: Iterator> {
+    tokens: I,
+    /// Tokens read from `tokens` or pushed back, but not yet returned. The next token is last.
+    lookahead: Vec<I::Item>,
+}
+
+impl<I: Iterator> TokenStream<I> {
+    pub fn new(tokens: I) -> TokenStream<I> {
+        TokenStream {
+            tokens,
+            lookahead: Vec::new(),
+        }
+    }
+
+    /// The next token without consuming it.
+    pub fn peek(&mut self) -> Option<&I::Item> {
+        if self.lookahead.is_empty() {
+            self.lookahead.extend(self.tokens.next());
+        }
+        self.lookahead.last()
+    }
+
+    /// Give back `token`, such that it is the next token returned.
+    pub fn push_back(&mut self, token: I::Item) {
+        self.lookahead.push(token);
+    }
+}
+
+impl<I: Iterator> Iterator for TokenStream<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.lookahead.pop().or_else(|| self.tokens.next())
+    }
+}
+
+/// Char iterator recording the offset after the last char yielded, which is the end of the input
+/// once the iterator is exhausted.
+struct CharsWithEnd<I: Iterator<Item=(usize, char)>> {
+    chars: I,
+    end: Rc<Cell<usize>>,
+}
+
+impl<I: Iterator<Item=(usize, char)>> CharsWithEnd<I> {
+    fn new(chars: I) -> (CharsWithEnd<I>, Rc<Cell<usize>>) {
+        let end = Rc::new(Cell::new(0));
+        (CharsWithEnd { chars, end: end.clone() }, end)
+    }
+}
+
+impl<I: Iterator<Item=(usize, char)>> Iterator for CharsWithEnd<I> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.chars.next();
+        if let Some((position, _)) = next {
+            self.end.set(position + 1);
+        }
+        next
+    }
+}
+
+/// Skip whitespace and read the next token from `char_iterator`, if there are more characters.
+/// The token ends where the next char starts, or at `input_end` if there are no more chars.
+fn next_token<I: Iterator<Item=(usize, char)>, G: TokenParser>(
+    char_iterator: &mut Peekable<I>,
+    input_end: &Cell<usize>,
+    token_generator: &G) -> Option<TokenResult<TokenWithPos<G::TokenType>>>
+{
+    loop {
+        while char_iterator.next_if(|c| c.1.is_whitespace()).is_some() {}
+        let comment_start = token_generator.comment_start();
+        if char_iterator.next_if(|c| Some(c.1) == comment_start).is_none() {
+            break;
+        }
+        while char_iterator.next_if(|c| c.1 != '\n').is_some() {}
+    }
+
+    let position = char_iterator.peek()?.0;
+    Some(
+        match token_generator.read_next_token(char_iterator) {
+            Ok(token) => {
+                let end = char_iterator.peek().map_or_else(|| input_end.get(), |c| c.0);
+                Ok(TokenWithPos{token, position, end})
+            }
+            Err(err) => Err(err),
+        }
+    )
+}
+
+/// Tokenize the input read from `read` without reading all of it into memory. Positions are
+/// char offsets, as for [TokenIterator]. Tokens may span the buffer refills of `read`.
+pub fn tokenize<R: BufRead, G: TokenParser>(read: R, token_generator: G) -> ReaderTokenIterator<R, G> {
+    let error = Rc::new(RefCell::new(None));
+    let (char_iterator, input_end) = CharsWithEnd::new(ReadChars {
+        read,
+        chars: VecDeque::new(),
+        incomplete: Vec::new(),
+        position: 0,
+        done: false,
+        error: error.clone(),
+    });
+    ReaderTokenIterator {
+        char_iterator: char_iterator.peekable(),
+        input_end,
+        token_generator,
+        error,
+    }
+}
+
+/// A token iterator based on a [BufRead] input and a [TokenParser], see [tokenize]. An I/O
+/// error or invalid UTF-8 in the input is returned as a [TokenError] at the position where
+/// reading stopped, after which the iterator ends.
+pub struct ReaderTokenIterator<R: BufRead, G: TokenParser> {
+    char_iterator: Peekable<CharsWithEnd<ReadChars<R>>>,
+    input_end: Rc<Cell<usize>>,
+    token_generator: G,
+    error: Rc<RefCell<Option<TokenError>>>,
+}
+
+impl<R: BufRead, G: TokenParser> Iterator for ReaderTokenIterator<R, G> {
+    type Item = TokenResult<TokenWithPos<G::TokenType>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_token(&mut self.char_iterator, &self.input_end, &self.token_generator)
+            .or_else(|| self.error.borrow_mut().take().map(Err))
+    }
+}
 
-        if self.char_iterator.peek().is_none() {
-            return None
+/// Chars read from a [BufRead] together with their char offsets. Stops at the first I/O error or
+/// invalid UTF-8, which is then stored in `error`.
+struct ReadChars<R: BufRead> {
+    read: R,
+    chars: VecDeque<char>,
+    /// Bytes of a char split across buffer refills.
+    incomplete: Vec<u8>,
+    position: usize,
+    /// Whether the end of the input or an error is reached.
+    done: bool,
+    error: Rc<RefCell<Option<TokenError>>>,
+}
+
+impl<R: BufRead> ReadChars<R> {
+    /// Read and decode the next buffer. Returns `false` at the end of the input or on error.
+    fn fill_chars(&mut self) -> bool {
+        if self.done {
+            return false;
+        }
+        let buffer = match self.read.fill_buf() {
+            Ok(buffer) => buffer,
+            Err(err) => return self.fail(err.to_string()),
+        };
+        if buffer.is_empty() {
+            return if self.incomplete.is_empty() {
+                self.done = true;
+                false
+            } else {
+                self.fail("Invalid UTF-8".to_string())
+            };
         }
+        self.incomplete.extend_from_slice(buffer);
+        let length = buffer.len();
+        self.read.consume(length);
 
-        let position = self.char_iterator.peek().unwrap().0;
-        Some(
-            match self.token_generator.read_next_token(&mut self.char_iterator) {
-                Ok(token) => Ok(TokenWithPos{token, position}),
-                Err(err) => Err(err),
+        let valid_length = match str::from_utf8(&self.incomplete) {
+            Ok(_) => self.incomplete.len(),
+            // an invalid sequence, as opposed to an incomplete sequence at the end of the bytes
+            Err(err) if err.error_len().is_some() => return self.fail("Invalid UTF-8".to_string()),
+            Err(err) => err.valid_up_to(),
+        };
+        let bytes: Vec<u8> = self.incomplete.drain(..valid_length).collect();
+        self.chars.extend(str::from_utf8(&bytes).expect("valid UTF-8").chars());
+        true
+    }
+
+    fn fail(&mut self, message: String) -> bool {
+        self.error.replace(Some(TokenError { message, position: self.position }));
+        self.done = true;
+        false
+    }
+}
+
+impl<R: BufRead> Iterator for ReadChars<R> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.chars.is_empty() {
+            if !self.fill_chars() {
+                return None;
             }
-        )
+        }
+        let position = self.position;
+        self.position += 1;
+        self.chars.pop_front().map(|c| (position, c))
     }
 }
 
-// pub fn tokenize<G, T, R>(read: R, tokenizer: T) -> impl Iterator<Item=io::Result<T>>
-//     where T: Token, G: TokenGenerator<T>, R: BufRead {
-//     read.has_data_left()
-//     i64::from_str()
-// }
+#[cfg(test)]
+mod tests {
+    use crate::token::{tokenize, TokenIterator, TokenWithPos, TokenError, TokenResult, TokenStream};
+    use crate::token::intring::IntRingTokenParser;
+    use crate::token::intring::IntegerToken::{DecimalInteger, PlusSign, MultiplicationSign, LeftParenthesis, RightParenthesis};
+    use std::io::{BufReader, Cursor};
 
+    #[test]
+    fn tokenize_cursor() {
+        let tokens: Vec<_> = tokenize(Cursor::new("12 + 345 * (6)"), IntRingTokenParser::new()).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: DecimalInteger(12), position: 0, end: 2}),
+            Ok(TokenWithPos{token: PlusSign, position: 3, end: 4}),
+            Ok(TokenWithPos{token: DecimalInteger(345), position: 5, end: 8}),
+            Ok(TokenWithPos{token: MultiplicationSign, position: 9, end: 10}),
+            Ok(TokenWithPos{token: LeftParenthesis, position: 11, end: 12}),
+            Ok(TokenWithPos{token: DecimalInteger(6), position: 12, end: 13}),
+            Ok(TokenWithPos{token: RightParenthesis, position: 13, end: 14})], tokens);
+    }
+
+    #[test]
+    fn tokenize_tiny_chunks() {
+        let str = "1234567890 mod 7 + ( 42 ) × 3";
+        let read = BufReader::with_capacity(1, str.as_bytes());
+
+        // the iterators do not advance past an invalid token, so only take up to the error
+        let tokens: Vec<TokenResult<_>> = tokenize(read, IntRingTokenParser::new()).take(8).collect();
+
+        let expected: Vec<TokenResult<_>> = TokenIterator::new(&str, IntRingTokenParser::new()).take(8).collect();
+        assert_eq!(expected, tokens);
+        assert_eq!(Some(&Ok(TokenWithPos{token: DecimalInteger(1234567890), position: 0, end: 10})), tokens.first());
+        assert_eq!(Some(&Err(TokenError{message: "Unexpected '×'; expected a digit, a letter, an operator or a parenthesis".to_string(), position: 26})),
+                   tokens.get(7));
+    }
+
+    #[test]
+    fn token_stream_peek_next() {
+        let mut stream = TokenIterator::new(&"1 + $", IntRingTokenParser::new()).peekable_tokens();
+
+        assert_eq!(Some(&Ok(TokenWithPos{token: DecimalInteger(1), position: 0, end: 1})), stream.peek());
+        assert_eq!(Some(&Ok(TokenWithPos{token: DecimalInteger(1), position: 0, end: 1})), stream.peek());
+        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(1), position: 0, end: 1})), stream.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: PlusSign, position: 2, end: 3})), stream.next());
+        let error = Err(TokenError{message: "Unexpected '$'; expected a digit, a letter, an operator or a parenthesis".to_string(), position: 4});
+        assert_eq!(Some(&error), stream.peek());
+        assert_eq!(Some(error), stream.next());
+    }
+
+    #[test]
+    fn token_stream_push_back() {
+        let mut stream = TokenStream::new(tokenize(Cursor::new("1 +"), IntRingTokenParser::new()));
+
+        let one = stream.next().expect("some");
+        let plus = stream.next().expect("some");
+        stream.push_back(plus.clone());
+        stream.push_back(one.clone());
+
+        assert_eq!(Some(&one), stream.peek());
+        assert_eq!(vec![one, plus], stream.by_ref().collect::<Vec<_>>());
+        assert_eq!(None, stream.peek());
+        assert_eq!(None, stream.next());
+    }
+
+    #[test]
+    fn tokenize_invalid_utf8() {
+        let read = BufReader::with_capacity(1, &[b'1', b'2', b'+', 0xff, b'3'][..]);
+
+        let tokens: Vec<_> = tokenize(read, IntRingTokenParser::new()).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: DecimalInteger(12), position: 0, end: 2}),
+            Ok(TokenWithPos{token: PlusSign, position: 2, end: 3}),
+            Err(TokenError{message: "Invalid UTF-8".to_string(), position: 3})], tokens);
+    }
+
+    #[test]
+    fn tokenize_truncated_utf8() {
+        let tokens: Vec<_> = tokenize(Cursor::new(&"7 ×".as_bytes()[..3]), IntRingTokenParser::new()).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: DecimalInteger(7), position: 0, end: 1}),
+            Err(TokenError{message: "Invalid UTF-8".to_string(), position: 2})], tokens);
+    }
+}
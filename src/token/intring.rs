@@ -1,58 +1,186 @@
-use crate::token::{Token, TokenParser, TokenResult, TokenError};
+use crate::token::{Token, TokenParser, TokenResult, TokenError, TokenWithPos};
 use std::iter::Peekable;
-use crate::token::intring::IntRingToken::{LeftParenthesis, MultiplicationSign, MinusSign, PlusSign, RightParenthesis, DecimalInteger, Modulo, DivisionSign};
+use std::marker::PhantomData;
+use std::str::FromStr;
+use crate::token::intring::IntegerToken::{LeftParenthesis, MultiplicationSign, MinusSign, PlusSign, RightParenthesis, DecimalInteger, BigDecimalInteger, DecimalFraction, Identifier, FunctionName, Comma, Modulo, DivisionSign, CaretSign, Percent, LessThanSign, GreaterThanSign, EqualsSign, QuestionMark, Colon};
 use std::fmt::{Display, Formatter, Write};
+use std::hash::Hash;
 
+/// Token of an integer expression, with integer literals of type `T`.
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
-pub enum IntRingToken {
+pub enum IntegerToken<T> {
     LeftParenthesis,
     RightParenthesis,
     PlusSign,
     MinusSign,
     MultiplicationSign,
     DivisionSign,
-    DecimalInteger(i64),
-    Modulo
+    /// `^`, exponentiation.
+    CaretSign,
+    LessThanSign,
+    GreaterThanSign,
+    /// `==`, comparison for equality.
+    EqualsSign,
+    /// `?`, between the condition and the first branch of a conditional.
+    QuestionMark,
+    /// `:`, between the branches of a conditional.
+    Colon,
+    DecimalInteger(T),
+    /// Decimal integer too big for [DecimalInteger], kept as written. Only produced with big
+    /// integers enabled, see [IntegerTokenParser::with_big_integers].
+    BigDecimalInteger(String),
+    /// Decimal number with a fractional part, e.g. `6.5`. Kept as written, since whether it is
+    /// valid depends on the ring the expression is parsed in.
+    DecimalFraction(String),
+    /// Name of a variable, a word of letters other than `mod`, e.g. `x`.
+    Identifier(String),
+    /// Name of a function, a word of letters directly followed by `(`, e.g. `abs` in `abs(-3)`.
+    FunctionName(String),
+    /// `,`, separating the arguments of a function call.
+    Comma,
+    Modulo,
+    /// Postfix `%`, dividing the preceding operand by 100. Only produced in calculator mode, see
+    /// [IntegerTokenParser::with_calculator_mode].
+    Percent,
 }
 
-impl Display for IntRingToken {
+/// Token of an [IntRing](crate::expression::ring::intring::IntRing) expression.
+pub type IntRingToken = IntegerToken<i64>;
+
+impl<T: Display> Display for IntegerToken<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            IntRingToken::LeftParenthesis => f.write_char('(')?,
-            IntRingToken::RightParenthesis => f.write_char(')')?,
-            IntRingToken::PlusSign => f.write_char('+')?,
-            IntRingToken::MinusSign => f.write_char('-')?,
-            IntRingToken::MultiplicationSign => f.write_char('*')?,
-            IntRingToken::DivisionSign => f.write_char('/')?,
-            IntRingToken::DecimalInteger(d) => write!(f, "{}", d)?,
-            IntRingToken::Modulo => f.write_str("mod")?,
+            IntegerToken::LeftParenthesis => f.write_char('(')?,
+            IntegerToken::RightParenthesis => f.write_char(')')?,
+            IntegerToken::PlusSign => f.write_char('+')?,
+            IntegerToken::MinusSign => f.write_char('-')?,
+            IntegerToken::MultiplicationSign => f.write_char('*')?,
+            IntegerToken::DivisionSign => f.write_char('/')?,
+            IntegerToken::CaretSign => f.write_char('^')?,
+            IntegerToken::LessThanSign => f.write_char('<')?,
+            IntegerToken::GreaterThanSign => f.write_char('>')?,
+            IntegerToken::EqualsSign => f.write_str("==")?,
+            IntegerToken::QuestionMark => f.write_char('?')?,
+            IntegerToken::Colon => f.write_char(':')?,
+            IntegerToken::DecimalInteger(d) => write!(f, "{}", d)?,
+            IntegerToken::BigDecimalInteger(str) | IntegerToken::DecimalFraction(str) => f.write_str(str)?,
+            IntegerToken::Identifier(name) | IntegerToken::FunctionName(name) => f.write_str(name)?,
+            IntegerToken::Comma => f.write_char(',')?,
+            IntegerToken::Modulo => f.write_str("mod")?,
+            IntegerToken::Percent => f.write_char('%')?,
         };
         Ok(())
     }
 }
 
-impl Token for IntRingToken {
+impl<T: Display + Eq + Hash + Clone> Token for IntegerToken<T> {
 
 }
 
-pub struct IntRingTokenParser {
+/// Kind of [IntegerToken], disregarding the specific operator or value.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum IntRingTokenKind {
+    Value,
+    Operator,
+    Parenthesis,
 }
 
-impl IntRingTokenParser {
-    pub fn new() -> IntRingTokenParser {
-        IntRingTokenParser{}
+impl<T> IntegerToken<T> {
+    pub fn kind(&self) -> IntRingTokenKind {
+        match self {
+            LeftParenthesis | RightParenthesis => IntRingTokenKind::Parenthesis,
+            PlusSign | MinusSign | MultiplicationSign | DivisionSign | CaretSign | LessThanSign | GreaterThanSign | EqualsSign
+            | QuestionMark | Colon | Modulo | Percent | FunctionName(_) | Comma => IntRingTokenKind::Operator,
+            DecimalInteger(_) | BigDecimalInteger(_) | DecimalFraction(_) | Identifier(_) => IntRingTokenKind::Value,
+        }
+    }
+}
+
+/// Number of tokens in a token sequence, in total and by kind.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Hash)]
+pub struct TokenStats {
+    pub tokens: usize,
+    pub values: usize,
+    pub operators: usize,
+    pub parentheses: usize,
+}
+
+/// Count the tokens in `tokens` by kind.
+pub fn token_stats(tokens: &[TokenWithPos<IntRingToken>]) -> TokenStats {
+    let mut stats = TokenStats::default();
+    for token in tokens {
+        stats.tokens += 1;
+        match token.token.kind() {
+            IntRingTokenKind::Value => stats.values += 1,
+            IntRingTokenKind::Operator => stats.operators += 1,
+            IntRingTokenKind::Parenthesis => stats.parentheses += 1,
+        }
     }
+    stats
 }
 
-impl TokenParser for IntRingTokenParser {
-    type TokenType = IntRingToken;
+/// Parses [IntegerToken]s with integer literals of type `T`. A literal is too big if `T::from_str`
+/// fails on its digits.
+pub struct IntegerTokenParser<T> {
+    calculator_mode: bool,
+    big_integers: bool,
+    integer_type: PhantomData<T>,
+}
+
+/// Parses [IntRingToken]s.
+pub type IntRingTokenParser = IntegerTokenParser<i64>;
+
+impl<T> Default for IntegerTokenParser<T> {
+    fn default() -> Self {
+        IntegerTokenParser::new()
+    }
+}
+
+impl<T> IntegerTokenParser<T> {
+    pub fn new() -> IntegerTokenParser<T> {
+        IntegerTokenParser{
+            calculator_mode: false,
+            big_integers: false,
+            integer_type: PhantomData,
+        }
+    }
+
+    /// Set calculator mode, in which `%` is read as a postfix [Percent] token, so `50%` means
+    /// `50 / 100`. Outside calculator mode `%` is not a token. The modulo operator is always
+    /// written `mod`, so `%` never means modulo.
+    pub fn with_calculator_mode(mut self, calculator_mode: bool) -> IntegerTokenParser<T> {
+        self.calculator_mode = calculator_mode;
+        self
+    }
+
+    /// Set whether integers too big for `T` are read as [BigDecimalInteger] tokens instead of
+    /// failing, for rings of integers of arbitrary size.
+    pub fn with_big_integers(mut self, big_integers: bool) -> IntegerTokenParser<T> {
+        self.big_integers = big_integers;
+        self
+    }
+
+    /// Description of the tokens accepted by the parser, used in error messages.
+    pub fn expected_description(&self) -> &str {
+        if self.calculator_mode {
+            "a digit, a letter, an operator, a parenthesis or %"
+        } else {
+            "a digit, a letter, an operator or a parenthesis"
+        }
+    }
+}
+
+impl<T: FromStr + Display + Eq + Hash + Clone> TokenParser for IntegerTokenParser<T> {
+    type TokenType = IntegerToken<T>;
 
     fn read_next_token<I: Iterator<Item=(usize, char)>>(
         &self, char_iterator: &mut Peekable<I>) -> TokenResult<Self::TokenType>
     {
-        fn invalid_token_result(pos: usize) -> TokenResult<IntRingToken> {
-            Err(TokenError{message: format!("Invalid token"), position: pos})
-        }
+        let invalid_token_result = |pos: usize, str: &str| -> TokenResult<IntegerToken<T>> {
+            Err(TokenError{
+                message: format!("Unexpected '{}'; expected {}", str, self.expected_description()),
+                position: pos})
+        };
 
         match char_iterator.peek().copied().unwrap() {
             (_, '(') => {char_iterator.next(); Ok(LeftParenthesis)},
@@ -61,14 +189,34 @@ impl TokenParser for IntRingTokenParser {
             (_, '-') => {char_iterator.next(); Ok(MinusSign)},
             (_, '*') => {char_iterator.next(); Ok(MultiplicationSign)},
             (_, '/') => {char_iterator.next(); Ok(DivisionSign)},
-            (pos, 'm') => {
-                let str: String = char_iterator.take(3).map(|(_, c)| c).collect();
-                if str == "mod" {
+            (_, '^') => {char_iterator.next(); Ok(CaretSign)},
+            (_, '<') => {char_iterator.next(); Ok(LessThanSign)},
+            (_, '>') => {char_iterator.next(); Ok(GreaterThanSign)},
+            (pos, '=') => {
+                char_iterator.next();
+                match char_iterator.next_if(|(_, c)| *c == '=') {
+                    Some(_) => Ok(EqualsSign),
+                    None => invalid_token_result(pos, "="),
+                }
+            },
+            (_, ',') => {char_iterator.next(); Ok(Comma)},
+            (_, '?') => {char_iterator.next(); Ok(QuestionMark)},
+            (_, ':') => {char_iterator.next(); Ok(Colon)},
+            (_, '%') if self.calculator_mode => {char_iterator.next(); Ok(Percent)},
+            (_, c) if c.is_alphabetic() => {
+                // consume the word but nothing after it, such that the next token starts right
+                // after the word, e.g. `mod3` is modulo followed by 3
+                let mut word = String::new();
+                while let Some((_, c)) = char_iterator.next_if(|(_, c)| c.is_alphabetic()) {
+                    word.push(c);
+                }
+                if word == "mod" {
                     Ok(Modulo)
+                } else if char_iterator.peek().is_some_and(|(_, c)| *c == '(') {
+                    Ok(FunctionName(word))
                 } else {
-                    invalid_token_result(pos)
+                    Ok(Identifier(word))
                 }
-
             },
             (pos, c) if c.is_numeric() => {
                 let mut decimals = String::new();
@@ -76,33 +224,61 @@ impl TokenParser for IntRingTokenParser {
                 //     char_iterator.next();
                 //     decimal.push(c);
                 // }
-                while let Some((_, c)) = char_iterator.next_if(|(_, c)| c.is_numeric()) {
+                while let Some((_, c)) = char_iterator.next_if(|(_, c)| c.is_numeric() || *c == '.' || *c == '_') {
                     decimals.push(c);
                 }
-                let parse_result = decimals.parse();
-                match parse_result {
-                    Ok(d) => Ok(DecimalInteger(d)),
-                    Err(_) => Err(TokenError{message: "Decimal number too big".to_string(), position: pos}),
+                let decimals = strip_digit_separators(&decimals).ok_or_else(|| TokenError {
+                    message: format!("Misplaced digit separator in '{}'", decimals),
+                    position: pos,
+                })?;
+                if decimals.contains('.') {
+                    return Ok(DecimalFraction(decimals));
+                }
+                if let Ok(value) = decimals.parse() {
+                    return Ok(DecimalInteger(value));
+                }
+                // digits that do not parse are too many for `T`, other numeric characters are invalid
+                if !decimals.chars().all(|c| c.is_ascii_digit()) {
+                    Err(TokenError{message: format!("Invalid integer '{}'", decimals), position: pos})
+                } else if self.big_integers {
+                    Ok(BigDecimalInteger(decimals))
+                } else {
+                    Err(TokenError{message: "Decimal number too big".to_string(), position: pos})
                 }
             }
-            (pos, _) => invalid_token_result(pos)
+            (pos, c) => invalid_token_result(pos, &c.to_string())
         }
     }
+
+    /// `#` starts a comment, e.g. `2 + 3 # add them`.
+    fn comment_start(&self) -> Option<char> {
+        Some('#')
+    }
+}
+
+/// Remove `_` digit separators from the literal `decimals`, e.g. `1_000` is `1000`. Returns `None`
+/// unless every separator is between two digits.
+fn strip_digit_separators(decimals: &str) -> Option<String> {
+    let chars: Vec<char> = decimals.chars().collect();
+    let separators_valid = chars.iter().enumerate()
+        .filter(|(_, c)| **c == '_')
+        .all(|(i, _)| i > 0 && chars[i - 1].is_numeric() && chars.get(i + 1).is_some_and(|c| c.is_numeric()));
+    separators_valid.then(|| chars.into_iter().filter(|c| *c != '_').collect())
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::token::{TokenIterator, TokenWithPos};
-    use crate::token::intring::IntRingTokenParser;
-    use crate::token::intring::IntRingToken::{LeftParenthesis, RightParenthesis, PlusSign, MinusSign, MultiplicationSign, DecimalInteger, Modulo, DivisionSign};
+    use crate::token::{TokenIterator, TokenWithPos, TokenError};
+    use crate::token::intring::{IntegerTokenParser, IntRingTokenParser, token_stats, TokenStats};
+    use crate::token::intring::IntegerToken::{LeftParenthesis, RightParenthesis, PlusSign, MinusSign, MultiplicationSign, DecimalInteger, BigDecimalInteger, DecimalFraction, Identifier, FunctionName, Comma, Modulo, DivisionSign, CaretSign, Percent, LessThanSign, GreaterThanSign, EqualsSign, QuestionMark, Colon};
 
     #[test]
     fn parse_single_token() {
         let str = "(";
         let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
 
-        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 0})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 0, end: 1})), iter.next());
         assert_eq!(None, iter.next());
     }
 
@@ -111,7 +287,7 @@ mod tests {
         let str = "(".to_string();
         let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
 
-        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 0})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 0, end: 1})), iter.next());
         assert_eq!(None, iter.next());
     }
 
@@ -120,8 +296,8 @@ mod tests {
         let str = "((";
         let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
 
-        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 0})), iter.next());
-        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 1})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 0, end: 1})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 1, end: 2})), iter.next());
         assert_eq!(None, iter.next());
     }
 
@@ -130,46 +306,195 @@ mod tests {
         let str = "  (  (  ";
         let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
 
-        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 2})), iter.next());
-        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 5})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 2, end: 3})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 5, end: 6})), iter.next());
         assert_eq!(None, iter.next());
     }
 
     #[test]
     fn parse_parentheses_and_operators() {
-        let str = "()+-*/";
+        let str = "()+-*/^";
         let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
 
-        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 0})), iter.next());
-        assert_eq!(Some(Ok(TokenWithPos{token: RightParenthesis, position: 1})), iter.next());
-        assert_eq!(Some(Ok(TokenWithPos{token: PlusSign, position: 2})), iter.next());
-        assert_eq!(Some(Ok(TokenWithPos{token: MinusSign, position: 3})), iter.next());
-        assert_eq!(Some(Ok(TokenWithPos{token: MultiplicationSign, position: 4})), iter.next());
-        assert_eq!(Some(Ok(TokenWithPos{token: DivisionSign, position: 5})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 0, end: 1})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: RightParenthesis, position: 1, end: 2})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: PlusSign, position: 2, end: 3})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: MinusSign, position: 3, end: 4})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: MultiplicationSign, position: 4, end: 5})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: DivisionSign, position: 5, end: 6})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: CaretSign, position: 6, end: 7})), iter.next());
         assert_eq!(None, iter.next());
     }
 
+    #[test]
+    fn parse_comparisons() {
+        let tokens: Vec<_> = TokenIterator::new(&"1<2>3==4", IntRingTokenParser::new()).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: DecimalInteger(1), position: 0, end: 1}),
+            Ok(TokenWithPos{token: LessThanSign, position: 1, end: 2}),
+            Ok(TokenWithPos{token: DecimalInteger(2), position: 2, end: 3}),
+            Ok(TokenWithPos{token: GreaterThanSign, position: 3, end: 4}),
+            Ok(TokenWithPos{token: DecimalInteger(3), position: 4, end: 5}),
+            Ok(TokenWithPos{token: EqualsSign, position: 5, end: 7}),
+            Ok(TokenWithPos{token: DecimalInteger(4), position: 7, end: 8})], tokens);
+    }
+
+    #[test]
+    fn parse_conditional() {
+        let tokens: Vec<_> = TokenIterator::new(&"1?2:3", IntRingTokenParser::new()).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: DecimalInteger(1), position: 0, end: 1}),
+            Ok(TokenWithPos{token: QuestionMark, position: 1, end: 2}),
+            Ok(TokenWithPos{token: DecimalInteger(2), position: 2, end: 3}),
+            Ok(TokenWithPos{token: Colon, position: 3, end: 4}),
+            Ok(TokenWithPos{token: DecimalInteger(3), position: 4, end: 5})], tokens);
+    }
+
+    #[test]
+    fn single_equals_sign_not_token() {
+        let err = TokenIterator::new(&"= 2", IntRingTokenParser::new()).next().unwrap().expect_err("should be error");
+
+        assert_eq!(0, err.position);
+        assert_eq!("Unexpected '='; expected a digit, a letter, an operator or a parenthesis", err.message);
+    }
+
     #[test]
     fn parse_modulo() {
         let str = "5 mod 7";
         let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
 
-        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(5), position: 0})), iter.next());
-        assert_eq!(Some(Ok(TokenWithPos{token: Modulo, position: 2})), iter.next());
-        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(7), position: 6})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(5), position: 0, end: 1})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: Modulo, position: 2, end: 5})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(7), position: 6, end: 7})), iter.next());
         assert_eq!(None, iter.next());
     }
 
     #[test]
-    fn invalid_token_starting_with_m() {
-        let str = "5 mm";
+    fn parse_percent_calculator_mode() {
+        let str = "50%";
+        let mut iter = TokenIterator::new(&str, IntRingTokenParser::new().with_calculator_mode(true));
+
+        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(50), position: 0, end: 2})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: Percent, position: 2, end: 3})), iter.next());
+        assert_eq!(None, iter.next());
+    }
+
+    #[test]
+    fn percent_not_token_outside_calculator_mode() {
+        let str = "50%";
         let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
 
-        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(5), position: 0})), iter.next());
-        let token_result = iter.next().unwrap();
-        let err = token_result.expect_err("should be error");
+        iter.next().unwrap().unwrap();
+        let err = iter.next().unwrap().expect_err("should be error");
         assert_eq!(2, err.position);
-        assert_eq!("Invalid token", err.message);
+        assert_eq!("Unexpected '%'; expected a digit, a letter, an operator or a parenthesis", err.message);
+    }
+
+    #[test]
+    fn identifiers() {
+        let tokens: Vec<_> = TokenIterator::new(&"x + mo*3 modx", IntRingTokenParser::new()).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: Identifier("x".to_string()), position: 0, end: 1}),
+            Ok(TokenWithPos{token: PlusSign, position: 2, end: 3}),
+            Ok(TokenWithPos{token: Identifier("mo".to_string()), position: 4, end: 6}),
+            Ok(TokenWithPos{token: MultiplicationSign, position: 6, end: 7}),
+            Ok(TokenWithPos{token: DecimalInteger(3), position: 7, end: 8}),
+            Ok(TokenWithPos{token: Identifier("modx".to_string()), position: 9, end: 13})], tokens);
+    }
+
+    #[test]
+    fn function_names() {
+        let tokens: Vec<_> = TokenIterator::new(&"gcd(1, x) abs (2)", IntRingTokenParser::new()).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: FunctionName("gcd".to_string()), position: 0, end: 3}),
+            Ok(TokenWithPos{token: LeftParenthesis, position: 3, end: 4}),
+            Ok(TokenWithPos{token: DecimalInteger(1), position: 4, end: 5}),
+            Ok(TokenWithPos{token: Comma, position: 5, end: 6}),
+            Ok(TokenWithPos{token: Identifier("x".to_string()), position: 7, end: 8}),
+            Ok(TokenWithPos{token: RightParenthesis, position: 8, end: 9}),
+            Ok(TokenWithPos{token: Identifier("abs".to_string()), position: 10, end: 13}),
+            Ok(TokenWithPos{token: LeftParenthesis, position: 14, end: 15}),
+            Ok(TokenWithPos{token: DecimalInteger(2), position: 15, end: 16}),
+            Ok(TokenWithPos{token: RightParenthesis, position: 16, end: 17})], tokens);
+    }
+
+    #[test]
+    fn mod_followed_by_digit() {
+        let tokens: Vec<_> = TokenIterator::new(&"7 mod3", IntRingTokenParser::new()).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: DecimalInteger(7), position: 0, end: 1}),
+            Ok(TokenWithPos{token: Modulo, position: 2, end: 5}),
+            Ok(TokenWithPos{token: DecimalInteger(3), position: 5, end: 6})], tokens);
+    }
+
+    #[test]
+    fn parse_decimal_fraction() {
+        let tokens: Vec<_> = TokenIterator::new(&"6.5+1", IntRingTokenParser::new()).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: DecimalFraction("6.5".to_string()), position: 0, end: 3}),
+            Ok(TokenWithPos{token: PlusSign, position: 3, end: 4}),
+            Ok(TokenWithPos{token: DecimalInteger(1), position: 4, end: 5})], tokens);
+    }
+
+    #[test]
+    fn parse_digit_separators() {
+        let tokens: Vec<_> = TokenIterator::new(&"1_000 + 2_500_000", IntRingTokenParser::new()).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: DecimalInteger(1000), position: 0, end: 5}),
+            Ok(TokenWithPos{token: PlusSign, position: 6, end: 7}),
+            Ok(TokenWithPos{token: DecimalInteger(2500000), position: 8, end: 17})], tokens);
+    }
+
+    #[test]
+    fn spans() {
+        let spans: Vec<_> = TokenIterator::new(&"123 mod (4*56) - 7", IntRingTokenParser::new())
+            .map(|token| token.expect("ok").span())
+            .collect();
+
+        assert_eq!(vec![0..3, 4..7, 8..9, 9..10, 10..11, 11..13, 13..14, 15..16, 17..18], spans);
+    }
+
+    #[test]
+    fn span_at_end_of_input() {
+        let token = TokenIterator::new(&"  42", IntRingTokenParser::new()).next().expect("token").expect("ok");
+
+        assert_eq!(2..4, token.span());
+    }
+
+    #[test]
+    fn leading_digit_separator() {
+        let err = TokenIterator::new(&"_5", IntRingTokenParser::new()).next().unwrap().expect_err("should be error");
+
+        assert_eq!(0, err.position);
+        assert_eq!("Unexpected '_'; expected a digit, a letter, an operator or a parenthesis", err.message);
+    }
+
+    #[test]
+    fn misplaced_digit_separators() {
+        for str in ["2 + 5_", "2 + 5__0"] {
+            let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
+            iter.next();
+            iter.next();
+
+            let err = iter.next().unwrap().expect_err("should be error");
+            assert_eq!(4, err.position);
+            assert_eq!(format!("Misplaced digit separator in '{}'", &str[4..]), err.message);
+        }
+    }
+
+    #[test]
+    fn digit_separators_too_big() {
+        let err = TokenIterator::new(&"9_223_372_036_854_775_808", IntRingTokenParser::new()).next().unwrap().expect_err("should be error");
+
+        assert_eq!(0, err.position);
+        assert_eq!("Decimal number too big", err.message);
     }
 
     #[test]
@@ -177,13 +502,13 @@ mod tests {
         let str = "1234567890";
         let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
 
-        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(1234567890), position: 0})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(1234567890), position: 0, end: 10})), iter.next());
         assert_eq!(None, iter.next());
 
         let str = "91";
         let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
 
-        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(91), position: 0})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(91), position: 0, end: 2})), iter.next());
         assert_eq!(None, iter.next());
     }
 
@@ -192,9 +517,9 @@ mod tests {
         let str = "(12)";
         let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
 
-        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 0})), iter.next());
-        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(12), position: 1})), iter.next());
-        assert_eq!(Some(Ok(TokenWithPos{token: RightParenthesis, position: 3})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: LeftParenthesis, position: 0, end: 1})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(12), position: 1, end: 3})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: RightParenthesis, position: 3, end: 4})), iter.next());
         assert_eq!(None, iter.next());
     }
 
@@ -203,7 +528,7 @@ mod tests {
         let str = "  12  ";
         let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
 
-        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(12), position: 2})), iter.next());
+        assert_eq!(Some(Ok(TokenWithPos{token: DecimalInteger(12), position: 2, end: 4})), iter.next());
         assert_eq!(None, iter.next());
     }
 
@@ -221,9 +546,30 @@ mod tests {
 
     }
 
+    #[test]
+    fn parse_big_int_token() {
+        let str = "12312312312312123123123123123 + 9_223_372_036_854_775_807";
+        let tokens: Vec<_> = TokenIterator::new(&str, IntRingTokenParser::new().with_big_integers(true)).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: BigDecimalInteger("12312312312312123123123123123".to_string()), position: 0, end: 29}),
+            Ok(TokenWithPos{token: PlusSign, position: 30, end: 31}),
+            Ok(TokenWithPos{token: DecimalInteger(i64::MAX), position: 32, end: 57})], tokens);
+    }
+
+    #[test]
+    fn parse_u8_token() {
+        let tokens: Vec<_> = TokenIterator::new(&"200 + 300", IntegerTokenParser::<u8>::new()).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: DecimalInteger(200), position: 0, end: 3}),
+            Ok(TokenWithPos{token: PlusSign, position: 4, end: 5}),
+            Err(TokenError{message: "Decimal number too big".to_string(), position: 6})], tokens);
+    }
+
     #[test]
     fn chars_not_token() {
-        let str = "() hest 2";
+        let str = "() $ 2";
         let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
 
         iter.next().unwrap().unwrap();
@@ -231,16 +577,79 @@ mod tests {
         let token_result = iter.next().unwrap();
         let err = token_result.expect_err("should be error");
         assert_eq!(3, err.position);
-        assert_eq!("Invalid token", err.message);
+        assert_eq!("Unexpected '$'; expected a digit, a letter, an operator or a parenthesis", err.message);
+    }
+
+    #[test]
+    fn invalid_token_in_word() {
+        let tokens: Vec<_> = TokenIterator::new(&"() hest h€st", IntRingTokenParser::new()).take(5).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: LeftParenthesis, position: 0, end: 1}),
+            Ok(TokenWithPos{token: RightParenthesis, position: 1, end: 2}),
+            Ok(TokenWithPos{token: Identifier("hest".to_string()), position: 3, end: 7}),
+            Ok(TokenWithPos{token: Identifier("h".to_string()), position: 8, end: 9}),
+            Err(TokenError{message: "Unexpected '€'; expected a digit, a letter, an operator or a parenthesis".to_string(), position: 9})], tokens);
+    }
+
+    #[test]
+    fn trailing_comment() {
+        let tokens: Vec<_> = TokenIterator::new(&"2 + 3 # add them", IntRingTokenParser::new()).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: DecimalInteger(2), position: 0, end: 1}),
+            Ok(TokenWithPos{token: PlusSign, position: 2, end: 3}),
+            Ok(TokenWithPos{token: DecimalInteger(3), position: 4, end: 5})], tokens);
+    }
+
+    #[test]
+    fn comment_only_line() {
+        let tokens: Vec<_> = TokenIterator::new(&"2 +\n# the second term\n  #\n3#", IntRingTokenParser::new()).collect();
+
+        assert_eq!(vec![
+            Ok(TokenWithPos{token: DecimalInteger(2), position: 0, end: 1}),
+            Ok(TokenWithPos{token: PlusSign, position: 2, end: 3}),
+            Ok(TokenWithPos{token: DecimalInteger(3), position: 26, end: 27})], tokens);
+        assert_eq!(0, TokenIterator::new(&"# nothing but a comment", IntRingTokenParser::new()).count());
+    }
+
+    #[test]
+    fn comment_in_invalid_token() {
+        let tokens: Vec<_> = TokenIterator::new(&"5#$ not a token", IntRingTokenParser::new()).collect();
+        assert_eq!(vec![Ok(TokenWithPos{token: DecimalInteger(5), position: 0, end: 1})], tokens);
+
+        let mut iter = TokenIterator::new(&"5 $#$", IntRingTokenParser::new());
+        iter.next().unwrap().unwrap();
+        let err = iter.next().unwrap().expect_err("should be error");
+        assert_eq!(2, err.position);
     }
 
     #[test]
     fn display() {
         let str = "()+-*/123mod";
-        let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
+        let iter = TokenIterator::new(&str, IntRingTokenParser::new());
 
-        while let Some(token_result) = iter.next() {
+        for token_result in iter {
             println!("{}", token_result.unwrap().token);
         }
     }
+
+    #[test]
+    fn invalid_token_message() {
+        let str = "5 $";
+        let mut iter = TokenIterator::new(&str, IntRingTokenParser::new());
+
+        iter.next().unwrap().unwrap();
+        let err = iter.next().unwrap().expect_err("should be error");
+        assert_eq!(2, err.position);
+        assert_eq!("Unexpected '$'; expected a digit, a letter, an operator or a parenthesis", err.message);
+    }
+
+    #[test]
+    fn stats() {
+        let str = "12 + (3 * 4)";
+        let tokens: Vec<_> = TokenIterator::new(&str, IntRingTokenParser::new()).collect::<Result<_, _>>().expect("ok");
+
+        assert_eq!(TokenStats { tokens: 7, values: 3, operators: 2, parentheses: 2 }, token_stats(&tokens));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,50 @@
+use aritexpr::expression::parser::parse_int_ring_expression;
+use aritexpr::expression::ring::intring::IntRingElement;
+use std::time::{Duration, Instant};
+
+/// Number of terms in the wide part of the fixture.
+const TERMS: i64 = 50_000;
+
+/// Nesting depth of the deep part of the fixture.
+const NESTING: usize = 10_000;
+
+/// Bound on parsing and evaluating the fixture in optimized builds. Far above the expected time,
+/// such that only a major regression, e.g. quadratic behavior, fails the test. Unoptimized builds
+/// are not timed, as their speed varies too much with the machine.
+const TIME_LIMIT: Duration = Duration::from_secs(5);
+
+/// Large expression with a wide sum of terms followed by deeply nested subtractions, and its value.
+fn fixture() -> (String, i64) {
+    let mut str = String::new();
+    let mut value = 0;
+    for i in 0..TERMS {
+        if i > 0 {
+            str.push_str(if i % 2 == 0 { " + " } else { " - " });
+        }
+        str.push_str(&format!("({} mod 7 + 1) * 2 ^ 2 / 4", i));
+        let term = i % 7 + 1;
+        value += if i % 2 == 1 { -term } else { term };
+    }
+    // 1 - (1 - (1 - ... (1 - 1)...)) is 1 for an even number of subtractions and 0 otherwise
+    str.push_str(" + ");
+    str.push_str(&"(1 - ".repeat(NESTING));
+    str.push('1');
+    str.push_str(&")".repeat(NESTING));
+    value += (NESTING as i64 + 1) % 2;
+    (str, value)
+}
+
+#[test]
+fn parse_and_evaluate_large_expression() {
+    let (str, value) = fixture();
+
+    let start = Instant::now();
+    let expression = parse_int_ring_expression(&str).expect("ok");
+    let result = expression.evaluate();
+    let elapsed = start.elapsed();
+
+    assert_eq!(Ok(IntRingElement::new(value)), result);
+    if cfg!(not(debug_assertions)) {
+        assert!(elapsed < TIME_LIMIT, "Parsing and evaluating {} bytes took {:?}, limit is {:?}", str.len(), elapsed, TIME_LIMIT);
+    }
+}